@@ -0,0 +1,206 @@
+use std::path::Path;
+
+// Values longer than this are cut short in the listing
+const MAX_VALUE_BYTES: usize = 64;
+
+// lsattr's letters, as documented in chattr(1)
+#[cfg(target_os = "linux")]
+const LINUX_FLAGS: &[(char, &str)] = &[
+    ('a', "append only"),
+    ('A', "no atime updates"),
+    ('c', "compressed"),
+    ('C', "no copy on write"),
+    ('d', "no dump"),
+    ('D', "synchronous directory updates"),
+    ('e', "extent format"),
+    ('E', "encrypted"),
+    ('F', "case-insensitive directory"),
+    ('i', "immutable"),
+    ('I', "indexed directory"),
+    ('j', "data journalling"),
+    ('m', "don't compress"),
+    ('N', "inline data"),
+    ('P', "project hierarchy"),
+    ('s', "secure deletion"),
+    ('S', "synchronous updates"),
+    ('t', "no tail-merging"),
+    ('T', "top of directory hierarchy"),
+    ('u', "undeletable"),
+    ('V', "verity"),
+    ('x', "direct access"),
+];
+
+#[cfg(target_os = "macos")]
+const MACOS_FLAGS: &[(u32, &str)] = &[
+    (0x1, "no dump"),
+    (0x2, "immutable"),
+    (0x4, "append only"),
+    (0x8, "opaque"),
+    (0x20, "compressed"),
+    (0x8000, "hidden"),
+    (0x10000, "archived"),
+    (0x20000, "system immutable"),
+    (0x40000, "system append only"),
+];
+
+/// Extended attributes and file flags of one file, each either read or the
+/// reason it couldn't be.
+pub struct Attributes {
+    pub xattrs: Result<Vec<(String, Vec<u8>)>, String>,
+    pub flags: Result<Vec<&'static str>, String>,
+}
+
+impl Attributes {
+    pub fn describe(&self, path: &Path) -> String {
+        let mut text = format!("{}\n\nExtended attributes\n", path.display());
+        match &self.xattrs {
+            Ok(xattrs) if xattrs.is_empty() => text.push_str("  (none)\n"),
+            Ok(xattrs) => {
+                for (name, value) in xattrs {
+                    text.push_str(&format!("  {} = {}\n", name, format_value(value)));
+                }
+            }
+            Err(e) => text.push_str(&format!("  unavailable: {}\n", e)),
+        }
+
+        text.push_str("\nFlags\n");
+        match &self.flags {
+            Ok(flags) if flags.is_empty() => text.push_str("  (none)\n"),
+            Ok(flags) => text.push_str(&format!("  {}\n", flags.join(", "))),
+            Err(e) => text.push_str(&format!("  unavailable: {}\n", e)),
+        }
+        text
+    }
+}
+
+/// Reads the attributes of `path` itself, not of what a symlink points at.
+pub fn read(path: &Path) -> Attributes {
+    Attributes {
+        xattrs: read_xattrs(path),
+        flags: read_flags(path),
+    }
+}
+
+#[cfg(unix)]
+fn read_xattrs(path: &Path) -> Result<Vec<(String, Vec<u8>)>, String> {
+    if !xattr::SUPPORTED_PLATFORM {
+        return Err("not supported on this system".to_string());
+    }
+    let mut xattrs = Vec::new();
+    for name in xattr::list(path).map_err(|e| e.to_string())? {
+        let value = xattr::get(path, &name)
+            .map_err(|e| e.to_string())?
+            .unwrap_or_default();
+        xattrs.push((name.to_string_lossy().into_owned(), value));
+    }
+    xattrs.sort();
+    Ok(xattrs)
+}
+
+#[cfg(not(unix))]
+fn read_xattrs(_path: &Path) -> Result<Vec<(String, Vec<u8>)>, String> {
+    Err("not supported on this system".to_string())
+}
+
+/// Linux keeps the flags behind an ioctl, so they're read through
+/// `lsattr` like the media info goes through `ffprobe`.
+#[cfg(target_os = "linux")]
+fn read_flags(path: &Path) -> Result<Vec<&'static str>, String> {
+    // lsattr would block opening a FIFO, can't read devices or sockets, and
+    // reports on a symlink's target rather than the link
+    let metadata = std::fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+    if !metadata.is_file() && !metadata.is_dir() {
+        return Ok(Vec::new());
+    }
+    let output = std::process::Command::new("lsattr")
+        .arg("-d")
+        .arg(path)
+        .output()
+        .map_err(|e| format!("couldn't run lsattr: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.trim().rsplit(": ").next().unwrap_or("lsattr failed");
+        return Err(reason.to_string());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(linux_flag_names(
+        stdout.split_whitespace().next().unwrap_or(""),
+    ))
+}
+
+#[cfg(target_os = "macos")]
+fn read_flags(path: &Path) -> Result<Vec<&'static str>, String> {
+    use std::os::macos::fs::MetadataExt;
+    let flags = std::fs::symlink_metadata(path)
+        .map_err(|e| e.to_string())?
+        .st_flags();
+    Ok(MACOS_FLAGS
+        .iter()
+        .filter(|(bit, _)| flags & bit != 0)
+        .map(|(_, name)| *name)
+        .collect())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn read_flags(_path: &Path) -> Result<Vec<&'static str>, String> {
+    Err("not supported on this system".to_string())
+}
+
+/// Names for the letters set in an lsattr attribute column like
+/// `----i---------e-------`.
+#[cfg(target_os = "linux")]
+fn linux_flag_names(column: &str) -> Vec<&'static str> {
+    LINUX_FLAGS
+        .iter()
+        .filter(|(letter, _)| column.contains(*letter))
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// Shows a value as quoted text when it is text, and as hex otherwise.
+fn format_value(value: &[u8]) -> String {
+    let shown = &value[..value.len().min(MAX_VALUE_BYTES)];
+    let more = if value.len() > MAX_VALUE_BYTES {
+        format!("… ({} bytes)", value.len())
+    } else {
+        String::new()
+    };
+    // A trailing NUL is common for values written by C programs
+    let text = shown.strip_suffix(&[0]).unwrap_or(shown);
+    match std::str::from_utf8(text) {
+        Ok(text) if !text.chars().any(char::is_control) => format!("\"{}\"{}", text, more),
+        _ => {
+            let hex: String = shown.iter().map(|byte| format!("{:02x}", byte)).collect();
+            format!("0x{}{}", hex, more)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_show_as_text_or_hex() {
+        assert_eq!(format_value(b"hello"), "\"hello\"");
+        assert_eq!(
+            format_value(b"unconfined_u:object_r:user_home_t:s0\0"),
+            "\"unconfined_u:object_r:user_home_t:s0\""
+        );
+        assert_eq!(format_value(&[0x01, 0xff]), "0x01ff");
+        assert_eq!(
+            format_value(&[b'a'; 100]),
+            format!("\"{}\"… (100 bytes)", "a".repeat(64))
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_lsattr_letters_are_named() {
+        assert_eq!(
+            linux_flag_names("----ia--------e-------"),
+            ["append only", "extent format", "immutable"]
+        );
+        assert!(linux_flag_names("----------------------").is_empty());
+    }
+}
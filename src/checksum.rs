@@ -0,0 +1,254 @@
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+const CHUNK: usize = 64 * 1024;
+
+// Progress is reported after every this many bytes
+const REPORT_EVERY: u64 = 8 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl Algorithm {
+    pub fn label(self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "MD5",
+            Algorithm::Sha1 => "SHA-1",
+            Algorithm::Sha256 => "SHA-256",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Algorithm::Md5 => Algorithm::Sha1,
+            Algorithm::Sha1 => Algorithm::Sha256,
+            Algorithm::Sha256 => Algorithm::Md5,
+        }
+    }
+
+    /// The algorithm a hex hash of this many digits comes from.
+    pub fn for_hex_len(len: usize) -> Option<Self> {
+        match len {
+            32 => Some(Algorithm::Md5),
+            40 => Some(Algorithm::Sha1),
+            64 => Some(Algorithm::Sha256),
+            _ => None,
+        }
+    }
+}
+
+enum Hasher {
+    Md5(Md5),
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn new(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::Md5 => Hasher::Md5(Md5::new()),
+            Algorithm::Sha1 => Hasher::Sha1(Sha1::new()),
+            Algorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Md5(hasher) => hasher.update(data),
+            Hasher::Sha1(hasher) => hasher.update(data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finish_hex(self) -> String {
+        let digest: Vec<u8> = match self {
+            Hasher::Md5(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha1(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha256(hasher) => hasher.finalize().to_vec(),
+        };
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+/// Streams `reader` through the hasher, calling `progress` with the bytes
+/// read so far every few megabytes. Stops early when `progress` returns
+/// false.
+pub fn hash_reader(
+    mut reader: impl Read,
+    algorithm: Algorithm,
+    mut progress: impl FnMut(u64) -> bool,
+) -> io::Result<String> {
+    let mut hasher = Hasher::new(algorithm);
+    let mut buffer = vec![0; CHUNK];
+    let (mut read, mut reported) = (0u64, 0u64);
+    loop {
+        let count = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buffer[..count]);
+        read += count as u64;
+        if read - reported >= REPORT_EVERY {
+            reported = read;
+            if !progress(read) {
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+            }
+        }
+    }
+    Ok(hasher.finish_hex())
+}
+
+pub enum Update {
+    /// Bytes hashed so far
+    Progress(u64),
+    Done(Result<String, String>),
+}
+
+/// A checksum being computed on a background thread, and what's known of
+/// it so far. Dropping it stops the thread at the next progress report.
+pub struct Job {
+    pub path: PathBuf,
+    pub algorithm: Algorithm,
+    pub size: u64,
+    pub hashed: u64,
+    pub result: Option<Result<String, String>>,
+    /// A hash to compare against, as pasted
+    pub expected: Option<String>,
+    receiver: Option<Receiver<Update>>,
+}
+
+impl Job {
+    pub fn start(path: PathBuf, algorithm: Algorithm) -> io::Result<Self> {
+        let file = File::open(&path)?;
+        let size = file.metadata()?.len();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let result = hash_reader(file, algorithm, |read| {
+                sender.send(Update::Progress(read)).is_ok()
+            });
+            let _ = sender.send(Update::Done(result.map_err(|e| e.to_string())));
+        });
+
+        Ok(Self {
+            path,
+            algorithm,
+            size,
+            hashed: 0,
+            result: None,
+            expected: None,
+            receiver: Some(receiver),
+        })
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// Takes in whatever the thread has reported since the last call.
+    pub fn poll(&mut self) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        loop {
+            match receiver.try_recv() {
+                Ok(Update::Progress(read)) => self.hashed = read,
+                Ok(Update::Done(result)) => {
+                    self.hashed = self.size;
+                    self.result = Some(result);
+                    self.receiver = None;
+                    return;
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.receiver = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Whether the finished hash equals the expected one, once both exist.
+    pub fn matches_expected(&self) -> Option<bool> {
+        match (&self.result, &self.expected) {
+            (Some(Ok(hash)), Some(expected)) => Some(matches(expected, hash)),
+            _ => None,
+        }
+    }
+}
+
+/// Compares a pasted hash with a computed one. Case and surrounding space
+/// don't matter, and a `sha256sum`-style line keeps only the hash.
+pub fn matches(expected: &str, actual: &str) -> bool {
+    let hash = expected_hash(expected);
+    !hash.is_empty() && hash.eq_ignore_ascii_case(actual)
+}
+
+/// The hash part of a pasted hash, without a trailing file name.
+pub fn expected_hash(expected: &str) -> &str {
+    expected.split_whitespace().next().unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(data: &[u8], algorithm: Algorithm) -> String {
+        hash_reader(data, algorithm, |_| true).unwrap()
+    }
+
+    #[test]
+    fn test_known_digests() {
+        assert_eq!(
+            hash(b"abc", Algorithm::Md5),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert_eq!(
+            hash(b"abc", Algorithm::Sha1),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hash(b"", Algorithm::Sha1),
+            "da39a3ee5e6b4b0d3255bfef95601890afd80709"
+        );
+        // Spans two blocks once padded
+        assert_eq!(
+            hash(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                Algorithm::Sha1
+            ),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+        assert_eq!(
+            hash(b"abc", Algorithm::Sha256),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_pasted_hashes_compare_loosely() {
+        let actual = "900150983cd24fb0d6963f7d28e17f72";
+        assert!(matches("900150983CD24FB0D6963F7D28E17F72", actual));
+        assert!(matches(
+            "  900150983cd24fb0d6963f7d28e17f72  abc.txt\n",
+            actual
+        ));
+        assert!(!matches("900150983cd24fb0", actual));
+        assert!(!matches("", actual));
+        assert_eq!(
+            Algorithm::for_hex_len(expected_hash(&format!("{}  x.iso", actual)).len()),
+            Some(Algorithm::Md5)
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use std::io::{self, Write};
+
+/// System clipboard with an OSC 52 fallback for sessions without a display
+/// server (SSH, bare consoles), where the terminal itself takes the text.
+pub struct Clipboard {
+    // Kept alive because on X11 the copied text is served by this process
+    system: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        Self {
+            system: arboard::Clipboard::new().ok(),
+        }
+    }
+
+    pub fn copy(&mut self, text: &str) -> io::Result<()> {
+        if let Some(system) = &mut self.system
+            && system.set_text(text).is_ok()
+        {
+            return Ok(());
+        }
+
+        let mut stdout = io::stdout();
+        write!(stdout, "\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))?;
+        stdout.flush()
+    }
+
+    /// The text on the system clipboard. Reading has no OSC 52 fallback,
+    /// since most terminals refuse clipboard queries.
+    pub fn paste(&mut self) -> Result<String, String> {
+        match &mut self.system {
+            Some(system) => system.get_text().map_err(|e| e.to_string()),
+            None => Err("no system clipboard is available".to_string()),
+        }
+    }
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"src/main.rs"), "c3JjL21haW4ucnM=");
+    }
+}
@@ -0,0 +1,465 @@
+use crate::footer;
+use crate::paths;
+use crate::size::SizeUnits;
+use crate::sorting::{DirOrder, SortKey};
+use crate::themes;
+use ratatui::style::{Color, Style};
+use serde::{Deserialize, Deserializer, de};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// What a new config file starts out as: every setting at its default,
+/// commented out, with a line on what it does.
+pub const TEMPLATE: &str = r##"# ls-pretty settings. Uncomment a line to change it; saving this file from
+# ls-pretty applies it right away.
+
+# Marker in front of the selected row, and the selection colors (names,
+# "#rrggbb" or a 256-color index)
+# highlight_symbol = "➤ "
+# selection_fg = "black"
+# selection_bg = "yellow"
+
+# Start Ctrl+T in the selected directory instead of the listed one
+# terminal_in_selection = false
+
+# How listings start out, read at startup (-a, -H, -s and -r win over
+# them): hidden files, human readable sizes, and the sort column ("name",
+# "size", "modified" or "extension") and direction
+# show_hidden = false
+# human_readable = false
+# sort = "name"
+# reverse_sort = false
+
+# Git branch in the header and each changed file's status (M, A, ??) beside
+# it; --no-git turns both off for the session
+# git = true
+
+# Human readable sizes in "binary" (1024, K/M/G) or "si" (1000, kB/MB/GB) units
+# size_units = "binary"
+
+# Where directories go: "dirs-first", "files-first" or "mixed"
+# dir_order = "dirs-first"
+
+# Editor cursor: "block", "bar" or "underline", and how it blinks
+# cursor_shape = "block"
+# cursor_blink = true
+# cursor_blink_ms = 500
+
+# Syntax highlighting theme (a built-in or a .tmTheme in theme_dir); T in
+# the viewer cycles through them for the session
+# theme = "base16-ocean.dark"
+# theme_dir = "~/.config/ls-pretty/themes"
+
+# Syntax colors in open files; bigger files than this are shown plain
+# syntax_highlighting = true
+# highlight_max_bytes = 2097152
+
+# Columns between tab stops when showing files indented with tabs, and
+# spaces the Tab key inserts while editing
+# tab_width = 4
+
+# Keep each file's undo history after closing it, in the state directory;
+# reopening brings it back unless the file changed in the meantime
+# persistent_undo = false
+
+# Overview of the whole file beside the viewer when it runs past the screen
+# minimap = true
+
+# Enter on a GIF or video shows its resolution, frames and duration
+# media_info = true
+
+# Deleting or moving more marked entries than this needs the phrase typed,
+# not just y; an empty phrase turns the check off
+# bulk_confirm_threshold = 20
+# bulk_confirm_phrase = "DELETE"
+
+# Build output and caches, and whether they start "dim", "hide" or "show"
+# clutter_dirs = ["target", "node_modules", ".git", "__pycache__", ".cache"]
+# clutter = "dim"
+
+# Entering a directory holding only one subdirectory keeps going down
+# auto_descend = false
+
+# Footer text; placeholders are {hints}, {mode}, {path}, {selected},
+# {count}, {size}, {git_branch}, {time}, {encoding}, {line_ending} and
+# {theme}
+# footer_format = "{hints}"
+
+# What Enter does on a file, by extension ("view", "open" or "run"):
+#   [actions]
+#   pdf = "open"
+
+# Named directories P offers to jump to:
+#   [places]
+#   projects = "~/code"
+"##;
+
+/// User settings from `config.toml`. Every field is optional in the file;
+/// anything left out keeps the built-in default.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Marker drawn in front of the selected row of the listing
+    pub highlight_symbol: String,
+    #[serde(deserialize_with = "color")]
+    pub selection_fg: Color,
+    #[serde(deserialize_with = "color")]
+    pub selection_bg: Color,
+    /// Open the terminal in the selected directory instead of the listed one
+    pub terminal_in_selection: bool,
+    /// Start with hidden files listed, like `-a`
+    pub show_hidden: bool,
+    /// Start with human readable sizes, like `-H`
+    pub human_readable: bool,
+    /// Column the listing starts sorted by, like `--sort`
+    pub sort: SortKey,
+    /// Start with the sort reversed, like `-r`
+    pub reverse_sort: bool,
+    /// Look up the git branch and file states; `--no-git` turns it off
+    pub git: bool,
+    /// What Enter does on a file, keyed by extension (`pdf = "open"`)
+    pub actions: HashMap<String, FileAction>,
+    /// "binary" (1024, K/M/G) or "si" (1000, kB/MB/GB) for human readable sizes
+    pub size_units: SizeUnits,
+    /// "dirs-first", "files-first" or "mixed"; `o` cycles through them
+    pub dir_order: DirOrder,
+    pub cursor_shape: CursorShape,
+    /// Set to false for a steady cursor, which also lets the app sleep longer
+    pub cursor_blink: bool,
+    /// Time the cursor spends on (and then off) per blink
+    pub cursor_blink_ms: u64,
+    /// Syntax highlighting theme: a syntect built-in or a file stem from
+    /// `theme_dir`
+    pub theme: String,
+    /// Directory searched for extra `.tmTheme` files
+    pub theme_dir: Option<PathBuf>,
+    /// Enter on a GIF or video shows its resolution, frames and duration
+    /// (videos need `ffprobe`)
+    pub media_info: bool,
+    /// Deleting or moving more marked entries than this asks for
+    /// `bulk_confirm_phrase` to be typed instead of a plain y/n; an empty
+    /// phrase turns that off
+    pub bulk_confirm_threshold: usize,
+    pub bulk_confirm_phrase: String,
+    /// Color files by their syntax; Alt+H turns it off for the session
+    pub syntax_highlighting: bool,
+    /// Files bigger than this are shown without syntax colors
+    pub highlight_max_bytes: usize,
+    /// Columns between tab stops when showing literal tab characters, and
+    /// spaces the Tab key inserts
+    pub tab_width: usize,
+    /// Save undo histories on close and restore them for unchanged files
+    pub persistent_undo: bool,
+    /// Show a clickable overview of the whole file beside long files
+    pub minimap: bool,
+    /// Build output and caches that clutter project listings
+    pub clutter_dirs: Vec<String>,
+    /// How `clutter_dirs` start out; `I` cycles through the modes
+    pub clutter: ClutterMode,
+    /// Named directories `P` offers to jump to (`projects = "~/code"`)
+    pub places: BTreeMap<String, PathBuf>,
+    /// Entering a directory that holds only one subdirectory keeps going
+    /// down until there's a choice to make
+    pub auto_descend: bool,
+    /// Footer text with `{placeholders}`, e.g. `"{mode} {path} {git_branch}"`
+    pub footer_format: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClutterMode {
+    /// Listed in a muted color
+    #[default]
+    Dim,
+    /// Left out of the listing
+    Hide,
+    /// Listed like any other directory
+    Show,
+}
+
+impl ClutterMode {
+    pub fn next(self) -> Self {
+        match self {
+            ClutterMode::Dim => ClutterMode::Hide,
+            ClutterMode::Hide => ClutterMode::Show,
+            ClutterMode::Show => ClutterMode::Dim,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ClutterMode::Dim => "dimmed",
+            ClutterMode::Hide => "hidden",
+            ClutterMode::Show => "shown normally",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CursorShape {
+    #[default]
+    Block,
+    Bar,
+    Underline,
+}
+
+impl CursorShape {
+    /// The glyph drawn at the editor cursor position.
+    pub fn glyph(self) -> &'static str {
+        match self {
+            CursorShape::Block => "█",
+            CursorShape::Bar => "▏",
+            CursorShape::Underline => "▁",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileAction {
+    /// Open in the internal viewer, even if it doesn't look like text
+    View,
+    /// Hand off to the system's default application
+    Open,
+    /// Execute it in the integrated terminal, after confirming
+    Run,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            highlight_symbol: "➤ ".to_string(),
+            selection_fg: Color::Black,
+            selection_bg: Color::Yellow,
+            terminal_in_selection: false,
+            show_hidden: false,
+            human_readable: false,
+            sort: SortKey::Name,
+            reverse_sort: false,
+            git: true,
+            actions: HashMap::new(),
+            size_units: SizeUnits::Binary,
+            dir_order: DirOrder::DirsFirst,
+            cursor_shape: CursorShape::Block,
+            cursor_blink: true,
+            cursor_blink_ms: 500,
+            theme: themes::DEFAULT_THEME.to_string(),
+            theme_dir: None,
+            media_info: true,
+            bulk_confirm_threshold: 20,
+            bulk_confirm_phrase: "DELETE".to_string(),
+            syntax_highlighting: true,
+            highlight_max_bytes: 2 * 1024 * 1024,
+            tab_width: 4,
+            persistent_undo: false,
+            minimap: true,
+            clutter_dirs: ["target", "node_modules", ".git", "__pycache__", ".cache"]
+                .map(String::from)
+                .to_vec(),
+            clutter: ClutterMode::Dim,
+            places: BTreeMap::new(),
+            auto_descend: false,
+            footer_format: footer::DEFAULT_FORMAT.to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// `~/.config/ls-pretty/config.toml` on Linux, the platform equivalent
+    /// elsewhere.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("ls-pretty").join("config.toml"))
+    }
+
+    /// Reads the config file. A missing file means defaults; a broken one is
+    /// reported on stderr and also falls back to defaults.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        if !path.exists() {
+            return Self::default();
+        }
+
+        Self::load_from(&path).unwrap_or_else(|e| {
+            eprintln!("Warning: ignoring invalid config {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    /// Reads a config file, with `~` in its paths expanded.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut config = Self::from_toml(&contents).map_err(|e| e.to_string())?;
+        config.theme_dir = config.theme_dir.map(|dir| paths::expand_home(&dir));
+        for dir in config.places.values_mut() {
+            *dir = paths::expand_home(dir);
+        }
+        Ok(config)
+    }
+
+    /// The config file's path, first writing `TEMPLATE` there if there's no
+    /// file yet.
+    pub fn create_if_missing() -> io::Result<PathBuf> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if !path.exists() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&path, TEMPLATE)?;
+        }
+        Ok(path)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(contents)
+    }
+
+    /// The configured action for `path`'s extension, if any. Extensions
+    /// match case-insensitively and may be written with a leading dot.
+    pub fn action_for(&self, path: &Path) -> Option<FileAction> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        self.actions.iter().find_map(|(key, action)| {
+            (key.trim_start_matches('.').to_lowercase() == extension).then_some(*action)
+        })
+    }
+
+    pub fn cursor_blink_interval(&self) -> Duration {
+        // Anything faster than the event loop can redraw is just flicker
+        Duration::from_millis(self.cursor_blink_ms.max(100))
+    }
+
+    /// Whether a directory called `name` is one of `clutter_dirs`.
+    pub fn is_clutter(&self, name: &str) -> bool {
+        self.clutter_dirs.iter().any(|dir| dir == name)
+    }
+
+    pub fn selection_style(&self) -> Style {
+        Style::default().fg(self.selection_fg).bg(self.selection_bg)
+    }
+}
+
+// Accepts names ("yellow", "lightblue"), hex ("#ff8800") and indexed ("208")
+fn color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value
+        .parse()
+        .map_err(|_| de::Error::custom(format!("unknown color `{}`", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        assert_eq!(Config::from_toml("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_template_documents_the_defaults() {
+        assert_eq!(Config::from_toml(TEMPLATE).unwrap(), Config::default());
+
+        // Uncommenting every setting changes nothing but the example theme_dir
+        let uncommented: String = TEMPLATE
+            .lines()
+            .map(|line| match line.strip_prefix("# ") {
+                Some(setting) if setting.contains(" = ") && !setting.starts_with(' ') => setting,
+                _ => line,
+            })
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let config = Config::from_toml(&uncommented).unwrap();
+        assert!(config.theme_dir.is_some());
+        assert_eq!(
+            Config {
+                theme_dir: None,
+                ..config
+            },
+            Config::default()
+        );
+    }
+
+    #[test]
+    fn test_listing_defaults() {
+        let config = Config::from_toml(
+            "show_hidden = true\nhuman_readable = true\nsort = \"modified\"\nreverse_sort = true\n",
+        )
+        .unwrap();
+        assert!(config.show_hidden && config.human_readable && config.reverse_sort);
+        assert_eq!(config.sort, SortKey::Modified);
+        assert!(Config::from_toml("sort = \"color\"").is_err());
+    }
+
+    #[test]
+    fn test_selection_settings() {
+        let config = Config::from_toml(
+            "highlight_symbol = \"> \"\nselection_fg = \"white\"\nselection_bg = \"#005f87\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.highlight_symbol, "> ");
+        assert_eq!(config.selection_fg, Color::White);
+        assert_eq!(config.selection_bg, Color::Rgb(0x00, 0x5f, 0x87));
+    }
+
+    #[test]
+    fn test_file_actions() {
+        let config = Config::from_toml("[actions]\npdf = \"open\"\n\".SH\" = \"run\"\n").unwrap();
+        assert_eq!(
+            config.action_for(Path::new("doc.PDF")),
+            Some(FileAction::Open)
+        );
+        assert_eq!(
+            config.action_for(Path::new("build.sh")),
+            Some(FileAction::Run)
+        );
+        assert_eq!(config.action_for(Path::new("notes.md")), None);
+        assert!(Config::from_toml("[actions]\npdf = \"print\"").is_err());
+    }
+
+    #[test]
+    fn test_cursor_settings() {
+        let config = Config::from_toml(
+            "cursor_shape = \"bar\"\ncursor_blink = false\ncursor_blink_ms = 20\n",
+        )
+        .unwrap();
+        assert_eq!(config.cursor_shape.glyph(), "▏");
+        assert!(!config.cursor_blink);
+        assert_eq!(config.cursor_blink_interval(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_clutter_settings() {
+        let defaults = Config::default();
+        assert!(defaults.is_clutter("node_modules"));
+        assert!(!defaults.is_clutter("src"));
+
+        let config = Config::from_toml("clutter_dirs = [\"dist\"]\nclutter = \"hide\"\n").unwrap();
+        assert!(config.is_clutter("dist"));
+        assert!(!config.is_clutter("target"));
+        assert_eq!(config.clutter, ClutterMode::Hide);
+        assert_eq!(ClutterMode::Show.next(), ClutterMode::Dim);
+    }
+
+    #[test]
+    fn test_places_are_listed_by_name() {
+        let config = Config::from_toml(
+            "[places]\nprojects = \"~/code\"\ndownloads = \"/home/me/Downloads\"\n",
+        )
+        .unwrap();
+        let names: Vec<&str> = config.places.keys().map(String::as_str).collect();
+        assert_eq!(names, ["downloads", "projects"]);
+        assert_eq!(config.places["projects"], Path::new("~/code"));
+    }
+
+    #[test]
+    fn test_unknown_color_is_an_error() {
+        let err = Config::from_toml("selection_bg = \"not-a-color\"").unwrap_err();
+        assert!(err.to_string().contains("unknown color `not-a-color`"));
+    }
+}
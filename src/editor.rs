@@ -0,0 +1,262 @@
+/// Deletes the character before the cursor. At the start of a line the line
+/// break is removed instead, joining the line onto the previous one and
+/// leaving the cursor where that line used to end. Cursor columns count
+/// chars, not bytes. Returns the new `(line, col)`.
+pub fn backspace(content: &mut String, line: usize, col: usize) -> (usize, usize) {
+    // `split` rather than `lines` so a trailing newline keeps its empty line
+    let mut lines: Vec<String> = content.split('\n').map(String::from).collect();
+    let line = line.min(lines.len() - 1);
+    let col = col.min(lines[line].chars().count());
+
+    let cursor = if col > 0 {
+        let (start, ch) = lines[line].char_indices().nth(col - 1).unwrap();
+        lines[line].replace_range(start..start + ch.len_utf8(), "");
+        (line, col - 1)
+    } else if line > 0 {
+        let current = lines.remove(line);
+        let previous = &mut lines[line - 1];
+        // A CRLF line ending goes away as a whole
+        if previous.ends_with('\r') {
+            previous.pop();
+        }
+        let joined_at = previous.chars().count();
+        previous.push_str(&current);
+        (line - 1, joined_at)
+    } else {
+        return (line, col);
+    };
+
+    *content = lines.join("\n");
+    cursor
+}
+
+/// Inserts `text` at `(line, col)`, the column counted in chars like the
+/// cursor's. A column past the end of the line inserts before its line
+/// ending.
+pub fn insert(content: &mut String, line: usize, col: usize, text: &str) {
+    let at = byte_offset(content, line, col);
+    content.insert_str(at, text);
+}
+
+/// Where `(line, col)` falls in `content` in bytes; the end of the content
+/// for a line past the last one.
+fn byte_offset(content: &str, line: usize, col: usize) -> usize {
+    let mut start = 0;
+    for (index, row) in content.split('\n').enumerate() {
+        let text = row.strip_suffix('\r').unwrap_or(row);
+        if index == line {
+            return start
+                + text
+                    .char_indices()
+                    .nth(col)
+                    .map_or(text.len(), |(at, _)| at);
+        }
+        start += row.len() + 1;
+    }
+    content.len()
+}
+
+/// Finds the bracket pairing with the one at `(line, col)`, skipping over
+/// nested pairs of the same kind. `None` if the cursor isn't on one of
+/// `()[]{}` or it has no partner. Columns count chars.
+pub fn matching_bracket(content: &str, line: usize, col: usize) -> Option<(usize, usize)> {
+    let lines: Vec<Vec<char>> = content.split('\n').map(|l| l.chars().collect()).collect();
+    let bracket = *lines.get(line)?.get(col)?;
+    let (open, close, forward) = match bracket {
+        '(' => ('(', ')', true),
+        '[' => ('[', ']', true),
+        '{' => ('{', '}', true),
+        ')' => ('(', ')', false),
+        ']' => ('[', ']', false),
+        '}' => ('{', '}', false),
+        _ => return None,
+    };
+
+    let positions = lines
+        .iter()
+        .enumerate()
+        .flat_map(|(l, chars)| chars.iter().enumerate().map(move |(c, ch)| (l, c, *ch)));
+    let candidates: Box<dyn Iterator<Item = (usize, usize, char)>> = if forward {
+        Box::new(positions.skip_while(|&(l, c, _)| (l, c) <= (line, col)))
+    } else {
+        let before: Vec<_> = positions
+            .take_while(|&(l, c, _)| (l, c) < (line, col))
+            .collect();
+        Box::new(before.into_iter().rev())
+    };
+
+    let mut depth = 0;
+    for (l, c, ch) in candidates {
+        if ch == bracket {
+            depth += 1;
+        } else if ch == open || ch == close {
+            if depth == 0 {
+                return Some((l, c));
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// `text` with each tab replaced by spaces up to the next tab stop, for
+/// display. `column` is the screen column `text` starts at and is advanced
+/// past it, so consecutive pieces of one line line up.
+pub fn expand_tabs(text: &str, column: &mut usize, tab_width: usize) -> String {
+    let tab_width = tab_width.max(1);
+    let mut expanded = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if ch == '\t' {
+            let width = tab_width - *column % tab_width;
+            expanded.extend(std::iter::repeat_n(' ', width));
+            *column += width;
+        } else {
+            expanded.push(ch);
+            *column += 1;
+        }
+    }
+    expanded
+}
+
+/// The screen column of char `col` in `line` once tabs are expanded.
+pub fn display_column(line: &str, col: usize, tab_width: usize) -> usize {
+    let prefix: String = line.chars().take(col).collect();
+    let mut column = 0;
+    expand_tabs(&prefix, &mut column, tab_width);
+    column
+}
+
+/// Footer label for the line breaks `content` uses: "LF", "CRLF" or
+/// "Mixed". `None` for a single line, which has none to go by.
+pub fn line_ending_label(content: &str) -> Option<&'static str> {
+    let breaks = content.matches('\n').count();
+    let crlf = content.matches("\r\n").count();
+    match (breaks, crlf) {
+        (0, _) => None,
+        (_, 0) => Some("LF"),
+        _ if crlf == breaks => Some("CRLF"),
+        _ => Some("Mixed"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backspace_at(content: &str, line: usize, col: usize) -> (String, usize, usize) {
+        let mut content = content.to_string();
+        let (line, col) = backspace(&mut content, line, col);
+        (content, line, col)
+    }
+
+    #[test]
+    fn test_backspace_within_line() {
+        assert_eq!(
+            backspace_at("hello\nworld", 1, 3),
+            ("hello\nwold".into(), 1, 2)
+        );
+    }
+
+    #[test]
+    fn test_backspace_joins_lines_of_various_lengths() {
+        assert_eq!(backspace_at("a\nb", 1, 0), ("ab".into(), 0, 1));
+        assert_eq!(
+            backspace_at("first line\nsecond\nthird", 1, 0),
+            ("first linesecond\nthird".into(), 0, 10)
+        );
+        assert_eq!(
+            backspace_at("one\ntwo\nthree", 2, 0),
+            ("one\ntwothree".into(), 1, 3)
+        );
+    }
+
+    #[test]
+    fn test_backspace_joins_empty_lines() {
+        // Empty current line
+        assert_eq!(backspace_at("abc\n\ndef", 1, 0), ("abc\ndef".into(), 0, 3));
+        // Empty previous line
+        assert_eq!(backspace_at("abc\n\ndef", 2, 0), ("abc\ndef".into(), 1, 0));
+        // Trailing empty line left by a final newline
+        assert_eq!(backspace_at("abc\n", 1, 0), ("abc".into(), 0, 3));
+    }
+
+    #[test]
+    fn test_backspace_at_start_of_file_is_noop() {
+        assert_eq!(backspace_at("abc\ndef", 0, 0), ("abc\ndef".into(), 0, 0));
+    }
+
+    #[test]
+    fn test_backspace_counts_chars_not_bytes() {
+        assert_eq!(backspace_at("héllo\nx", 1, 0), ("héllox".into(), 0, 5));
+        assert_eq!(backspace_at("héllo", 0, 2), ("hllo".into(), 0, 1));
+    }
+
+    #[test]
+    fn test_insert_counts_chars_not_bytes() {
+        let mut content = "héllo wörld\n🦀 x".to_string();
+        insert(&mut content, 0, 2, "é");
+        insert(&mut content, 1, 1, "!");
+        insert(&mut content, 0, 12, "?");
+        assert_eq!(content, "hééllo wörld?\n🦀! x");
+    }
+
+    #[test]
+    fn test_insert_keeps_crlf_and_clamps_past_the_end() {
+        let mut content = "ab\r\ncd".to_string();
+        insert(&mut content, 0, 9, "x");
+        insert(&mut content, 5, 0, "!");
+        assert_eq!(content, "abx\r\ncd!");
+    }
+
+    #[test]
+    fn test_backspace_removes_crlf_line_ending() {
+        assert_eq!(backspace_at("abc\r\ndef", 1, 0), ("abcdef".into(), 0, 3));
+    }
+
+    #[test]
+    fn test_matching_bracket_handles_nesting() {
+        let code = "fn f(a: [u8; 2]) {\n    if (a[0]) { g(); }\n}";
+        assert_eq!(matching_bracket(code, 0, 4), Some((0, 15)));
+        assert_eq!(matching_bracket(code, 0, 15), Some((0, 4)));
+        assert_eq!(matching_bracket(code, 0, 17), Some((2, 0)));
+        assert_eq!(matching_bracket(code, 2, 0), Some((0, 17)));
+        assert_eq!(matching_bracket(code, 1, 7), Some((1, 12)));
+        assert_eq!(matching_bracket(code, 1, 14), Some((1, 21)));
+    }
+
+    #[test]
+    fn test_matching_bracket_counts_chars_not_bytes() {
+        assert_eq!(matching_bracket("(é)", 0, 0), Some((0, 2)));
+    }
+
+    #[test]
+    fn test_tabs_expand_to_the_next_stop() {
+        let mut column = 0;
+        assert_eq!(expand_tabs("\tx", &mut column, 4), "    x");
+        assert_eq!(column, 5);
+        // A later piece of the same line continues from where this one ended
+        assert_eq!(expand_tabs("ab\t", &mut column, 4), "ab ");
+        assert_eq!(column, 8);
+
+        assert_eq!(display_column("\t\tfoo", 2, 4), 8);
+        assert_eq!(display_column("a\tb", 2, 8), 8);
+        assert_eq!(display_column("no tabs", 3, 4), 3);
+    }
+
+    #[test]
+    fn test_unmatched_or_non_bracket_is_none() {
+        assert_eq!(matching_bracket("((a)", 0, 0), None);
+        assert_eq!(matching_bracket("a)]", 0, 1), None);
+        assert_eq!(matching_bracket("abc", 0, 1), None);
+        assert_eq!(matching_bracket("()", 5, 0), None);
+        assert_eq!(matching_bracket("()", 0, 9), None);
+    }
+
+    #[test]
+    fn test_line_endings_are_labelled() {
+        assert_eq!(line_ending_label("a\nb\n"), Some("LF"));
+        assert_eq!(line_ending_label("a\r\nb\r\n"), Some("CRLF"));
+        assert_eq!(line_ending_label("a\r\nb\nc"), Some("Mixed"));
+        assert_eq!(line_ending_label("one line"), None);
+    }
+}
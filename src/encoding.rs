@@ -0,0 +1,198 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A text file decoded to UTF-8, with what's needed to write it back.
+#[derive(Debug, Clone)]
+pub struct Decoded {
+    pub text: String,
+    pub encoding: &'static Encoding,
+    pub has_bom: bool,
+    /// Undecodable bytes were replaced, so saving would corrupt the file
+    pub lossy: bool,
+}
+
+pub fn read_file(path: &Path) -> io::Result<Decoded> {
+    fs::read(path).map(|bytes| decode(&bytes))
+}
+
+/// Decodes `bytes`, trusting a BOM first, then a UTF-16 guess from NUL byte
+/// placement, then UTF-8, and finally chardetng's guess for legacy
+/// encodings (Latin-1, Shift_JIS, ...).
+pub fn decode(bytes: &[u8]) -> Decoded {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        let (text, had_errors) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return Decoded {
+            text: text.into_owned(),
+            encoding,
+            has_bom: true,
+            lossy: had_errors,
+        };
+    }
+
+    // Checked before UTF-8 since NUL-padded ASCII is also valid UTF-8
+    let utf16 = guess_utf16(bytes);
+    if utf16.is_none()
+        && let Ok(text) = std::str::from_utf8(bytes)
+    {
+        return Decoded {
+            text: text.to_string(),
+            encoding: UTF_8,
+            has_bom: false,
+            lossy: false,
+        };
+    }
+
+    let encoding = utf16.unwrap_or_else(|| {
+        let mut detector = EncodingDetector::new();
+        detector.feed(bytes, true);
+        detector.guess(None, true)
+    });
+    let (text, had_errors) = encoding.decode_without_bom_handling(bytes);
+    if !had_errors {
+        return Decoded {
+            text: text.into_owned(),
+            encoding,
+            has_bom: false,
+            lossy: false,
+        };
+    }
+
+    // Nothing fits cleanly: show what we can, but never write it back
+    Decoded {
+        text: String::from_utf8_lossy(bytes).into_owned(),
+        encoding: UTF_8,
+        has_bom: false,
+        lossy: true,
+    }
+}
+
+/// Re-encodes `text` for saving in the encoding the file was read with.
+/// Text the encoding has no bytes for is an error naming the first such
+/// character, rather than saved as `&#...;` references in its place.
+pub fn encode(text: &str, encoding: &'static Encoding, has_bom: bool) -> Result<Vec<u8>, String> {
+    // encoding_rs only decodes UTF-16, so it's encoded by hand
+    let mut bytes = Vec::with_capacity(text.len());
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let little_endian = encoding == UTF_16LE;
+        let bom = has_bom.then_some(0xFEFF_u16);
+        for unit in bom.into_iter().chain(text.encode_utf16()) {
+            bytes.extend(if little_endian {
+                unit.to_le_bytes()
+            } else {
+                unit.to_be_bytes()
+            });
+        }
+        return Ok(bytes);
+    }
+
+    if has_bom && encoding == UTF_8 {
+        bytes.extend_from_slice(b"\xEF\xBB\xBF");
+    }
+    let (encoded, _, had_errors) = encoding.encode(text);
+    if had_errors {
+        let mut buffer = [0; 4];
+        let unmappable = text
+            .chars()
+            .find(|c| encoding.encode(c.encode_utf8(&mut buffer)).2)
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+        return Err(format!(
+            "{:?} can't be written in {}",
+            unmappable,
+            encoding.name()
+        ));
+    }
+    bytes.extend_from_slice(&encoded);
+    Ok(bytes)
+}
+
+// BOM-less UTF-16 text is mostly ASCII with a NUL in every other byte
+fn guess_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(4096) & !1];
+    if sample.is_empty() {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let even_nuls = sample.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd_nuls = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|b| **b == 0)
+        .count();
+
+    if odd_nuls * 10 > pairs * 7 && even_nuls * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even_nuls * 10 > pairs * 7 && odd_nuls * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{SHIFT_JIS, WINDOWS_1252};
+
+    #[test]
+    fn test_utf8_passes_through() {
+        let decoded = decode("héllo".as_bytes());
+        assert_eq!(decoded.text, "héllo");
+        assert_eq!(decoded.encoding, UTF_8);
+        assert!(!decoded.lossy);
+    }
+
+    #[test]
+    fn test_latin1_is_detected_and_round_trips() {
+        let bytes = b"caf\xe9 cr\xe8me br\xfbl\xe9e, d\xe9j\xe0 vu";
+        let decoded = decode(bytes);
+        assert_eq!(decoded.text, "café crème brûlée, déjà vu");
+        assert_eq!(decoded.encoding, WINDOWS_1252);
+        assert_eq!(
+            encode(&decoded.text, decoded.encoding, false).unwrap(),
+            bytes
+        );
+    }
+
+    #[test]
+    fn test_text_the_encoding_cannot_hold_is_refused() {
+        assert_eq!(
+            encode("café ✓", WINDOWS_1252, false),
+            Err("'✓' can't be written in windows-1252".to_string())
+        );
+        assert!(encode("日本", SHIFT_JIS, false).is_ok());
+    }
+
+    #[test]
+    fn test_shift_jis_is_detected() {
+        let (bytes, _, _) = SHIFT_JIS.encode("日本語のテキストファイルです。これはテストです。");
+        let decoded = decode(&bytes);
+        assert_eq!(decoded.encoding, SHIFT_JIS);
+        assert_eq!(
+            decoded.text,
+            "日本語のテキストファイルです。これはテストです。"
+        );
+    }
+
+    #[test]
+    fn test_utf16_with_and_without_bom_round_trips() {
+        let with_bom = encode("hi\nthere", UTF_16LE, true).unwrap();
+        assert_eq!(&with_bom[..2], b"\xFF\xFE");
+        let decoded = decode(&with_bom);
+        assert_eq!(decoded.text, "hi\nthere");
+        assert_eq!(decoded.encoding, UTF_16LE);
+        assert!(decoded.has_bom);
+        assert_eq!(
+            encode(&decoded.text, decoded.encoding, decoded.has_bom).unwrap(),
+            with_bom
+        );
+
+        let without_bom = encode("plain text", UTF_16BE, false).unwrap();
+        let decoded = decode(&without_bom);
+        assert_eq!(decoded.text, "plain text");
+        assert_eq!(decoded.encoding, UTF_16BE);
+    }
+}
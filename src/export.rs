@@ -0,0 +1,118 @@
+use crate::{App, FileItem, server, size};
+use clap::ValueEnum;
+use std::path::Path;
+
+/// Formats the listing can be written out in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// The same rows as `--list`
+    Text,
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Picks the format from a file name: `.txt`, `.csv`, `.json` or `.md`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "txt" => Some(ExportFormat::Text),
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            "md" | "markdown" => Some(ExportFormat::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// The listing as currently shown, in sort order and with filters applied.
+/// Sizes follow the `-H` / `--si` setting.
+pub fn render(app: &App, format: ExportFormat) -> String {
+    let files: Vec<&FileItem> = app.files.iter().filter(|file| file.name != "..").collect();
+    let size = |file: &FileItem| size::format_size(file.size, app.human_readable, app.size_units);
+
+    match format {
+        ExportFormat::Text => {
+            let name_width = app.name_column_width(usize::MAX);
+            files
+                .iter()
+                .map(|file| file.format_row(name_width, app.human_readable, app.size_units) + "\n")
+                .collect()
+        }
+        ExportFormat::Csv => {
+            let mut out = String::from("name,path,type,size,permissions,modified\n");
+            for file in files {
+                let fields = [
+                    file.name.clone(),
+                    file.path.display().to_string(),
+                    kind(file).to_string(),
+                    size(file),
+                    file.permissions.clone(),
+                    file.format_date(),
+                ];
+                let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                out.push_str(&row.join(","));
+                out.push('\n');
+            }
+            out
+        }
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(&server::listing(app)).unwrap_or_default() + "\n"
+        }
+        ExportFormat::Markdown => {
+            let mut out = String::from(
+                "| Name | Type | Size | Permissions | Modified |\n|---|---|---:|---|---|\n",
+            );
+            for file in files {
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    file.name.replace('|', "\\|"),
+                    kind(file),
+                    size(file),
+                    file.permissions,
+                    file.format_date()
+                ));
+            }
+            out
+        }
+    }
+}
+
+fn kind(file: &FileItem) -> &'static str {
+    if file.is_dir { "directory" } else { "file" }
+}
+
+// Quoted only when needed, with embedded quotes doubled (RFC 4180)
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("plain.txt"), "plain.txt");
+        assert_eq!(csv_field("a,b.txt"), "\"a,b.txt\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out.CSV")),
+            Some(ExportFormat::Csv)
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("inventory.md")),
+            Some(ExportFormat::Markdown)
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("listing")), None);
+    }
+}
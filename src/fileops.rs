@@ -0,0 +1,442 @@
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// The first free name for a copy of `name` in `dir`: `name copy.ext`, then
+/// `name copy 2.ext`, `name copy 3.ext`, ...
+pub fn copy_name(dir: &Path, name: &str) -> PathBuf {
+    let path = Path::new(name);
+    let (stem, extension) = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) => (
+            stem.to_string_lossy().into_owned(),
+            format!(".{}", extension.to_string_lossy()),
+        ),
+        _ => (name.to_string(), String::new()),
+    };
+
+    (1..)
+        .map(|n| match n {
+            1 => dir.join(format!("{} copy{}", stem, extension)),
+            n => dir.join(format!("{} copy {}{}", stem, n, extension)),
+        })
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .expect("some copy name is free")
+}
+
+/// Copies a file, or a directory and everything in it. Symlinks are copied
+/// as links rather than followed.
+pub fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(from)?;
+    if metadata.file_type().is_symlink() {
+        copy_symlink(from, to)
+    } else if metadata.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, metadata.permissions())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    fs::copy(from, to).map(|_| ())
+}
+
+/// Creates `link` as a symbolic link to `original`. Never replaces an
+/// existing entry.
+#[cfg(unix)]
+pub fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+/// Windows has separate links for files and directories, and only allows
+/// them with Developer Mode on or as an administrator.
+#[cfg(windows)]
+pub fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    if original.is_dir() {
+        std::os::windows::fs::symlink_dir(original, link)
+    } else {
+        std::os::windows::fs::symlink_file(original, link)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn symlink(_original: &Path, _link: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "symbolic links aren't supported on this system",
+    ))
+}
+
+/// Copies `path` next to itself under a free "copy" name and returns the
+/// new path.
+pub fn duplicate(path: &Path) -> io::Result<PathBuf> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "nothing to duplicate",
+        ));
+    };
+    let target = copy_name(dir, &name.to_string_lossy());
+    copy_recursive(path, &target)?;
+    Ok(target)
+}
+
+/// Creates `input` (which may be nested, like `a/b/c`) under `base` in the
+/// manner of `mkdir -p`, and returns the top-level component it created or
+/// reused. Absolute paths and `..` are refused so nothing lands outside
+/// `base`.
+pub fn create_dirs(base: &Path, input: &str) -> io::Result<PathBuf> {
+    let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidInput, reason.to_string());
+    let relative = Path::new(input.trim());
+
+    let components: Vec<Component> = relative
+        .components()
+        .filter(|c| *c != Component::CurDir)
+        .collect();
+    if components.contains(&Component::ParentDir) {
+        return Err(invalid("`..` would leave the current directory"));
+    }
+    let first = match components.first() {
+        Some(Component::Normal(first)) => *first,
+        Some(_) => return Err(invalid("use a path relative to the current directory")),
+        None => return Err(invalid("no directory name given")),
+    };
+
+    fs::create_dir_all(base.join(relative))?;
+    Ok(base.join(first))
+}
+
+/// Where `path` really is: its parent with `..` and symlinks resolved, and
+/// its own name kept so a symlink still names the link. Parents that don't
+/// exist yet are resolved as far up as they do.
+pub fn resolve(path: &Path) -> PathBuf {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => fs::canonicalize(parent)
+            .unwrap_or_else(|_| resolve(parent))
+            .join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Whether `a` and `b` name the same entry, however each is spelled.
+pub fn same_entry(a: &Path, b: &Path) -> bool {
+    a == b || resolve(a) == resolve(b)
+}
+
+/// Deletes a file, or a directory and everything in it. A symlink is
+/// removed itself, never what it points to.
+pub fn remove(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Whether `path` can be opened for writing. Asks the OS rather than reading
+/// the permission bits, so ACLs, read-only mounts and root all count.
+pub fn is_writable(path: &Path) -> bool {
+    fs::OpenOptions::new().write(true).open(path).is_ok()
+}
+
+/// Gives the owner write permission (`chmod u+w`). Only the owner, or root,
+/// may do this.
+pub fn make_writable(path: &Path) -> io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(permissions.mode() | 0o200);
+    }
+    #[cfg(not(unix))]
+    permissions.set_readonly(false);
+    fs::set_permissions(path, permissions)
+}
+
+/// A write or rename that could replace something already on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteOp {
+    Write {
+        path: PathBuf,
+        contents: Vec<u8>,
+    },
+    Rename {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// A file or a whole directory, replacing whatever `to` was
+    Copy {
+        from: PathBuf,
+        to: PathBuf,
+    },
+}
+
+impl WriteOp {
+    pub fn target(&self) -> &Path {
+        match self {
+            WriteOp::Write { path, .. } => path,
+            WriteOp::Rename { to, .. } | WriteOp::Copy { to, .. } => to,
+        }
+    }
+
+    /// Whether performing this would replace an existing file.
+    pub fn would_overwrite(&self) -> bool {
+        let replaces_other = match self {
+            WriteOp::Write { .. } => true,
+            WriteOp::Rename { from, to } | WriteOp::Copy { from, to } => !same_entry(from, to),
+        };
+        replaces_other && fs::symlink_metadata(self.target()).is_ok()
+    }
+
+    pub fn apply(&self) -> io::Result<()> {
+        match self {
+            WriteOp::Write { path, contents } => fs::write(path, contents),
+            WriteOp::Rename { from, to } => match fs::rename(from, to) {
+                // Across filesystems a move has to be a copy and a delete
+                Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                    copy_recursive(from, to)?;
+                    remove(from)
+                }
+                result => result,
+            },
+            WriteOp::Copy { from, to } => {
+                // Replacing `to` would delete `from` itself, and copying a
+                // directory into itself never ends
+                if resolve(to).starts_with(resolve(from)) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "can't copy an entry onto or into itself",
+                    ));
+                }
+                if fs::symlink_metadata(to).is_ok() {
+                    remove(to)?;
+                }
+                copy_recursive(from, to)
+            }
+        }
+    }
+}
+
+/// Holds back any write that would replace an existing file until the user
+/// confirms it. `T` tags the operation with whatever the caller needs to
+/// finish up afterwards.
+#[derive(Debug)]
+pub struct OverwriteGuard<T> {
+    pending: Option<(WriteOp, T)>,
+}
+
+impl<T> Default for OverwriteGuard<T> {
+    fn default() -> Self {
+        Self { pending: None }
+    }
+}
+
+impl<T> OverwriteGuard<T> {
+    /// Performs `op` at once when nothing would be replaced and hands it
+    /// back; otherwise parks it for `confirm` and returns `None`.
+    pub fn write(&mut self, op: WriteOp, tag: T) -> io::Result<Option<(WriteOp, T)>> {
+        if op.would_overwrite() {
+            self.pending = Some((op, tag));
+            return Ok(None);
+        }
+        op.apply()?;
+        Ok(Some((op, tag)))
+    }
+
+    /// The write waiting for confirmation, if any.
+    pub fn pending(&self) -> Option<&WriteOp> {
+        self.pending.as_ref().map(|(op, _)| op)
+    }
+
+    /// Performs the parked write, replacing the existing file.
+    pub fn confirm(&mut self) -> io::Result<Option<(WriteOp, T)>> {
+        let Some((op, tag)) = self.pending.take() else {
+            return Ok(None);
+        };
+        op.apply()?;
+        Ok(Some((op, tag)))
+    }
+
+    pub fn cancel(&mut self) -> Option<WriteOp> {
+        self.pending.take().map(|(op, _)| op)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_picks_the_next_free_name() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("src/nested")).unwrap();
+        fs::write(dir.join("notes.txt"), "hello").unwrap();
+        fs::write(dir.join("src/nested/lib.rs"), "fn main() {}").unwrap();
+
+        let first = duplicate(&dir.join("notes.txt")).unwrap();
+        let second = duplicate(&dir.join("notes.txt")).unwrap();
+        assert_eq!(first, dir.join("notes copy.txt"));
+        assert_eq!(second, dir.join("notes copy 2.txt"));
+        assert_eq!(fs::read_to_string(&second).unwrap(), "hello");
+
+        let copied_dir = duplicate(&dir.join("src")).unwrap();
+        assert_eq!(copied_dir, dir.join("src copy"));
+        assert_eq!(
+            fs::read_to_string(copied_dir.join("nested/lib.rs")).unwrap(),
+            "fn main() {}"
+        );
+
+        assert_eq!(copy_name(dir, ".bashrc"), dir.join(".bashrc copy"));
+    }
+
+    #[test]
+    fn test_remove_files_and_trees() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("tree/nested")).unwrap();
+        fs::write(dir.join("tree/nested/file"), "").unwrap();
+        fs::write(dir.join("single"), "").unwrap();
+
+        remove(&dir.join("single")).unwrap();
+        remove(&dir.join("tree")).unwrap();
+        assert!(!dir.join("single").exists());
+        assert!(!dir.join("tree").exists());
+        assert!(remove(&dir.join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_create_dirs() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+
+        assert_eq!(create_dirs(dir, "a/b/c").unwrap(), dir.join("a"));
+        assert!(dir.join("a/b/c").is_dir());
+        assert_eq!(create_dirs(dir, "./single").unwrap(), dir.join("single"));
+        // Existing levels are fine, like mkdir -p
+        assert_eq!(create_dirs(dir, "a/b/d").unwrap(), dir.join("a"));
+
+        for bad in ["", "../escape", "a/../../escape", "/tmp/absolute"] {
+            let err = create_dirs(dir, bad).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", bad);
+        }
+        assert!(!dir.join("../escape").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_make_writable_adds_owner_write() {
+        use std::os::unix::fs::PermissionsExt;
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let file = dir.join("locked.txt");
+        fs::write(&file, "text").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o444)).unwrap();
+
+        make_writable(&file).unwrap();
+        assert_eq!(
+            fs::metadata(&file).unwrap().permissions().mode() & 0o777,
+            0o644
+        );
+        assert!(is_writable(&file));
+        // Probing never creates anything
+        assert!(!is_writable(&dir.join("missing.txt")));
+        assert!(!dir.join("missing.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_points_at_the_original() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let original = dir.join("original.txt");
+        fs::write(&original, "text").unwrap();
+
+        symlink(&original, &dir.join("link.txt")).unwrap();
+        assert_eq!(fs::read_link(dir.join("link.txt")).unwrap(), original);
+        assert_eq!(fs::read_to_string(dir.join("link.txt")).unwrap(), "text");
+        // An existing name is left alone
+        let err = symlink(&original, &original).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&original).unwrap(), "text");
+    }
+
+    #[test]
+    fn test_overwrite_guard_blocks_until_confirmed() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let existing = dir.join("existing.txt");
+        fs::write(&existing, "keep me").unwrap();
+        let mut guard = OverwriteGuard::default();
+
+        // A fresh path goes straight through
+        let fresh = WriteOp::Write {
+            path: dir.join("fresh.txt"),
+            contents: b"new".to_vec(),
+        };
+        assert!(guard.write(fresh, "fresh").unwrap().is_some());
+        assert_eq!(fs::read_to_string(dir.join("fresh.txt")).unwrap(), "new");
+
+        // An existing one is untouched until confirmed
+        let clobber = WriteOp::Write {
+            path: existing.clone(),
+            contents: b"replaced".to_vec(),
+        };
+        assert!(guard.write(clobber.clone(), "save").unwrap().is_none());
+        assert_eq!(guard.pending(), Some(&clobber));
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "keep me");
+        assert_eq!(guard.cancel(), Some(clobber.clone()));
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "keep me");
+
+        // Renames onto an existing file wait too
+        let rename = WriteOp::Rename {
+            from: dir.join("fresh.txt"),
+            to: existing.clone(),
+        };
+        assert!(guard.write(rename, "rename").unwrap().is_none());
+        assert!(dir.join("fresh.txt").exists());
+        let (_, tag) = guard.confirm().unwrap().unwrap();
+        assert_eq!(tag, "rename");
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "new");
+        assert!(!dir.join("fresh.txt").exists());
+        assert!(guard.pending().is_none());
+    }
+
+    #[test]
+    fn test_copy_onto_itself_is_refused() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("tree")).unwrap();
+        let file = dir.join("notes.txt");
+        fs::write(&file, "keep me").unwrap();
+        let dir_name = dir.file_name().unwrap();
+
+        // The same file reached through `..` is neither an overwrite nor
+        // something to delete first
+        let onto_itself = WriteOp::Copy {
+            from: file.clone(),
+            to: dir.join("..").join(dir_name).join("notes.txt"),
+        };
+        assert!(!onto_itself.would_overwrite());
+        let err = onto_itself.apply().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "keep me");
+
+        let into_itself = WriteOp::Copy {
+            from: dir.join("tree"),
+            to: dir.join("tree/../tree/inner"),
+        };
+        assert!(into_itself.apply().is_err());
+        assert!(!dir.join("tree/inner").exists());
+    }
+}
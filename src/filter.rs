@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// Windows the "recently modified" key cycles through, shortest first.
+pub const AGE_WINDOWS: [Duration; 3] = [
+    Duration::from_secs(HOUR),
+    Duration::from_secs(DAY),
+    Duration::from_secs(WEEK),
+];
+
+/// How many entries of a directory each filter left out, in the order the
+/// listing applies them. An entry is only counted against the first filter
+/// that drops it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FilterCounts {
+    pub read: usize,
+    pub clutter: usize,
+    pub hidden: usize,
+    pub age: usize,
+    pub name: usize,
+}
+
+impl FilterCounts {
+    pub fn shown(self) -> usize {
+        self.read - self.clutter - self.hidden - self.age - self.name
+    }
+}
+
+/// Whether `name` passes the `/` filter: it contains `query`, ignoring
+/// case. An empty query lets everything through.
+pub fn name_matches(name: &str, query: &str) -> bool {
+    query.is_empty() || name.to_lowercase().contains(&query.to_lowercase())
+}
+
+/// Parses durations like `30m`, `2h`, `1d` or `2w`. A bare number is seconds.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+
+    let amount: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration `{}`", input))?;
+    let unit_secs = match unit {
+        "" | "s" => 1,
+        "m" => MINUTE,
+        "h" => HOUR,
+        "d" => DAY,
+        "w" => WEEK,
+        _ => return Err(format!("unknown unit `{}` (use s, m, h, d or w)", unit)),
+    };
+    amount
+        .checked_mul(unit_secs)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("duration `{}` is too long", input))
+}
+
+/// Formats a duration with the largest unit that divides it evenly.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    for (unit_secs, unit) in [(WEEK, "w"), (DAY, "d"), (HOUR, "h"), (MINUTE, "m")] {
+        if secs >= unit_secs && secs.is_multiple_of(unit_secs) {
+            return format!("{}{}", secs / unit_secs, unit);
+        }
+    }
+    format!("{}s", secs)
+}
+
+/// The next window after `current`, wrapping back to no filter.
+pub fn next_age_window(current: Option<Duration>) -> Option<Duration> {
+    match current {
+        None => Some(AGE_WINDOWS[0]),
+        Some(current) => AGE_WINDOWS.iter().copied().find(|w| *w > current),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(2 * HOUR)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(DAY)));
+        assert_eq!(parse_duration("2w"), Ok(Duration::from_secs(2 * WEEK)));
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("3y").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_refuses_overflow() {
+        let too_many_weeks = format!("{}w", u64::MAX / WEEK + 1);
+        assert_eq!(
+            parse_duration(&too_many_weeks),
+            Err(format!("duration `{}` is too long", too_many_weeks))
+        );
+        let max = format!("{}s", u64::MAX);
+        assert_eq!(parse_duration(&max), Ok(Duration::from_secs(u64::MAX)));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(DAY)), "1d");
+        assert_eq!(format_duration(Duration::from_secs(36 * HOUR)), "36h");
+        assert_eq!(format_duration(Duration::from_secs(90)), "90s");
+    }
+
+    #[test]
+    fn test_age_windows_cycle() {
+        let mut window = None;
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            window = next_age_window(window);
+            seen.push(window);
+        }
+        assert_eq!(
+            seen,
+            vec![
+                Some(AGE_WINDOWS[0]),
+                Some(AGE_WINDOWS[1]),
+                Some(AGE_WINDOWS[2]),
+                None
+            ]
+        );
+        // A custom --newer-than window joins the cycle at the next larger step
+        assert_eq!(
+            next_age_window(Some(Duration::from_secs(2 * HOUR))),
+            Some(AGE_WINDOWS[1])
+        );
+    }
+
+    #[test]
+    fn test_name_filter_ignores_case() {
+        assert!(name_matches("README.md", "readme"));
+        assert!(name_matches("src/Main.rs", "MAIN"));
+        assert!(name_matches("Ärger.txt", "äR"));
+        assert!(name_matches("anything", ""));
+        assert!(!name_matches("Cargo.toml", "lock"));
+    }
+}
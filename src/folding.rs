@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+/// Collapsed line ranges in the viewer. Each fold keeps its header line
+/// visible and hides the indented block below it.
+#[derive(Debug, Clone, Default)]
+pub struct Folds {
+    // Header line -> last hidden line, both 0-based
+    ranges: BTreeMap<usize, usize>,
+}
+
+impl Folds {
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Whether `line` is inside a collapsed block.
+    pub fn is_hidden(&self, line: usize) -> bool {
+        self.ranges
+            .range(..line)
+            .any(|(&start, &end)| start < line && line <= end)
+    }
+
+    /// How many lines are collapsed under `line`, if it heads a fold.
+    pub fn hidden_under(&self, line: usize) -> Option<usize> {
+        self.ranges.get(&line).map(|end| end - line)
+    }
+
+    /// Indices of the lines still shown out of `total`.
+    pub fn visible_lines(&self, total: usize) -> Vec<usize> {
+        (0..total).filter(|&line| !self.is_hidden(line)).collect()
+    }
+
+    /// Collapses the first unfolded block headed by a visible line in
+    /// `from..to`, returning its header.
+    pub fn fold_first(&mut self, lines: &[&str], from: usize, to: usize) -> Option<usize> {
+        let header = (from..to.min(lines.len())).find(|&line| {
+            !self.is_hidden(line)
+                && !self.ranges.contains_key(&line)
+                && block_end(lines, line).is_some()
+        })?;
+        let end = block_end(lines, header)?;
+        // Folds nested inside the new one would only shadow it
+        self.ranges
+            .retain(|&start, _| start <= header || start > end);
+        self.ranges.insert(header, end);
+        Some(header)
+    }
+
+    /// Expands every fold that hides `line`.
+    pub fn reveal(&mut self, line: usize) {
+        self.ranges
+            .retain(|&start, &mut end| !(start < line && line <= end));
+    }
+}
+
+/// The last line of the block under `header`: the following lines that are
+/// blank or indented deeper than it, minus trailing blanks. `None` when
+/// nothing would be hidden.
+pub fn block_end(lines: &[&str], header: usize) -> Option<usize> {
+    let text = lines.get(header)?;
+    if text.trim().is_empty() {
+        return None;
+    }
+    let indent = indentation(text);
+
+    let mut end = None;
+    for (line, text) in lines.iter().enumerate().skip(header + 1) {
+        if text.trim().is_empty() {
+            continue;
+        }
+        if indentation(text) <= indent {
+            break;
+        }
+        end = Some(line);
+    }
+    end
+}
+
+// Tabs count as four columns so mixed indentation still nests sensibly
+fn indentation(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &[&str] = &[
+        "fn main() {",
+        "    if ready {",
+        "        go();",
+        "",
+        "    }",
+        "}",
+        "",
+        "fn other() {}",
+    ];
+
+    #[test]
+    fn test_block_end_follows_indentation() {
+        assert_eq!(block_end(SOURCE, 0), Some(4));
+        assert_eq!(block_end(SOURCE, 1), Some(2));
+        assert_eq!(block_end(SOURCE, 2), None);
+        assert_eq!(block_end(SOURCE, 6), None);
+        assert_eq!(block_end(SOURCE, 7), None);
+    }
+
+    #[test]
+    fn test_fold_hides_block_and_reveal_expands_it() {
+        let mut folds = Folds::default();
+        assert_eq!(folds.fold_first(SOURCE, 1, 8), Some(1));
+        assert_eq!(folds.hidden_under(1), Some(1));
+        assert_eq!(folds.fold_first(SOURCE, 0, 8), Some(0));
+        // The outer fold swallows the inner one
+        assert_eq!(folds.hidden_under(1), None);
+        assert_eq!(folds.visible_lines(SOURCE.len()), vec![0, 5, 6, 7]);
+
+        folds.reveal(3);
+        assert_eq!(folds.visible_lines(SOURCE.len()).len(), SOURCE.len());
+    }
+}
@@ -0,0 +1,65 @@
+/// What the footer shows when `footer_format` isn't set: the key hints for
+/// whatever has focus.
+pub const DEFAULT_FORMAT: &str = "{hints}";
+
+/// Fills in each `{name}` of a `footer_format` with `value(name)`. Names it
+/// doesn't know are left as written so a typo shows up on screen; `{{` and
+/// `}}` stand for literal braces.
+pub fn render(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        if rest[start..].starts_with("{{") || rest[start..].starts_with("}}") {
+            out.push_str(&rest[start..start + 1]);
+            rest = &after[1..];
+            continue;
+        }
+
+        match after.find('}').filter(|_| rest[start..].starts_with('{')) {
+            Some(end) => {
+                let name = &after[..end];
+                match value(name) {
+                    Some(text) => out.push_str(&text),
+                    None => out.push_str(&rest[start..start + end + 2]),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..start + 1]);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(name: &str) -> Option<String> {
+        match name {
+            "path" => Some("/home/me".to_string()),
+            "count" => Some("12".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_placeholders_are_filled_in() {
+        assert_eq!(render("{path} ({count})", value), "/home/me (12)");
+        assert_eq!(render("no placeholders", value), "no placeholders");
+        assert_eq!(render("", value), "");
+    }
+
+    #[test]
+    fn test_unknown_and_escaped_braces_are_kept() {
+        assert_eq!(render("{nope} {count}", value), "{nope} 12");
+        assert_eq!(render("{{count}} {count}", value), "{count} 12");
+        assert_eq!(render("unclosed {count", value), "unclosed {count");
+        assert_eq!(render("stray } brace", value), "stray } brace");
+    }
+}
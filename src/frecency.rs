@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Least useful entries are dropped past this so the file stays small
+const MAX_ENTRIES: usize = 1000;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Visit {
+    count: u32,
+    /// Seconds since the Unix epoch
+    last_opened: u64,
+}
+
+/// How often and how recently files were opened, keyed by absolute path.
+/// Used to float the files you actually work on to the top of the finder.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Frecency {
+    visits: HashMap<PathBuf, Visit>,
+}
+
+impl Frecency {
+    /// `~/.local/share/ls-pretty/frecency.json` on Linux, the platform
+    /// equivalent elsewhere.
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("ls-pretty").join("frecency.json"))
+    }
+
+    /// A missing or unreadable store starts out empty.
+    pub fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    pub fn record(&mut self, path: &Path, now: u64) {
+        let visit = self.visits.entry(path.to_path_buf()).or_insert(Visit {
+            count: 0,
+            last_opened: now,
+        });
+        visit.count += 1;
+        visit.last_opened = now;
+
+        if self.visits.len() > MAX_ENTRIES {
+            let weakest = self
+                .visits
+                .iter()
+                .min_by(|a, b| score(a.1, now).total_cmp(&score(b.1, now)))
+                .map(|(path, _)| path.clone());
+            if let Some(weakest) = weakest {
+                self.visits.remove(&weakest);
+            }
+        }
+    }
+
+    /// Every file opened so far with when it was last opened, newest first.
+    pub fn recent(&self) -> Vec<(PathBuf, u64)> {
+        let mut recent: Vec<(PathBuf, u64)> = self
+            .visits
+            .iter()
+            .map(|(path, visit)| (path.clone(), visit.last_opened))
+            .collect();
+        recent.sort_by_key(|(_, last_opened)| std::cmp::Reverse(*last_opened));
+        recent
+    }
+
+    /// Forgets every file ever opened.
+    pub fn clear(&mut self) {
+        self.visits.clear();
+    }
+
+    /// Open count weighted by how recently the file was last opened; 0 for
+    /// files never opened.
+    pub fn score(&self, path: &Path, now: u64) -> f64 {
+        self.visits.get(path).map_or(0.0, |visit| score(visit, now))
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn score(visit: &Visit, now: u64) -> f64 {
+    let age = now.saturating_sub(visit.last_opened);
+    let weight = if age < HOUR {
+        4.0
+    } else if age < DAY {
+        2.0
+    } else if age < WEEK {
+        0.5
+    } else {
+        0.25
+    };
+    visit.count as f64 * weight
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frequent_and_recent_files_score_higher() {
+        let now = 10 * WEEK;
+        let mut frecency = Frecency::default();
+        let (often, once, stale) = (Path::new("/a"), Path::new("/b"), Path::new("/c"));
+        for _ in 0..3 {
+            frecency.record(often, now);
+        }
+        frecency.record(once, now);
+        for _ in 0..3 {
+            frecency.record(stale, now - 2 * WEEK);
+        }
+
+        assert!(frecency.score(often, now) > frecency.score(once, now));
+        assert!(frecency.score(once, now) > frecency.score(stale, now));
+        assert_eq!(frecency.score(Path::new("/never"), now), 0.0);
+    }
+
+    #[test]
+    fn test_recent_lists_newest_first_until_cleared() {
+        let mut frecency = Frecency::default();
+        frecency.record(Path::new("/old"), 10);
+        frecency.record(Path::new("/new"), 30);
+        frecency.record(Path::new("/middle"), 20);
+        let order: Vec<PathBuf> = frecency.recent().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(
+            order,
+            [
+                PathBuf::from("/new"),
+                PathBuf::from("/middle"),
+                PathBuf::from("/old")
+            ]
+        );
+
+        frecency.clear();
+        assert!(frecency.recent().is_empty());
+    }
+
+    #[test]
+    fn test_round_trips_through_disk() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("state").join("frecency.json");
+        let mut frecency = Frecency::default();
+        frecency.record(Path::new("/src/main.rs"), 100);
+        frecency.save_to(&path).unwrap();
+
+        let loaded = Frecency::load_from(&path);
+        assert_eq!(loaded.score(Path::new("/src/main.rs"), 100), 4.0);
+        assert!(
+            Frecency::load_from(Path::new("/nonexistent/frecency.json"))
+                .visits
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use ratatui::style::Color;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Branch and working tree state of the repository containing a directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoSummary {
+    pub branch: String,
+    pub dirty: bool,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+impl RepoSummary {
+    pub fn header_label(&self) -> String {
+        let mut label = format!(" {} {}", self.branch, if self.dirty { "●" } else { "✓" });
+        if self.ahead > 0 {
+            label.push_str(&format!(" ↑{}", self.ahead));
+        }
+        if self.behind > 0 {
+            label.push_str(&format!(" ↓{}", self.behind));
+        }
+        label
+    }
+}
+
+/// How a file differs from the last commit. Variants are ordered by how much
+/// they matter, so a directory shows the most pressing state inside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+pub enum GitState {
+    Untracked,
+    Deleted,
+    Renamed,
+    Added,
+    Modified,
+    Conflicted,
+}
+
+impl GitState {
+    /// The marker shown next to the entry, as `git status --short` has it
+    pub fn marker(self) -> &'static str {
+        match self {
+            GitState::Untracked => "??",
+            GitState::Deleted => "D",
+            GitState::Renamed => "R",
+            GitState::Added => "A",
+            GitState::Modified => "M",
+            GitState::Conflicted => "U",
+        }
+    }
+
+    /// Green for staged additions, red for what git doesn't know about or
+    /// can't merge, yellow for edits
+    pub fn color(self) -> Color {
+        match self {
+            GitState::Untracked | GitState::Deleted | GitState::Conflicted => Color::Red,
+            GitState::Renamed => Color::Cyan,
+            GitState::Added => Color::Green,
+            GitState::Modified => Color::Yellow,
+        }
+    }
+}
+
+/// Changed paths under a repository, with every directory above a change
+/// (up to, not including, `workdir`) carrying the weightiest state below it.
+#[cfg(any(feature = "git", test))]
+fn roll_up(workdir: &Path, changes: Vec<(PathBuf, GitState)>) -> HashMap<PathBuf, GitState> {
+    let mut states = HashMap::new();
+    for (relative, state) in changes {
+        let mut path = workdir.to_path_buf();
+        for component in relative.components() {
+            path.push(component);
+            let entry = states.entry(path.clone()).or_insert(state);
+            *entry = (*entry).max(state);
+        }
+    }
+    states
+}
+
+/// Looks up the repository `path` belongs to, and the state of each changed
+/// file in it keyed by absolute path. Returns `None` outside a repo or when
+/// git support is compiled out.
+#[cfg(feature = "git")]
+pub fn summarize(path: &Path) -> Option<(RepoSummary, HashMap<PathBuf, GitState>)> {
+    use git2::{BranchType, ErrorCode, Repository, StatusOptions};
+
+    let repo = Repository::discover(path).ok()?;
+    if repo.is_bare() {
+        return None;
+    }
+
+    let (branch, head_oid) = match repo.head() {
+        Ok(head) if head.is_branch() => (head.shorthand()?.to_string(), head.target()),
+        Ok(head) => {
+            // Detached HEAD: show the abbreviated commit instead of a name
+            let oid = head.target()?;
+            (oid.to_string()[..7].to_string(), Some(oid))
+        }
+        // A fresh `git init` has a HEAD pointing at a branch with no commits
+        Err(e) if e.code() == ErrorCode::UnbornBranch => {
+            let head = repo.find_reference("HEAD").ok()?;
+            let target = head.symbolic_target()?;
+            (target.trim_start_matches("refs/heads/").to_string(), None)
+        }
+        Err(_) => return None,
+    };
+
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(false)
+        .exclude_submodules(true);
+    let changes: Vec<(PathBuf, GitState)> = repo
+        .statuses(Some(&mut options))
+        .map(|statuses| {
+            statuses
+                .iter()
+                .filter_map(|entry| {
+                    let path = PathBuf::from(entry.path()?);
+                    Some((path, state_of(entry.status())?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let dirty = !changes.is_empty();
+
+    let (ahead, behind) = head_oid
+        .and_then(|local| {
+            let upstream = repo
+                .find_branch(&branch, BranchType::Local)
+                .ok()?
+                .upstream()
+                .ok()?;
+            let upstream_oid = upstream.get().target()?;
+            repo.graph_ahead_behind(local, upstream_oid).ok()
+        })
+        .unwrap_or((0, 0));
+
+    let summary = RepoSummary {
+        branch,
+        dirty,
+        ahead,
+        behind,
+    };
+    let states = repo
+        .workdir()
+        .map(|workdir| roll_up(workdir, changes))
+        .unwrap_or_default();
+    Some((summary, states))
+}
+
+#[cfg(feature = "git")]
+fn state_of(status: git2::Status) -> Option<GitState> {
+    use git2::Status;
+
+    if status.is_conflicted() {
+        Some(GitState::Conflicted)
+    } else if status.intersects(
+        Status::INDEX_MODIFIED
+            | Status::WT_MODIFIED
+            | Status::INDEX_TYPECHANGE
+            | Status::WT_TYPECHANGE,
+    ) {
+        Some(GitState::Modified)
+    } else if status.is_index_new() {
+        Some(GitState::Added)
+    } else if status.intersects(Status::INDEX_RENAMED | Status::WT_RENAMED) {
+        Some(GitState::Renamed)
+    } else if status.intersects(Status::INDEX_DELETED | Status::WT_DELETED) {
+        Some(GitState::Deleted)
+    } else if status.is_wt_new() {
+        Some(GitState::Untracked)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(feature = "git"))]
+pub fn summarize(_path: &Path) -> Option<(RepoSummary, HashMap<PathBuf, GitState>)> {
+    None
+}
+
+/// Working directory root of the repository containing `path`.
+#[cfg(feature = "git")]
+pub fn repo_root(path: &Path) -> Option<PathBuf> {
+    let repo = git2::Repository::discover(path).ok()?;
+    repo.workdir().map(Path::to_path_buf)
+}
+
+#[cfg(not(feature = "git"))]
+pub fn repo_root(_path: &Path) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_label() {
+        let mut summary = RepoSummary {
+            branch: "main".to_string(),
+            dirty: false,
+            ahead: 0,
+            behind: 0,
+        };
+        assert_eq!(summary.header_label(), " main ✓");
+
+        summary.dirty = true;
+        summary.ahead = 2;
+        summary.behind = 1;
+        assert_eq!(summary.header_label(), " main ● ↑2 ↓1");
+    }
+
+    #[test]
+    fn test_directories_take_the_weightiest_state_below() {
+        let workdir = Path::new("/repo");
+        let states = roll_up(
+            workdir,
+            vec![
+                (PathBuf::from("src/new.rs"), GitState::Untracked),
+                (PathBuf::from("src/main.rs"), GitState::Modified),
+                (PathBuf::from("docs/"), GitState::Untracked),
+                (PathBuf::from("README.md"), GitState::Added),
+            ],
+        );
+
+        assert_eq!(states[&workdir.join("src")], GitState::Modified);
+        assert_eq!(states[&workdir.join("src/new.rs")], GitState::Untracked);
+        assert_eq!(states[&workdir.join("docs")], GitState::Untracked);
+        assert_eq!(states[&workdir.join("README.md")].marker(), "A");
+        assert!(!states.contains_key(workdir));
+    }
+}
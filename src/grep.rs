@@ -0,0 +1,109 @@
+use ignore::WalkBuilder;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+// Long minified lines would otherwise swamp the results popup
+const MAX_PREVIEW_CHARS: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepMatch {
+    pub path: PathBuf,
+    /// Zero-based, like the editor's cursor line
+    pub line: usize,
+    pub text: String,
+}
+
+/// Searches text files under `root` on a background thread, honouring
+/// `.gitignore`. Matches stream through the returned channel, which
+/// disconnects when the walk finishes. Dropping the receiver stops the search.
+pub fn spawn_search(
+    root: PathBuf,
+    query: String,
+    is_text: fn(&Path) -> bool,
+) -> Receiver<GrepMatch> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        // Smart case, like ripgrep: only an uppercase letter makes it exact
+        let case_sensitive = query.chars().any(char::is_uppercase);
+        let needle = if case_sensitive {
+            query
+        } else {
+            query.to_lowercase()
+        };
+
+        for entry in WalkBuilder::new(&root).build().flatten() {
+            let path = entry.path();
+            if !entry.file_type().is_some_and(|t| t.is_file()) || !is_text(path) {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(path) else {
+                continue;
+            };
+
+            for (line, text) in content.lines().enumerate() {
+                let found = if case_sensitive {
+                    text.contains(&needle)
+                } else {
+                    text.to_lowercase().contains(&needle)
+                };
+                if !found {
+                    continue;
+                }
+
+                let grep_match = GrepMatch {
+                    path: path.to_path_buf(),
+                    line,
+                    text: text.trim().chars().take(MAX_PREVIEW_CHARS).collect(),
+                };
+                if sender.send(grep_match).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(root: &Path, query: &str) -> Vec<(String, usize)> {
+        let mut matches: Vec<(String, usize)> =
+            spawn_search(root.to_path_buf(), query.to_string(), |path| {
+                path.extension().is_some_and(|ext| ext == "txt")
+            })
+            .iter()
+            .map(|m| {
+                let name = m.path.file_name().unwrap().to_string_lossy().to_string();
+                (name, m.line)
+            })
+            .collect();
+        matches.sort();
+        matches
+    }
+
+    #[test]
+    fn test_finds_lines_with_smart_case() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "hello\nHello world\nbye\n").unwrap();
+        fs::write(dir.join("sub/b.txt"), "say hello\n").unwrap();
+        fs::write(dir.join("c.bin"), "hello\n").unwrap();
+
+        assert_eq!(
+            search(dir, "hello"),
+            vec![
+                ("a.txt".to_string(), 0),
+                ("a.txt".to_string(), 1),
+                ("b.txt".to_string(), 0)
+            ]
+        );
+        assert_eq!(search(dir, "Hello"), vec![("a.txt".to_string(), 1)]);
+    }
+}
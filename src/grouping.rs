@@ -0,0 +1,67 @@
+use std::path::Path;
+
+/// Sections of the grouped listing, in display order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FileGroup {
+    Directories,
+    Source,
+    Images,
+    Other,
+}
+
+impl FileGroup {
+    pub fn of(path: &Path, is_dir: bool) -> Self {
+        if is_dir {
+            return FileGroup::Directories;
+        }
+        let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+            return FileGroup::Other;
+        };
+        match extension.to_lowercase().as_str() {
+            "rs" | "py" | "js" | "ts" | "jsx" | "tsx" | "go" | "c" | "h" | "cpp" | "hpp" | "cc"
+            | "java" | "kt" | "rb" | "php" | "swift" | "cs" | "sh" | "bash" | "zsh" | "fish"
+            | "lua" | "html" | "css" | "scss" | "sql" => FileGroup::Source,
+            "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "bmp" | "ico" | "tiff" => {
+                FileGroup::Images
+            }
+            _ => FileGroup::Other,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FileGroup::Directories => "Directories",
+            FileGroup::Source => "Source",
+            FileGroup::Images => "Images",
+            FileGroup::Other => "Other",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_groups_by_kind_and_orders_directories_first() {
+        assert_eq!(
+            FileGroup::of(Path::new("src"), true),
+            FileGroup::Directories
+        );
+        assert_eq!(
+            FileGroup::of(Path::new("main.RS"), false),
+            FileGroup::Source
+        );
+        assert_eq!(
+            FileGroup::of(Path::new("logo.png"), false),
+            FileGroup::Images
+        );
+        assert_eq!(FileGroup::of(Path::new("LICENSE"), false), FileGroup::Other);
+        assert_eq!(
+            FileGroup::of(Path::new("notes.txt"), false),
+            FileGroup::Other
+        );
+        assert!(FileGroup::Directories < FileGroup::Source);
+        assert!(FileGroup::Images < FileGroup::Other);
+    }
+}
@@ -0,0 +1,43 @@
+use ratatui::style::Color;
+use std::time::Duration;
+
+// Ages at which the gradient starts and bottoms out
+const HOTTEST: Duration = Duration::from_secs(60);
+const COLDEST: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+const HOT: (u8, u8, u8) = (255, 196, 0);
+const COLD: (u8, u8, u8) = (88, 88, 88);
+
+/// Bright for files modified moments ago, fading to grey over a year. The
+/// scale is logarithmic so minutes, days and months are all distinguishable.
+pub fn heat_color(age: Duration) -> Color {
+    let span = (COLDEST.as_secs_f64() / HOTTEST.as_secs_f64()).ln();
+    let t = (age.max(HOTTEST).as_secs_f64() / HOTTEST.as_secs_f64()).ln() / span;
+    let t = t.clamp(0.0, 1.0);
+
+    let mix = |hot: u8, cold: u8| (hot as f64 + (cold as f64 - hot as f64) * t).round() as u8;
+    Color::Rgb(mix(HOT.0, COLD.0), mix(HOT.1, COLD.1), mix(HOT.2, COLD.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heat_fades_with_age() {
+        let hot = Color::Rgb(HOT.0, HOT.1, HOT.2);
+        let cold = Color::Rgb(COLD.0, COLD.1, COLD.2);
+        assert_eq!(heat_color(Duration::ZERO), hot);
+        assert_eq!(heat_color(HOTTEST), hot);
+        assert_eq!(heat_color(COLDEST), cold);
+        assert_eq!(heat_color(COLDEST * 10), cold);
+
+        let Color::Rgb(red_day, ..) = heat_color(Duration::from_secs(24 * 60 * 60)) else {
+            unreachable!()
+        };
+        let Color::Rgb(red_month, ..) = heat_color(Duration::from_secs(30 * 24 * 60 * 60)) else {
+            unreachable!()
+        };
+        assert!(HOT.0 > red_day && red_day > red_month && red_month > COLD.0);
+    }
+}
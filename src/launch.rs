@@ -0,0 +1,90 @@
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Opens `path` with the platform's default application, without waiting
+/// for it to exit.
+pub fn open_externally(path: &Path) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        // The empty argument is `start`'s window title
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+
+    command.arg(path);
+    spawn_detached(command)
+}
+
+/// Shows `path` in the graphical file manager: selected inside its folder
+/// on macOS and Windows, and on other systems by opening the directory
+/// itself (or a file's parent), since `xdg-open` can't select.
+pub fn reveal_in_file_manager(path: &Path) -> io::Result<()> {
+    let command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    } else if cfg!(windows) {
+        let mut command = Command::new("explorer");
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(path);
+        command.arg(select);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(path)
+        });
+        command
+    };
+    spawn_detached(command)
+}
+
+/// Runs a command line through the shell, without waiting for it to exit.
+pub fn run_detached(command_line: &str) -> io::Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    command.arg(command_line);
+    spawn_detached(command)
+}
+
+fn spawn_detached(mut command: Command) -> io::Result<()> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+/// Quotes `path` so a POSIX shell runs it as a single word.
+pub fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote(Path::new("/tmp/run.sh")), "'/tmp/run.sh'");
+        assert_eq!(
+            shell_quote(Path::new("/tmp/it's here.sh")),
+            r"'/tmp/it'\''s here.sh'"
+        );
+    }
+}
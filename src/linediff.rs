@@ -0,0 +1,192 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+// Past this many cells in the LCS table the changed region is just paired
+// up line by line instead
+const MAX_TABLE: usize = 1_000_000;
+
+/// Wait this long after the last edit before diffing again, so typing
+/// doesn't rediff on every key
+pub const DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// How a line of the buffer differs from the file as it was opened or last
+/// saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Modified,
+    /// Lines were deleted right below this one (above it, for the first line)
+    Removed,
+}
+
+/// One entry per line of `current`: what changed there compared to
+/// `original`, or `None` where the line is untouched.
+pub fn changes(original: &str, current: &str) -> Vec<Option<Change>> {
+    let old: Vec<&str> = original.lines().collect();
+    let new: Vec<&str> = current.lines().collect();
+    let mut marks = vec![None; new.len()];
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut hunk = |old_start: usize, old_end: usize, new_start: usize, new_end: usize| {
+        let (removed, added) = (old_end - old_start, new_end - new_start);
+        for (offset, mark) in marks[prefix + new_start..prefix + new_end]
+            .iter_mut()
+            .enumerate()
+        {
+            *mark = Some(if offset < removed {
+                Change::Modified
+            } else {
+                Change::Added
+            });
+        }
+        if added == 0 && removed > 0 {
+            let line = (prefix + new_start).saturating_sub(1);
+            if let Some(mark) = marks.get_mut(line) {
+                mark.get_or_insert(Change::Removed);
+            }
+        }
+    };
+
+    if old_mid.len() * new_mid.len() > MAX_TABLE {
+        hunk(0, old_mid.len(), 0, new_mid.len());
+        return marks;
+    }
+
+    // lcs[i][j]: longest common subsequence of old_mid[i..] and new_mid[j..]
+    let width = new_mid.len() + 1;
+    let mut lcs = vec![0u32; (old_mid.len() + 1) * width];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lcs[i * width + j] = if old_mid[i] == new_mid[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let (mut hunk_i, mut hunk_j) = (0, 0);
+    while i < old_mid.len() && j < new_mid.len() {
+        if old_mid[i] == new_mid[j] {
+            if (hunk_i, hunk_j) != (i, j) {
+                hunk(hunk_i, i, hunk_j, j);
+            }
+            i += 1;
+            j += 1;
+            (hunk_i, hunk_j) = (i, j);
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    if (hunk_i, hunk_j) != (old_mid.len(), new_mid.len()) {
+        hunk(hunk_i, old_mid.len(), hunk_j, new_mid.len());
+    }
+    marks
+}
+
+/// The change markers for one buffer, rediffed once edits have settled.
+#[derive(Debug, Clone, Default)]
+pub struct GutterMarks {
+    marks: Vec<Option<Change>>,
+    // Content hash the marks were computed for
+    diffed: Option<u64>,
+    // Latest content hash seen, and when it first appeared
+    seen: Option<(u64, Instant)>,
+}
+
+impl GutterMarks {
+    pub fn get(&self, line: usize) -> Option<Change> {
+        self.marks.get(line).copied().flatten()
+    }
+
+    /// Brings the marks up to date with `current`. Returns how much longer
+    /// to wait when a rediff is held back by the debounce.
+    pub fn refresh(&mut self, original: &str, current: &str, now: Instant) -> Option<Duration> {
+        let hash = content_hash(current);
+        if self.diffed == Some(hash) {
+            return None;
+        }
+        if current == original {
+            self.marks.clear();
+            self.diffed = Some(hash);
+            return None;
+        }
+        let since = match self.seen {
+            Some((seen, since)) if seen == hash => since,
+            _ => {
+                self.seen = Some((hash, now));
+                now
+            }
+        };
+        let waited = now.duration_since(since);
+        if waited < DEBOUNCE {
+            return Some(DEBOUNCE - waited);
+        }
+        self.marks = changes(original, current);
+        self.diffed = Some(hash);
+        None
+    }
+}
+
+fn content_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marks_added_modified_and_removed_lines() {
+        let original = "a\nb\nc\nd\ne\n";
+        assert_eq!(changes(original, original), vec![None; 5]);
+        assert_eq!(
+            changes(original, "a\nB\nc\nnew\nd\ne\n"),
+            [
+                None,
+                Some(Change::Modified),
+                None,
+                Some(Change::Added),
+                None,
+                None
+            ]
+        );
+        assert_eq!(
+            changes(original, "a\nd\ne\n"),
+            [Some(Change::Removed), None, None]
+        );
+        assert_eq!(changes(original, "b\nc\nd\ne\n")[0], Some(Change::Removed));
+    }
+
+    #[test]
+    fn test_refresh_waits_for_edits_to_settle() {
+        let start = Instant::now();
+        let mut gutter = GutterMarks::default();
+        assert_eq!(gutter.refresh("a\n", "a\n", start), None);
+        assert_eq!(gutter.get(0), None);
+
+        assert_eq!(gutter.refresh("a\n", "b\n", start), Some(DEBOUNCE));
+        assert_eq!(gutter.get(0), None);
+        assert_eq!(gutter.refresh("a\n", "b\n", start + DEBOUNCE), None);
+        assert_eq!(gutter.get(0), Some(Change::Modified));
+
+        // Back to what's on disk clears them at once
+        assert_eq!(gutter.refresh("a\n", "a\n", start + DEBOUNCE), None);
+        assert_eq!(gutter.get(0), None);
+    }
+}
@@ -1,4 +1,39 @@
+mod attributes;
+mod checksum;
+mod clipboard;
+mod config;
+mod editor;
+mod encoding;
+mod export;
+mod fileops;
+mod filter;
+mod folding;
+mod footer;
+mod frecency;
+mod git;
+mod grep;
+mod grouping;
+mod heat;
+mod launch;
+mod linediff;
+mod markdown;
+mod media;
+mod minimap;
+mod modeline;
+mod notifications;
+mod paths;
+mod pdf;
+mod server;
+mod shortcut;
+mod signals;
+mod size;
+mod sorting;
 mod tabs;
+mod tail;
+mod themes;
+mod transfer;
+mod undo;
+mod walk;
 
 #[cfg(feature = "tabs-demo")]
 mod tabs_demo;
@@ -7,13 +42,13 @@ use anyhow::Result as AppResult;
 use clap::Parser;
 use crossterm::{
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseButton,
-        MouseEventKind, poll,
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind, poll,
     },
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
 use lsp_types::{
     CompletionParams, DidChangeTextDocumentParams, DidOpenTextDocumentParams, InitializeParams,
     Position, TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
@@ -23,7 +58,7 @@ use portable_pty::{CommandBuilder, MasterPty, PtySize};
 use ratatui::{
     Frame, Terminal,
     backend::{Backend, CrosstermBackend},
-    layout::{Alignment, Constraint, Direction, Layout, Margin},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{
@@ -36,16 +71,28 @@ use std::process::Stdio;
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 
+use config::{ClutterMode, Config, FileAction};
+use filter::FilterCounts;
+use grouping::FileGroup;
+use size::SizeUnits;
+use sorting::{DirOrder, SortFields, SortKey, SortMode, natural_cmp};
 use std::{
+    collections::{HashMap, HashSet},
     fs::{self, DirEntry, Metadata},
     io,
-    path::PathBuf,
+    path::{Path, PathBuf},
     time::SystemTime,
 };
-use syntect::{easy::HighlightLines, highlighting::ThemeSet, parsing::SyntaxSet};
-use tabs::{Tab, TabManager};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::ThemeSet,
+    parsing::{SyntaxReference, SyntaxSet},
+};
+use tabs::{Tab, TabError, TabManager};
 use tokio::io::AsyncWriteExt;
 use tokio::process::{Child, ChildStdin, ChildStdout};
+use transfer::{ConflictPolicy, Resolution, Transfer, TransferKind};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 use url::Url as UrlType;
 
 #[derive(Debug, Clone, Copy)]
@@ -67,7 +114,6 @@ struct SearchMatch {
 struct CompletionCandidate {
     label: String,
     detail: Option<String>,
-    kind: Option<String>,
     insert_text: Option<String>,
 }
 
@@ -88,7 +134,6 @@ enum LspStatus {
     Starting,
     Running,
     Failed(String),
-    Stopped,
 }
 
 impl LspClient {
@@ -161,8 +206,6 @@ impl LspClient {
     async fn initialize(&mut self) -> AppResult<()> {
         let initialize_params = InitializeParams {
             process_id: Some(std::process::id()),
-            root_path: None,
-            root_uri: None,
             initialization_options: None,
             capabilities: lsp_types::ClientCapabilities {
                 text_document: Some(lsp_types::TextDocumentClientCapabilities {
@@ -182,6 +225,7 @@ impl LspClient {
             client_info: None,
             locale: None,
             work_done_progress_params: Default::default(),
+            ..Default::default()
         };
 
         self.send_request("initialize", initialize_params).await?;
@@ -276,7 +320,7 @@ impl LspClient {
         self.send_request("textDocument/completion", params).await
     }
 
-    fn is_go_file(path: &PathBuf) -> bool {
+    fn is_go_file(path: &Path) -> bool {
         path.extension()
             .and_then(|ext| ext.to_str())
             .map(|ext| ext.to_lowercase() == "go")
@@ -288,23 +332,163 @@ impl LspClient {
 #[command(name = "ls-pretty")]
 #[command(about = "A beautiful TUI file browser")]
 struct Args {
-    /// Directory to browse
+    /// Directory to browse, or a file to open
     #[arg(default_value = ".")]
     path: PathBuf,
 
+    /// Line to open the file at; `+N` works too
+    #[arg(long, value_name = "N")]
+    line: Option<usize>,
+
     /// Show hidden files
     #[arg(short = 'a', long)]
     all: bool,
 
+    /// Column to sort by
+    #[arg(short = 's', long, value_enum, value_name = "KEY")]
+    sort: Option<SortKey>,
+
+    /// Sort in descending order
+    #[arg(short = 'r', long)]
+    reverse: bool,
+
     /// Show file sizes in human readable format
     #[arg(short = 'H', long)]
     human_readable: bool,
 
+    /// Human readable sizes in powers of 1000 (kB, MB, GB) instead of 1024
+    #[arg(long)]
+    si: bool,
+
     /// Simple list mode (no TUI)
     #[arg(short = 'l', long)]
     list: bool,
+
+    /// Show inode numbers and hard-link counts (Unix)
+    #[arg(short = 'i', long)]
+    inode: bool,
+
+    /// Headless mode: read JSON commands from stdin, answer on stdout
+    #[arg(long, conflicts_with = "list")]
+    server: bool,
+
+    /// Write the listing in this format instead of starting the TUI
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with_all = ["list", "server"])]
+    export: Option<export::ExportFormat>,
+
+    /// File to write the --export output to (stdout by default)
+    #[arg(short = 'o', long, value_name = "FILE", requires = "export")]
+    output: Option<PathBuf>,
+
+    /// Don't allow navigating above this directory
+    #[arg(long, value_name = "DIR")]
+    root: Option<PathBuf>,
+
+    /// Only list entries modified within this window (e.g. 30m, 2h, 1d, 1w)
+    #[arg(long, value_name = "DURATION", value_parser = filter::parse_duration)]
+    newer_than: Option<std::time::Duration>,
+
+    /// Open files in a read-only pager instead of the editor
+    #[arg(long)]
+    view_only: bool,
+
+    /// Draw in the normal screen instead of the alternate one, so the last
+    /// frame stays in the scrollback after quitting
+    #[arg(long)]
+    no_alt_screen: bool,
+
+    /// Open the config file in the editor, creating it with the defaults
+    /// commented out if there isn't one
+    #[arg(long, conflicts_with_all = ["list", "server", "export"])]
+    edit_config: bool,
+
+    /// Minimum width of the name column
+    #[arg(long, default_value_t = 20)]
+    name_min_width: usize,
+
+    /// Maximum width of the name column
+    #[arg(long, default_value_t = 60)]
+    name_max_width: usize,
+
+    /// Quit after this many seconds without a keypress
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    idle_timeout: Option<u64>,
+
+    /// Let --idle-timeout quit even with unsaved changes
+    #[arg(long, requires = "idle_timeout")]
+    idle_force: bool,
+
+    /// Skip looking up the git branch and file states
+    #[arg(long)]
+    no_git: bool,
 }
 
+const COMMAND_PALETTE_COMMANDS: &[&str] = &[
+    "Open File",
+    "Open in Split",
+    "Close Split",
+    "New Tab",
+    "Close Tab",
+    "Close All Tabs",
+    "Save",
+    "Save As",
+    "Save All",
+    "Rename",
+    "New Directory",
+    "Set Syntax",
+    "Make Writable",
+    "Checksum",
+    "Paste Into New File",
+    "Search in Files",
+    "Copy Path",
+    "Copy Relative Path",
+    "Show File Tree",
+    "Show Terminal",
+    "Show Log",
+    "Recent Files",
+    "Toggle Hidden Files",
+    "Toggle Auto-Descend",
+    "Edit Config",
+    "Revert Changes",
+    "Next Theme",
+    "Go To Name",
+    "Refresh",
+    "Go to Parent Directory",
+    "Exit",
+];
+
+const PINNED_SLOTS: usize = 4;
+
+// Event loop wakeups: often enough for streaming output, or rarely when idle
+const ACTIVE_POLL: std::time::Duration = std::time::Duration::from_millis(100);
+const IDLE_POLL: std::time::Duration = std::time::Duration::from_secs(1);
+
+// Limits for the flat recursive view, which can otherwise walk a whole disk
+const FLAT_MAX_DEPTH: usize = 8;
+const MAX_FLAT_ENTRIES: usize = 20_000;
+
+// Enough to be useful without letting a broad query eat memory
+const MAX_GREP_RESULTS: usize = 1000;
+
+// Terminal output kept for scrolling back through once the pane is maximized
+const TERMINAL_SCROLLBACK_BYTES: usize = 512 * 1024;
+
+// Icon, size, permissions and date columns plus the spaces between them
+const ROW_FIXED_COLUMNS: usize = 42;
+// Git marker in front of each row ("??" and a space)
+const GIT_COLUMN_WIDTH: usize = 3;
+
+// Saving more clipboard text than this asks first, in case it's not what
+// you meant to copy
+const PASTE_CONFIRM_BYTES: usize = 1024 * 1024;
+
+// Auto-descend stops after this many levels, so a symlink back up can't
+// loop forever
+const MAX_AUTO_DESCEND: usize = 32;
+
+// Columns ←/→ move the pager when long lines aren't wrapped
+const HORIZONTAL_STEP: usize = 8;
+
 #[derive(Clone)]
 struct FileItem {
     name: String,
@@ -314,23 +498,97 @@ struct FileItem {
     modified: SystemTime,
     permissions: String,
     is_hidden: bool,
+    /// Set when `read_dir` listed the entry but it couldn't be stat'ed
+    stale: Option<StaleReason>,
+    /// Inode number and hard-link count; 0 where the platform has neither
+    inode: u64,
+    links: u64,
+    /// How many expanded directories this entry sits inside, for the tree
+    /// indentation; 0 for the listed directory's own entries
+    depth: usize,
+}
+
+/// Why a listed entry has no metadata.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StaleReason {
+    /// Deleted between `read_dir` and the stat
+    Vanished,
+    /// Permission denied or some other stat failure
+    Unreadable,
+}
+
+impl StaleReason {
+    fn from_error(error: &io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => StaleReason::Vanished,
+            _ => StaleReason::Unreadable,
+        }
+    }
+
+    fn marker(self) -> &'static str {
+        match self {
+            StaleReason::Vanished => "(stale)",
+            StaleReason::Unreadable => "(unreadable)",
+        }
+    }
 }
 
 impl FileItem {
-    fn from_dir_entry(entry: DirEntry) -> io::Result<Self> {
-        let metadata = entry.metadata()?;
+    /// Never fails: an entry that can't be stat'ed is kept with zeroed
+    /// fields and a stale marker so the listing matches what `read_dir` saw.
+    fn from_dir_entry(entry: DirEntry) -> Self {
         let name = entry.file_name().to_string_lossy().to_string();
-        let is_hidden = name.starts_with('.');
+        match entry.metadata() {
+            Ok(metadata) => Self::from_metadata(name, entry.path(), &metadata),
+            Err(e) => Self::stale(name, entry.path(), StaleReason::from_error(&e)),
+        }
+    }
+
+    fn stale(name: String, path: PathBuf, reason: StaleReason) -> Self {
+        FileItem {
+            is_hidden: name.starts_with('.'),
+            name,
+            path,
+            is_dir: false,
+            size: 0,
+            modified: SystemTime::UNIX_EPOCH,
+            permissions: "?---------".to_string(),
+            stale: Some(reason),
+            inode: 0,
+            links: 0,
+            depth: 0,
+        }
+    }
 
-        Ok(FileItem {
-            name: name.clone(),
-            path: entry.path(),
+    fn from_metadata(name: String, path: PathBuf, metadata: &Metadata) -> Self {
+        let is_hidden = path
+            .file_name()
+            .is_some_and(|file_name| file_name.to_string_lossy().starts_with('.'));
+
+        let (inode, links) = inode_and_links(metadata);
+        FileItem {
+            name,
+            path,
             is_dir: metadata.is_dir(),
             size: metadata.len(),
             modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
-            permissions: format_permissions(&metadata),
+            permissions: format_permissions(metadata),
             is_hidden,
-        })
+            stale: None,
+            inode,
+            links,
+            depth: 0,
+        }
+    }
+
+    /// A file with more than one name. Directories always have several
+    /// links (`.` and each subdirectory's `..`), so they never count.
+    fn is_hard_linked(&self) -> bool {
+        !self.is_dir && self.links > 1
+    }
+
+    fn format_links(&self) -> String {
+        format!("{:>10} {:>5}", self.inode, self.links)
     }
 
     fn get_icon(&self) -> &'static str {
@@ -356,28 +614,49 @@ impl FileItem {
         }
     }
 
-    fn format_size(size: u64, human_readable: bool) -> String {
-        if human_readable {
-            const UNITS: &[&str] = &["B", "K", "M", "G", "T"];
-            let mut size = size as f64;
-            let mut unit_index = 0;
+    fn format_row(&self, name_width: usize, human_readable: bool, units: SizeUnits) -> String {
+        format!(
+            "{} {}",
+            self.format_row_without_date(name_width, human_readable, units),
+            self.format_date()
+        )
+    }
 
-            while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-                size /= 1024.0;
-                unit_index += 1;
-            }
+    fn format_row_without_date(
+        &self,
+        name_width: usize,
+        human_readable: bool,
+        units: SizeUnits,
+    ) -> String {
+        let name = match self.stale {
+            Some(reason) => format!("{} {}", self.name, reason.marker()),
+            None => self.name.clone(),
+        };
+        // Entries of expanded directories are indented under them, taking
+        // the space out of the name column so the others stay aligned
+        let indent = "  ".repeat(self.depth);
+        format!(
+            "{}{} {} {:>10} {}",
+            indent,
+            self.get_icon(),
+            fit_to_width(&name, name_width.saturating_sub(indent.len())),
+            size::format_size(self.size, human_readable, units),
+            self.permissions
+        )
+    }
 
-            if unit_index == 0 {
-                format!("{:.0}{}", size, UNITS[unit_index])
-            } else {
-                format!("{:.1}{}", size, UNITS[unit_index])
-            }
-        } else {
-            size.to_string()
+    fn sort_fields(&self) -> SortFields<'_> {
+        SortFields {
+            name: &self.name,
+            size: self.size,
+            modified: self.modified,
         }
     }
 
     fn format_date(&self) -> String {
+        if self.stale.is_some() {
+            return "Unknown".to_string();
+        }
         match self.modified.duration_since(SystemTime::UNIX_EPOCH) {
             Ok(duration) => {
                 let timestamp = duration.as_secs();
@@ -391,6 +670,110 @@ impl FileItem {
     }
 }
 
+struct Prompt {
+    kind: PromptKind,
+    input: String,
+}
+
+/// What a paused copy or move of marked entries is waiting to hear.
+enum TransferPrompt {
+    /// How to handle this many name collisions across the whole batch
+    Policy(usize),
+    /// What to do about the entry that would replace this path
+    Conflict(PathBuf),
+}
+
+#[derive(PartialEq)]
+enum PromptKind {
+    SaveAs,
+    Rename(PathBuf),
+    NewDirectory,
+    SetSyntax,
+    Export,
+    ExpectedChecksum,
+    /// An entry of the listing to select by name
+    GoToName,
+    /// Name for a symbolic link to this entry, created in the listed
+    /// directory
+    Symlink(PathBuf),
+    /// Where to copy or move this entry: into a directory, or under a new
+    /// name
+    SendTo(PathBuf, TransferKind),
+    /// Where to save text read from the clipboard
+    PasteToFile(String),
+    /// A bulk delete over the configured threshold, confirmed by typing
+    /// `phrase`
+    ConfirmDelete {
+        paths: Vec<PathBuf>,
+        phrase: String,
+    },
+    /// A bulk move over the same threshold, confirmed the same way
+    ConfirmMove {
+        sources: Vec<PathBuf>,
+        phrase: String,
+    },
+}
+
+impl PromptKind {
+    /// The directory a typed path starts from, for prompts that take one.
+    fn completion_base(&self, current: &Path) -> Option<PathBuf> {
+        match self {
+            PromptKind::SaveAs
+            | PromptKind::Export
+            | PromptKind::PasteToFile(_)
+            | PromptKind::Symlink(_)
+            | PromptKind::SendTo(..) => Some(current.to_path_buf()),
+            PromptKind::Rename(from) => from.parent().map(Path::to_path_buf),
+            _ => None,
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            PromptKind::SaveAs => "Save as".to_string(),
+            PromptKind::Rename(_) => "Rename to".to_string(),
+            PromptKind::NewDirectory => "New directory (a/b/c creates all three)".to_string(),
+            PromptKind::SetSyntax => "Syntax (name or extension, e.g. JSON or sh)".to_string(),
+            PromptKind::Export => "Export listing to (.txt, .csv, .json or .md)".to_string(),
+            PromptKind::ExpectedChecksum => "Expected hash (MD5, SHA-1 or SHA-256)".to_string(),
+            PromptKind::GoToName => "Go to (exact name or its start)".to_string(),
+            PromptKind::Symlink(original) => format!("Link to {} as", original.display()),
+            PromptKind::SendTo(source, kind) => format!(
+                "{} {} to (Tab completes)",
+                if *kind == TransferKind::Copy {
+                    "Copy"
+                } else {
+                    "Move"
+                },
+                source.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            PromptKind::PasteToFile(text) => {
+                format!("Save the clipboard ({} lines) as", text.lines().count())
+            }
+            PromptKind::ConfirmDelete { paths, phrase } => format!(
+                "You are about to delete {} entries — type {} to confirm",
+                paths.len(),
+                phrase
+            ),
+            PromptKind::ConfirmMove { sources, phrase } => format!(
+                "You are about to move {} entries here — type {} to confirm",
+                sources.len(),
+                phrase
+            ),
+        }
+    }
+}
+
+/// What to update once a guarded write has gone through.
+enum WriteFollowUp {
+    /// Point the active tab at the new file
+    SaveAs,
+    /// Reload the listing and select the result
+    Reveal,
+    /// Reload the listing, select the new file and open it in a tab
+    Open,
+}
+
 struct App {
     files: Vec<FileItem>,
     current_path: PathBuf,
@@ -398,22 +781,146 @@ struct App {
     list_state: ListState,
     scroll_state: ScrollbarState,
     show_hidden: bool,
+    // Split the header totals into hidden vs. visible entries
+    show_hidden_summary: bool,
+    // Overlay explaining what the filters left out of the listing
+    show_filters: bool,
+    // What they left out of the listed directory itself; None in the flat
+    // view, which walks subdirectories instead
+    filter_counts: Option<FilterCounts>,
+    // Inode and hard-link count columns after the date
+    show_inodes: bool,
+    // A bar after the date for each file's size relative to the largest
+    show_size_bars: bool,
+    // Color the date column by age, bright for recent changes
+    heat_colors: bool,
+    // Section the listing into directories, source, images and other
+    group_by_type: bool,
+    sort_mode: SortMode,
+    dir_order: DirOrder,
+    // Where each column title was last drawn (row and columns), so a click
+    // can pick the sort column
+    sort_header: Vec<(u16, std::ops::Range<u16>, SortKey)>,
+    // Where the listing was last drawn, borders included, for clicks
+    file_list_area: Option<Rect>,
+    // What happens to the configured build and cache directories
+    clutter: ClutterMode,
+    // Pass through directories holding a single subdirectory; the last
+    // chain walked is kept to show in the header while it's listed
+    auto_descend: bool,
+    descended: Option<(PathBuf, PathBuf)>,
+    // Soft-wrap long lines, remembered separately for editing and viewing
+    editor_wrap: bool,
+    viewer_wrap: bool,
+    // Syntax colors in open files; starts from the config, Alt+H flips it
+    syntax_highlighting: bool,
+    // Syntax theme; starts from the config, T in the viewer cycles it
+    current_theme: String,
+    // List every file below current_path, by relative path, instead of one level
+    flat_mode: bool,
+    flat_receiver: Option<mpsc::Receiver<PathBuf>>,
     human_readable: bool,
+    size_units: SizeUnits,
+    name_min_width: usize,
+    name_max_width: usize,
+    view_only: bool,
+    // Entries marked for bulk operations; kept across directory changes
+    marked_paths: HashSet<PathBuf>,
+    // Total size of the marked files, kept in step with marked_paths by
+    // update_marked_size; marked directories aren't walked, only counted
+    marked_size: u64,
+    marked_dirs: usize,
+    // Hide entries not modified within this window
+    newer_than: Option<std::time::Duration>,
+    // Only list entries whose name contains this (/ types it), in the
+    // directory it was typed in; leaving that directory drops it
+    name_filter: String,
+    name_filter_dir: PathBuf,
+    name_filter_mode: bool,
+    // The listing as read, while a filter is being typed; each keystroke
+    // filters this instead of reading the directory again
+    unfiltered_files: Vec<FileItem>,
+    // Navigation never goes above this directory when set
+    root: Option<PathBuf>,
+    // Directory ls-pretty was started in
+    start_path: PathBuf,
+    // (real, as typed) when the start path went through a symlink; paths
+    // under the real one are shown under the typed one
+    path_alias: Option<(PathBuf, PathBuf)>,
+    clipboard: clipboard::Clipboard,
+    config: Config,
+    // Detected at startup; without it videos get no media info
+    ffprobe_available: bool,
+    // Repository state for current_path, refreshed on every directory load
+    git_summary: Option<git::RepoSummary>,
+    // Changed files (and the directories above them) in that repository
+    git_status: HashMap<PathBuf, git::GitState>,
+    // Off with --no-git, which skips the lookups entirely
+    use_git: bool,
+    // Short-lived message shown in place of the footer hints
+    notifications: notifications::Notifications,
+    show_log: bool,
+    // Session-only quick-switch slots for keys 1-4
+    pinned_dirs: [Option<PathBuf>; PINNED_SLOTS],
+    // Directory and selected row remembered by `m`, restored by `
+    position_mark: Option<(PathBuf, usize)>,
+    // Directory the listing was last loaded from, and the one before it
+    // (what `-` swaps back to)
+    listed_path: PathBuf,
+    previous_path: Option<PathBuf>,
+    // Directories shown open in place, their entries indented below them
+    expanded_dirs: HashSet<PathBuf>,
+    log_scroll: usize,
+    log_page_height: usize,
+    // A file waiting for y/n before it's run in the terminal
+    pending_run: Option<PathBuf>,
+    // A PDF with no readable text, and why, waiting for y/n before it's
+    // handed to the default application
+    pending_open: Option<(PathBuf, String)>,
+    // Where the launcher file just shown points, until y/n is answered
+    pending_shortcut: Option<shortcut::Target>,
+    // Clipboard text too big to save without a y/n first
+    pending_paste: Option<String>,
+    // Marked entries waiting on a y/n before being deleted
+    pending_delete: Option<Vec<PathBuf>>,
+    // A copy or move of marked entries paused on a name collision
+    transfer: Option<(Transfer, TransferPrompt)>,
+    // One-line text input shown in the footer (save as, rename)
+    prompt: Option<Prompt>,
+    // A prompt dismissed with text in it, handed back if the same prompt
+    // is opened again
+    parked_prompt: Option<Prompt>,
+    // Writes that would replace an existing file wait here for y/n
+    overwrite_guard: fileops::OverwriteGuard<WriteFollowUp>,
     show_help: bool,
+    help_scroll: usize,
+    // Rows visible inside the help popup on the last draw
+    help_page_height: usize,
     show_file_content: bool,
     file_content: String,
     file_content_scroll: usize,
     file_editing_mode: bool,
     file_has_unsaved_changes: bool,
-    original_file_content: String,
     show_unsaved_alert: bool,
     cursor_line: usize,
     cursor_col: usize,
     // Tab management
     tab_manager: TabManager,
+    // Rows available to the file content area on the last draw
+    content_viewport_height: usize,
+    // Where each pane's minimap was last drawn and which tab it shows, so a
+    // click can jump there
+    minimap_hits: Vec<(Rect, usize)>,
     // Cursor display
     cursor_blink_state: bool,
-    cursor_blink_timer: usize,
+    // When the cursor last appeared or disappeared
+    cursor_blink_changed: std::time::Instant,
+    // Time left before a held-back gutter rediff is due
+    gutter_due: Option<std::time::Duration>,
+    // Quit once no key has been pressed for this long (--idle-timeout)
+    idle_timeout: Option<std::time::Duration>,
+    idle_force: bool,
+    last_key: std::time::Instant,
     // Search functionality
     search_mode: bool,
     search_query: String,
@@ -421,11 +928,38 @@ struct App {
     current_search_match: usize,
     // File finder
     file_finder_mode: bool,
+    // The next file the finder opens goes beside the current tab
+    open_in_split: bool,
+    // The listing shown next to open files, and whether it has the keyboard
+    show_file_pane: bool,
+    browser_focused: bool,
     file_finder_query: String,
     file_finder_results: Vec<PathBuf>,
     file_finder_all_files: Vec<PathBuf>,
+    // Open history that ranks finder results; only persisted when a store
+    // path is set
+    frecency: frecency::Frecency,
+    frecency_path: Option<PathBuf>,
     file_finder_selected: usize,
     // Command palette
+    // Search in files (Ctrl+G)
+    grep_mode: bool,
+    grep_query: String,
+    // Query the current results belong to; Enter searches again when it differs
+    grep_results_query: String,
+    grep_results: Vec<grep::GrepMatch>,
+    grep_selected: usize,
+    grep_receiver: Option<mpsc::Receiver<grep::GrepMatch>>,
+    // Recently opened files popup, newest first with Unix timestamps
+    history: Option<Vec<(PathBuf, u64)>>,
+    history_selected: usize,
+    // Waiting for y/n before wiping the open history
+    history_confirm_clear: bool,
+    // The `[places]` popup, with its selected row
+    show_places: bool,
+    places_selected: usize,
+    // The checksum popup and the hash it's working on
+    checksum: Option<checksum::Job>,
     command_palette_mode: bool,
     command_palette_query: String,
     command_palette_results: Vec<String>,
@@ -443,16 +977,22 @@ struct App {
     syntax_set: SyntaxSet,
     theme_set: ThemeSet,
     show_terminal: bool,
+    // The terminal covering everything between the header and the footer
+    terminal_maximized: bool,
+    // Lines scrolled back from the newest output, while maximized
+    terminal_scroll: usize,
+    terminal_page_height: usize,
     terminal_output: Arc<Mutex<String>>,
     terminal_input: String,
     terminal_pty: Option<Box<dyn MasterPty + Send>>,
+    // The shell, so Ctrl+C can reach it when no foreground group is known
+    terminal_child: Option<Box<dyn portable_pty::Child + Send + Sync>>,
     terminal_receiver: Option<std::sync::mpsc::Receiver<String>>,
     // LSP and autocomplete
     lsp_client: Option<LspClient>,
     show_completions: bool,
     completions: Vec<CompletionCandidate>,
     completion_selected: usize,
-    fuzzy_matcher: SkimMatcherV2,
     // LSP status display
     show_lsp_status: bool,
     lsp_status_message: String,
@@ -465,37 +1005,125 @@ struct App {
 }
 
 impl App {
-    fn new(path: PathBuf, show_hidden: bool, human_readable: bool) -> AppResult<Self> {
+    /// Starts in `path` with the listing set up the way `config` (already
+    /// merged with the command line) says.
+    fn new(path: PathBuf, config: Config) -> AppResult<Self> {
         let mut app = Self {
             files: Vec::new(),
-            current_path: path,
+            current_path: path.clone(),
             selected_index: 0,
             list_state: ListState::default(),
             scroll_state: ScrollbarState::default(),
-            show_hidden,
-            human_readable,
+            show_hidden: config.show_hidden,
+            show_hidden_summary: false,
+            show_filters: false,
+            filter_counts: None,
+            show_inodes: false,
+            show_size_bars: false,
+            heat_colors: false,
+            group_by_type: false,
+            sort_mode: SortMode {
+                key: config.sort,
+                reverse: config.reverse_sort,
+            },
+            dir_order: config.dir_order,
+            sort_header: Vec::new(),
+            file_list_area: None,
+            clutter: config.clutter,
+            auto_descend: config.auto_descend,
+            descended: None,
+            editor_wrap: true,
+            viewer_wrap: true,
+            syntax_highlighting: config.syntax_highlighting,
+            current_theme: themes::DEFAULT_THEME.to_string(),
+            flat_mode: false,
+            flat_receiver: None,
+            human_readable: config.human_readable,
+            size_units: config.size_units,
+            name_min_width: 20,
+            name_max_width: 60,
+            view_only: false,
+            marked_paths: HashSet::new(),
+            marked_size: 0,
+            marked_dirs: 0,
+            newer_than: None,
+            name_filter: String::new(),
+            name_filter_dir: PathBuf::new(),
+            name_filter_mode: false,
+            unfiltered_files: Vec::new(),
+            root: None,
+            start_path: path.clone(),
+            path_alias: None,
+            clipboard: clipboard::Clipboard::new(),
+            ffprobe_available: config.media_info && media::ffprobe_available(),
+            use_git: config.git,
+            config,
+            git_summary: None,
+            git_status: HashMap::new(),
+            notifications: notifications::Notifications::default(),
+            show_log: false,
+            pinned_dirs: Default::default(),
+            position_mark: None,
+            listed_path: path,
+            previous_path: None,
+            expanded_dirs: HashSet::new(),
+            log_scroll: 0,
+            log_page_height: 0,
+            pending_run: None,
+            pending_open: None,
+            pending_shortcut: None,
+            pending_paste: None,
+            pending_delete: None,
+            transfer: None,
+            prompt: None,
+            parked_prompt: None,
+            overwrite_guard: fileops::OverwriteGuard::default(),
             show_help: false,
+            help_scroll: 0,
+            help_page_height: 10,
             show_file_content: false,
             file_content: String::new(),
             file_content_scroll: 0,
             file_editing_mode: false,
             file_has_unsaved_changes: false,
-            original_file_content: String::new(),
             show_unsaved_alert: false,
             cursor_line: 0,
             cursor_col: 0,
             tab_manager: TabManager::new(),
+            content_viewport_height: 30,
+            minimap_hits: Vec::new(),
             cursor_blink_state: false,
-            cursor_blink_timer: 0,
+            cursor_blink_changed: std::time::Instant::now(),
+            gutter_due: None,
+            idle_timeout: None,
+            idle_force: false,
+            last_key: std::time::Instant::now(),
             search_mode: false,
             search_query: String::new(),
             search_matches: Vec::new(),
             current_search_match: 0,
             file_finder_mode: false,
+            open_in_split: false,
+            show_file_pane: false,
+            browser_focused: false,
             file_finder_query: String::new(),
             file_finder_results: Vec::new(),
             file_finder_all_files: Vec::new(),
+            frecency: frecency::Frecency::default(),
+            frecency_path: None,
             file_finder_selected: 0,
+            grep_mode: false,
+            grep_query: String::new(),
+            grep_results_query: String::new(),
+            grep_results: Vec::new(),
+            grep_selected: 0,
+            grep_receiver: None,
+            history: None,
+            history_selected: 0,
+            history_confirm_clear: false,
+            show_places: false,
+            places_selected: 0,
+            checksum: None,
             command_palette_mode: false,
             command_palette_query: String::new(),
             command_palette_results: Vec::new(),
@@ -511,15 +1139,18 @@ impl App {
             syntax_set: SyntaxSet::load_defaults_newlines(),
             theme_set: ThemeSet::load_defaults(),
             show_terminal: false,
+            terminal_maximized: false,
+            terminal_scroll: 0,
+            terminal_page_height: 0,
             terminal_output: Arc::new(Mutex::new(String::new())),
             terminal_input: String::new(),
             terminal_pty: None,
+            terminal_child: None,
             terminal_receiver: None,
             lsp_client: None,
             show_completions: false,
             completions: Vec::new(),
             completion_selected: 0,
-            fuzzy_matcher: SkimMatcherV2::default(),
             show_lsp_status: false,
             lsp_status_message: String::new(),
             last_completion_trigger: std::time::Instant::now(),
@@ -531,34 +1162,249 @@ impl App {
         Ok(app)
     }
 
-    fn refresh_files(&mut self) -> AppResult<()> {
-        self.load_directory().map_err(|e| anyhow::anyhow!(e))
+    fn age_cutoff(&self) -> Option<SystemTime> {
+        self.newer_than
+            .and_then(|window| SystemTime::now().checked_sub(window))
     }
 
-    fn load_directory(&mut self) -> io::Result<()> {
-        self.files.clear();
-        self.selected_index = 0;
+    /// Sorts by `key`, or flips the direction if already sorted by it.
+    fn sort_by(&mut self, key: SortKey) -> AppResult<()> {
+        self.sort_mode.select(key);
+        self.resort()
+    }
+
+    fn reverse_sort(&mut self) -> AppResult<()> {
+        self.sort_mode.reverse = !self.sort_mode.reverse;
+        self.resort()
+    }
+
+    fn resort(&mut self) -> AppResult<()> {
+        self.sort_files();
+        self.set_status(format!(
+            "Sorted by {} {}",
+            self.sort_mode.key.label().to_lowercase(),
+            if self.sort_mode.reverse {
+                "descending"
+            } else {
+                "ascending"
+            }
+        ));
+        Ok(())
+    }
+
+    /// Puts the listing in the current order without reading the disk
+    /// again. `..` stays on top and each open directory's rows stay under
+    /// it.
+    fn sort_files(&mut self) {
+        let selected = self.files.get(self.selected_index).map(|f| f.path.clone());
+        let (mut files, rows): (Vec<_>, Vec<_>) = std::mem::take(&mut self.files)
+            .into_iter()
+            .partition(|file| file.name == "..");
+        files.extend(self.sort_level(rows));
+        self.files = files;
+        if let Some(selected) = selected {
+            self.select_entry(&selected);
+        }
+    }
+
+    /// Sorts the rows of one level, each carrying along the rows nested
+    /// under it.
+    fn sort_level(&self, rows: Vec<FileItem>) -> Vec<FileItem> {
+        let Some(depth) = rows.first().map(|row| row.depth) else {
+            return rows;
+        };
+        let mut entries: Vec<(FileItem, Vec<FileItem>)> = Vec::new();
+        for row in rows {
+            match entries.last_mut() {
+                Some((_, children)) if row.depth > depth => children.push(row),
+                _ => entries.push((row, Vec::new())),
+            }
+        }
+        entries.sort_by(|(a, _), (b, _)| self.compare_entries(a, b));
+        entries
+            .into_iter()
+            .flat_map(|(entry, children)| std::iter::once(entry).chain(self.sort_level(children)))
+            .collect()
+    }
+
+    /// The listing order: by group when grouping is on, then directories
+    /// and files apart (or not), then by the chosen column.
+    fn compare_entries(&self, a: &FileItem, b: &FileItem) -> std::cmp::Ordering {
+        let group = |file: &FileItem| {
+            self.group_by_type
+                .then(|| FileGroup::of(&file.path, file.is_dir))
+        };
+        group(a)
+            .cmp(&group(b))
+            .then_with(|| self.dir_order.compare(a.is_dir, b.is_dir))
+            .then_with(|| self.sort_mode.compare(&a.sort_fields(), &b.sort_fields()))
+    }
 
-        let entries = fs::read_dir(&self.current_path)?;
-        for entry in entries {
-            if let Ok(entry) = entry {
-                if let Ok(file_item) = FileItem::from_dir_entry(entry) {
-                    if self.show_hidden || !file_item.is_hidden {
+    fn cycle_dir_order(&mut self) -> AppResult<()> {
+        self.dir_order = self.dir_order.next();
+        let selected = self.files.get(self.selected_index).map(|f| f.path.clone());
+        self.refresh_files()?;
+        if let Some(selected) = selected {
+            self.select_entry(&selected);
+        }
+        self.set_status(self.dir_order.label());
+        Ok(())
+    }
+
+    fn toggle_group_by_type(&mut self) -> AppResult<()> {
+        self.group_by_type = !self.group_by_type;
+        let selected = self.files.get(self.selected_index).map(|f| f.path.clone());
+        self.refresh_files()?;
+        if let Some(selected) = selected {
+            self.select_entry(&selected);
+        }
+        Ok(())
+    }
+
+    /// The entry drawn on `row` of the listing, counting the group headers
+    /// between entries. None for a header, or past the last entry.
+    fn entry_at_list_row(&self, row: usize) -> Option<usize> {
+        let mut headers = 0;
+        for (start, _, _) in self.group_starts() {
+            match row.cmp(&(start + headers)) {
+                std::cmp::Ordering::Less => break,
+                std::cmp::Ordering::Equal => return None,
+                std::cmp::Ordering::Greater => headers += 1,
+            }
+        }
+        let index = row - headers;
+        (index < self.files.len()).then_some(index)
+    }
+
+    /// Where each group starts in `files`, for the section headers. Empty
+    /// unless grouping is on; `..` stays above the first header.
+    fn group_starts(&self) -> Vec<(usize, FileGroup, usize)> {
+        if !self.group_by_type || self.flat_mode {
+            return Vec::new();
+        }
+        let mut starts: Vec<(usize, FileGroup, usize)> = Vec::new();
+        for (index, file) in self.files.iter().enumerate() {
+            // Entries of expanded directories stay under their directory
+            if file.name == ".." || file.depth > 0 {
+                continue;
+            }
+            let group = FileGroup::of(&file.path, file.is_dir);
+            match starts.last_mut() {
+                Some((_, last, count)) if *last == group => *count += 1,
+                _ => starts.push((index, group, 1)),
+            }
+        }
+        starts
+    }
+
+    fn toggle_flat_mode(&mut self) -> AppResult<()> {
+        self.flat_mode = !self.flat_mode;
+        self.refresh_files()
+    }
+
+    /// Adds files found by the flat view's walk, named by their path relative
+    /// to the listed directory.
+    fn poll_flat_results(&mut self) {
+        let Some(receiver) = &self.flat_receiver else {
+            return;
+        };
+
+        let cutoff = self.age_cutoff();
+        let mut added = false;
+        loop {
+            match receiver.try_recv() {
+                Ok(path) => {
+                    let Ok(metadata) = fs::metadata(&path) else {
+                        continue;
+                    };
+                    let name = path
+                        .strip_prefix(&self.current_path)
+                        .unwrap_or(&path)
+                        .to_string_lossy()
+                        .into_owned();
+                    let file_item = FileItem::from_metadata(name, path, &metadata);
+                    if cutoff.is_some_and(|cutoff| file_item.modified < cutoff) {
+                        continue;
+                    }
+                    if self.name_filter_mode {
+                        self.unfiltered_files.push(file_item.clone());
+                    }
+                    if filter::name_matches(&file_item.name, &self.name_filter) {
                         self.files.push(file_item);
+                        added = true;
+                    }
+                    if self.files.len() >= MAX_FLAT_ENTRIES {
+                        self.flat_receiver = None;
+                        break;
                     }
                 }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.flat_receiver = None;
+                    break;
+                }
             }
         }
 
-        // Sort: directories first, then files, both alphabetically
-        self.files.sort_by(|a, b| match (a.is_dir, b.is_dir) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-        });
+        if added {
+            self.scroll_state = self.scroll_state.content_length(self.files.len());
+        }
+    }
+
+    fn is_clutter(&self, file: &FileItem) -> bool {
+        file.is_dir && self.config.is_clutter(&file.name)
+    }
+
+    /// Cycles the configured build and cache directories between dimmed,
+    /// hidden and shown normally.
+    fn cycle_clutter(&mut self) -> AppResult<()> {
+        self.clutter = self.clutter.next();
+        let selected = self.files.get(self.selected_index).map(|f| f.path.clone());
+        self.refresh_files()?;
+        if let Some(selected) = selected {
+            self.select_entry(&selected);
+        }
+        self.set_status(format!(
+            "Build and cache directories {}",
+            self.clutter.label()
+        ));
+        Ok(())
+    }
+
+    fn refresh_files(&mut self) -> AppResult<()> {
+        self.load_directory().map_err(|e| anyhow::anyhow!(e))
+    }
+
+    fn load_directory(&mut self) -> io::Result<()> {
+        self.files.clear();
+        self.selected_index = 0;
+        self.flat_receiver = None;
+        self.filter_counts = None;
+        if self.name_filter_dir != self.current_path {
+            self.name_filter.clear();
+            self.name_filter_mode = false;
+            self.unfiltered_files.clear();
+        }
+
+        if self.flat_mode {
+            // Fail up front like the normal listing; files then arrive from
+            // the walk in poll_flat_results
+            fs::read_dir(&self.current_path)?;
+            self.flat_receiver = Some(walk::spawn_walk(
+                self.current_path.clone(),
+                self.show_hidden,
+                FLAT_MAX_DEPTH,
+            ));
+        } else {
+            let (entries, counts) = self.read_entries_counted(&self.current_path)?;
+            self.files = self.with_expanded(entries, 0);
+            self.filter_counts = Some(counts);
+        }
 
         // Add parent directory entry if not at root
-        if let Some(parent) = self.current_path.parent() {
+        if let Some(parent) = self.current_path.parent()
+            && self.is_within_root(parent)
+        {
             let parent_item = FileItem {
                 name: "..".to_string(),
                 path: parent.to_path_buf(),
@@ -567,10 +1413,27 @@ impl App {
                 modified: SystemTime::UNIX_EPOCH,
                 permissions: "drwxrwxrwx".to_string(),
                 is_hidden: false,
+                stale: None,
+                inode: 0,
+                links: 0,
+                depth: 0,
             };
             self.files.insert(0, parent_item);
         }
 
+        let repo = if self.use_git {
+            git::summarize(&self.current_path)
+        } else {
+            None
+        };
+        let (summary, status) = repo.unzip();
+        self.git_summary = summary;
+        self.git_status = status.unwrap_or_default();
+        if self.listed_path != self.current_path {
+            let left = std::mem::replace(&mut self.listed_path, self.current_path.clone());
+            self.previous_path = Some(left);
+        }
+
         // Update scroll state
         self.scroll_state = self.scroll_state.content_length(self.files.len());
         self.list_state.select(Some(0));
@@ -578,6 +1441,162 @@ impl App {
         Ok(())
     }
 
+    /// The entries of `dir` as the listing shows them: filtered, then sorted
+    /// directories first and grouped when that's on.
+    fn read_entries(&self, dir: &Path) -> io::Result<Vec<FileItem>> {
+        self.read_entries_counted(dir).map(|(files, _)| files)
+    }
+
+    /// `read_entries`, also telling how many entries each filter dropped.
+    fn read_entries_counted(&self, dir: &Path) -> io::Result<(Vec<FileItem>, FilterCounts)> {
+        let cutoff = self.age_cutoff();
+        let mut files = Vec::new();
+        let mut counts = FilterCounts::default();
+        for entry in fs::read_dir(dir)? {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            counts.read += 1;
+            let file_item = FileItem::from_dir_entry(entry);
+            if self.clutter == ClutterMode::Hide && self.is_clutter(&file_item) {
+                counts.clutter += 1;
+            } else if !self.show_hidden && file_item.is_hidden {
+                counts.hidden += 1;
+            } else if cutoff.is_some_and(|cutoff| file_item.modified < cutoff)
+                // Stale entries have no date to filter on, so they stay
+                && file_item.stale.is_none()
+            {
+                counts.age += 1;
+            } else if !filter::name_matches(&file_item.name, &self.name_filter) {
+                counts.name += 1;
+            } else {
+                files.push(file_item);
+            }
+        }
+
+        files.sort_by(|a, b| self.compare_entries(a, b));
+        Ok((files, counts))
+    }
+
+    /// Puts `files` at `depth` with the contents of each expanded directory
+    /// right below it, one level deeper. Directories that can no longer be
+    /// read just show up collapsed.
+    fn with_expanded(&self, files: Vec<FileItem>, depth: usize) -> Vec<FileItem> {
+        let mut tree = Vec::with_capacity(files.len());
+        for mut file in files {
+            file.depth = depth;
+            let children = (file.is_dir && self.expanded_dirs.contains(&file.path))
+                .then(|| self.read_entries(&file.path).ok())
+                .flatten();
+            tree.push(file);
+            if let Some(children) = children {
+                tree.extend(self.with_expanded(children, depth + 1));
+            }
+        }
+        tree
+    }
+
+    /// Shows the selected directory's contents indented below it instead of
+    /// entering it. On a directory that's already open, moves to its first
+    /// entry.
+    fn expand_selected(&mut self) {
+        let Some(file) = self.files.get(self.selected_index) else {
+            return;
+        };
+        if !file.is_dir || file.name == ".." || self.flat_mode {
+            return;
+        }
+        if self.expanded_dirs.contains(&file.path) {
+            if self
+                .files
+                .get(self.selected_index + 1)
+                .is_some_and(|next| next.depth > file.depth)
+            {
+                self.navigate_down();
+            }
+            return;
+        }
+
+        let (path, depth) = (file.path.clone(), file.depth);
+        match self.read_entries(&path) {
+            Ok(children) => {
+                self.expanded_dirs.insert(path);
+                let children = self.with_expanded(children, depth + 1);
+                let at = self.selected_index + 1;
+                self.files.splice(at..at, children);
+                self.scroll_state = self.scroll_state.content_length(self.files.len());
+            }
+            Err(e) => self.report_error(format!("Could not read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Folds the selected directory back up, or from inside one, moves to
+    /// the directory it's listed under.
+    fn collapse_selected(&mut self) {
+        let Some(file) = self.files.get(self.selected_index) else {
+            return;
+        };
+        let depth = file.depth;
+        if file.is_dir && self.expanded_dirs.remove(&file.path) {
+            let start = self.selected_index + 1;
+            let end = start
+                + self.files[start..]
+                    .iter()
+                    .take_while(|child| child.depth > depth)
+                    .count();
+            self.files.drain(start..end);
+            self.scroll_state = self.scroll_state.content_length(self.files.len());
+        } else if depth > 0
+            && let Some(parent) = self.files[..self.selected_index]
+                .iter()
+                .rposition(|row| row.depth < depth)
+        {
+            self.selected_index = parent;
+            self.list_state.select(Some(parent));
+            self.scroll_state = self.scroll_state.position(parent);
+        }
+    }
+
+    /// From a directory just entered, keeps going down while the only thing
+    /// listed is a single subdirectory, remembering the levels passed.
+    fn descend_single_child_chain(&mut self) {
+        let start = self.current_path.clone();
+        for _ in 0..MAX_AUTO_DESCEND {
+            match self.single_subdirectory(&self.current_path) {
+                Some(child) if self.is_within_root(&child) => self.current_path = child,
+                _ => break,
+            }
+        }
+        if self.current_path != start {
+            let base = start.parent().unwrap_or(&start);
+            let chain = self
+                .current_path
+                .strip_prefix(base)
+                .unwrap_or(&self.current_path)
+                .to_path_buf();
+            self.descended = Some((self.current_path.clone(), chain));
+        }
+    }
+
+    /// The one entry of `dir`, when that's a directory. Hidden entries only
+    /// count while they're shown.
+    fn single_subdirectory(&self, dir: &Path) -> Option<PathBuf> {
+        let mut entries = fs::read_dir(dir).ok()?.flatten().filter(|entry| {
+            self.show_hidden || !entry.file_name().to_string_lossy().starts_with('.')
+        });
+        let only = entries.next()?.path();
+        (entries.next().is_none() && only.is_dir()).then_some(only)
+    }
+
+    fn toggle_auto_descend(&mut self) {
+        self.auto_descend = !self.auto_descend;
+        self.set_status(if self.auto_descend {
+            "Entering a directory now skips through single-subdirectory chains"
+        } else {
+            "Entering a directory now stops at every level"
+        });
+    }
+
     fn navigate_up(&mut self) {
         if self.selected_index > 0 {
             self.selected_index -= 1;
@@ -594,78 +1613,2360 @@ impl App {
         }
     }
 
-    fn enter_directory(&mut self) -> AppResult<()> {
-        if let Some(selected_file) = self.files.get(self.selected_index) {
-            if selected_file.is_dir {
-                self.current_path = selected_file.path.clone();
-                self.load_directory()?;
-            } else {
-                // Try to open as text file
-                self.open_file().map_err(anyhow::Error::from)?;
-            }
-        }
-        Ok(())
+    /// Whether `path` is inside --root, once resolved: a symlinked
+    /// directory in the root can point anywhere. A path that doesn't exist
+    /// is judged by its parent.
+    fn is_within_root(&self, path: &Path) -> bool {
+        self.root
+            .as_ref()
+            .is_none_or(|root| match path.canonicalize() {
+                Ok(path) => path.starts_with(root),
+                Err(_) => {
+                    path.file_name().is_some()
+                        && path
+                            .parent()
+                            .and_then(|parent| parent.canonicalize().ok())
+                            .is_some_and(|parent| parent.starts_with(root))
+                }
+            })
     }
 
-    fn toggle_hidden(&mut self) -> AppResult<()> {
-        self.show_hidden = !self.show_hidden;
-        self.load_directory().map_err(anyhow::Error::from)
+    /// Like is_within_root for a path that may not exist yet, resolving `..`
+    /// and symlinks through its parent directory.
+    fn target_within_root(&self, path: &Path) -> bool {
+        self.root.is_none()
+            || path
+                .parent()
+                .and_then(|parent| parent.canonicalize().ok())
+                .is_some_and(|parent| self.is_within_root(&parent))
     }
 
-    fn toggle_help(&mut self) {
-        self.show_help = !self.show_help;
+    fn set_status(&mut self, message: impl Into<String>) {
+        self.notifications.push(notifications::Level::Info, message);
     }
 
-    fn open_file(&mut self) -> io::Result<()> {
-        if let Some(selected_file) = self.files.get(self.selected_index) {
-            if self.is_text_file(selected_file) {
-                let file_path = selected_file.path.clone();
-                match fs::read_to_string(&file_path) {
-                    Ok(content) => {
-                        let file_name = selected_file.name.clone();
-                        self.tab_manager
-                            .add_tab(file_name, file_path.clone(), content);
-
-                        // Initialize LSP for Go files
-                        if LspClient::is_go_file(&file_path) {
-                            let rt = tokio::runtime::Runtime::new().unwrap();
-                            let _ = rt.block_on(self.open_file_with_lsp(&file_path));
-                        }
-                    }
-                    Err(_) => {
-                        // If file can't be read as text, do nothing
-                    }
-                }
+    fn report_error(&mut self, message: impl Into<String>) {
+        self.notifications
+            .push(notifications::Level::Error, message);
+    }
+
+    fn toggle_log(&mut self) {
+        self.show_log = !self.show_log;
+        // Open on the newest entries; ui clamps this to the last page
+        self.log_scroll = usize::MAX;
+        self.notifications.dismiss_toast();
+    }
+
+    /// Scrolls the notification log while it's open. Returns true if the key
+    /// was consumed.
+    /// Alt+M maximizes the terminal and restores it. Maximized, the arrow
+    /// and page keys scroll back through its output and Esc restores it;
+    /// everything else still goes to the shell.
+    fn handle_terminal_view_key(&mut self, key: KeyEvent) -> bool {
+        if !self.show_terminal {
+            return false;
+        }
+        if key.code == KeyCode::Char('m') && key.modifiers.contains(KeyModifiers::ALT) {
+            self.terminal_maximized = !self.terminal_maximized;
+            self.terminal_scroll = 0;
+            return true;
+        }
+        if !self.terminal_maximized {
+            return false;
+        }
+
+        let page = self.terminal_page_height.max(1);
+        match key.code {
+            KeyCode::Esc => {
+                self.terminal_maximized = false;
+                self.terminal_scroll = 0;
             }
+            KeyCode::Up => self.terminal_scroll = self.terminal_scroll.saturating_add(1),
+            KeyCode::Down => self.terminal_scroll = self.terminal_scroll.saturating_sub(1),
+            KeyCode::PageUp => self.terminal_scroll = self.terminal_scroll.saturating_add(page),
+            KeyCode::PageDown => self.terminal_scroll = self.terminal_scroll.saturating_sub(page),
+            KeyCode::Home => self.terminal_scroll = usize::MAX,
+            KeyCode::End => self.terminal_scroll = 0,
+            _ => return false,
         }
-        Ok(())
+        true
     }
 
-    fn close_file(&mut self) {
-        if self.tab_manager.has_tabs() {
-            let _ = self.tab_manager.close_active_tab();
+    fn handle_log_key(&mut self, key: KeyEvent) -> bool {
+        if !self.show_log {
+            return false;
+        }
+
+        let page = self.log_page_height.max(1);
+        match key.code {
+            KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.toggle_log()
+            }
+            KeyCode::Char('q') | KeyCode::Esc => self.show_log = false,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.log_scroll = self.log_scroll.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.log_scroll = self.log_scroll.saturating_add(1);
+            }
+            KeyCode::PageUp => self.log_scroll = self.log_scroll.saturating_sub(page),
+            KeyCode::PageDown => self.log_scroll = self.log_scroll.saturating_add(page),
+            KeyCode::Home => self.log_scroll = 0,
+            KeyCode::End => self.log_scroll = usize::MAX,
+            // Ctrl+Q still force-quits from under the panel
+            _ => return !key.modifiers.contains(KeyModifiers::CONTROL),
         }
+        true
     }
 
-    fn actually_close_file(&mut self) {
-        // This method is now handled by TabManager
-        if let Some(index) = self.tab_manager.tab_to_close {
-            self.tab_manager.confirm_close_tab();
+    /// Drives the recent files popup: ↑↓ choose, Enter opens, C clears the
+    /// whole history after a y/n.
+    fn handle_history_key(&mut self, key: KeyEvent) -> bool {
+        let Some(history) = &self.history else {
+            return false;
+        };
+        let count = history.len();
+
+        if self.history_confirm_clear {
+            self.history_confirm_clear = false;
+            if key.code == KeyCode::Char('y') {
+                self.clear_history();
+            }
+            return true;
         }
-        // Cursor position is now managed by individual tabs
-        self.cursor_blink_state = true;
-        self.cursor_blink_timer = 0;
-        self.search_mode = false;
-        self.search_query.clear();
-        self.search_matches.clear();
-        self.current_search_match = 0;
-        self.file_finder_mode = false;
-        self.file_finder_query.clear();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.history = None,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.history_selected = self.history_selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.history_selected + 1 < count => {
+                self.history_selected += 1;
+            }
+            KeyCode::Enter => self.open_history_entry(),
+            KeyCode::Char('C') if count > 0 => self.history_confirm_clear = true,
+            _ => return !key.modifiers.contains(KeyModifiers::CONTROL),
+        }
+        true
+    }
+
+    fn open_history_entry(&mut self) {
+        let Some((path, _)) = self
+            .history
+            .as_ref()
+            .and_then(|history| history.get(self.history_selected))
+            .cloned()
+        else {
+            return;
+        };
+        if !self.is_within_root(&path) {
+            self.set_status("The file is outside the root directory");
+            return;
+        }
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.history = None;
+        self.open_entry(name, path);
+    }
+
+    fn clear_history(&mut self) {
+        self.frecency.clear();
+        self.history = Some(Vec::new());
+        self.history_selected = 0;
+        if let Some(store) = &self.frecency_path
+            && let Err(e) = self.frecency.save_to(store)
+        {
+            self.report_error(format!("Could not clear open history: {}", e));
+            return;
+        }
+        self.set_status("Cleared the open history");
+    }
+
+    fn open_places(&mut self) {
+        if self.config.places.is_empty() {
+            self.set_status("No places configured (add a [places] table to config.toml)");
+            return;
+        }
+        self.show_places = true;
+        self.places_selected = 0;
+    }
+
+    /// Drives the places popup: ↑↓ choose, Enter goes there.
+    fn handle_places_key(&mut self, key: KeyEvent) -> AppResult<bool> {
+        if !self.show_places {
+            return Ok(false);
+        }
+        let count = self.config.places.len();
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.show_places = false,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.places_selected = self.places_selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.places_selected + 1 < count => {
+                self.places_selected += 1;
+            }
+            KeyCode::Enter => {
+                self.show_places = false;
+                if let Some((name, path)) = self.config.places.iter().nth(self.places_selected) {
+                    let (name, path) = (name.clone(), path.clone());
+                    self.go_to_place(&name, path)?;
+                }
+            }
+            _ => return Ok(!key.modifiers.contains(KeyModifiers::CONTROL)),
+        }
+        Ok(true)
+    }
+
+    /// Hashes the selected file in the background and shows it in a popup.
+    fn open_checksum(&mut self) {
+        let Some(file) = self.files.get(self.selected_index) else {
+            return;
+        };
+        if file.is_dir {
+            self.set_status("Select a file to checksum");
+            return;
+        }
+        let path = file.path.clone();
+        self.start_checksum(path, checksum::Algorithm::Sha256, None);
+    }
+
+    fn start_checksum(
+        &mut self,
+        path: PathBuf,
+        algorithm: checksum::Algorithm,
+        expected: Option<String>,
+    ) {
+        match checksum::Job::start(path.clone(), algorithm) {
+            Ok(mut job) => {
+                job.expected = expected;
+                self.checksum = Some(job);
+            }
+            Err(e) => self.report_error(format!("Could not read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Drives the checksum popup: Tab switches the algorithm, c copies the
+    /// hash, v asks for the hash to compare with.
+    fn handle_checksum_key(&mut self, key: KeyEvent) -> bool {
+        let Some(job) = &self.checksum else {
+            return false;
+        };
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.checksum = None,
+            KeyCode::Tab | KeyCode::Char('a') => {
+                let (path, algorithm) = (job.path.clone(), job.algorithm.next());
+                let expected = job.expected.clone();
+                self.start_checksum(path, algorithm, expected);
+            }
+            KeyCode::Char('c' | 'y') => match &job.result {
+                Some(Ok(hash)) => {
+                    let hash = hash.clone();
+                    match self.clipboard.copy(&hash) {
+                        Ok(()) => self.set_status(format!("Copied {}", hash)),
+                        Err(e) => self.report_error(format!("Copy failed: {}", e)),
+                    }
+                }
+                _ => self.set_status("The checksum isn't ready yet"),
+            },
+            KeyCode::Char('v' | '=') => {
+                let input = job.expected.clone().unwrap_or_default();
+                self.start_prompt(PromptKind::ExpectedChecksum, input);
+            }
+            _ => return !key.modifiers.contains(KeyModifiers::CONTROL),
+        }
+        true
+    }
+
+    /// Compares against a pasted hash, switching to the algorithm its length
+    /// implies.
+    fn expect_checksum(&mut self, expected: &str) {
+        let Some(job) = &mut self.checksum else {
+            return;
+        };
+        let expected = expected.to_string();
+        match checksum::Algorithm::for_hex_len(checksum::expected_hash(&expected).len()) {
+            Some(algorithm) if algorithm != job.algorithm => {
+                let path = job.path.clone();
+                self.start_checksum(path, algorithm, Some(expected));
+            }
+            _ => job.expected = Some(expected),
+        }
+    }
+
+    fn go_to_place(&mut self, name: &str, path: PathBuf) -> AppResult<()> {
+        if !path.is_dir() {
+            self.report_error(format!("{} ({}) is not a directory", name, path.display()));
+            return Ok(());
+        }
+        if !self.is_within_root(&path) {
+            self.set_status(format!("{} is outside the root directory", name));
+            return Ok(());
+        }
+        self.current_path = path;
+        self.refresh_files()
+    }
+
+    /// The open tab's file, or the selected entry in the listing.
+    fn focused_path(&self) -> Option<PathBuf> {
+        if let Some(tab) = self.tab_manager.get_active_tab() {
+            return Some(tab.path.clone());
+        }
+        self.files
+            .get(self.selected_index)
+            .filter(|file| file.name != "..")
+            .map(|file| file.path.clone())
+    }
+
+    /// Copies the focused path, either absolute or relative to the git
+    /// repository root (falling back to the start directory). With entries
+    /// marked, copies all of their paths instead, one per line.
+    fn copy_focused_path(&mut self, relative: bool) {
+        if !self.marked_paths.is_empty() {
+            self.copy_marked_paths(relative);
+            return;
+        }
+        let Some(path) = self.focused_path() else {
+            return;
+        };
+
+        let text = match self.path_for_copy(&path, relative) {
+            Ok(text) => text,
+            Err(message) => {
+                self.set_status(message);
+                return;
+            }
+        };
+
+        match self.clipboard.copy(&text) {
+            Ok(()) => self.set_status(format!("Copied {}", text)),
+            Err(e) => self.report_error(format!("Copy failed: {}", e)),
+        }
+    }
+
+    fn copy_marked_paths(&mut self, relative: bool) {
+        let mut paths: Vec<&PathBuf> = self.marked_paths.iter().collect();
+        paths.sort();
+        let lines: Result<Vec<String>, String> = paths
+            .into_iter()
+            .map(|path| self.path_for_copy(path, relative))
+            .collect();
+        let lines = match lines {
+            Ok(lines) => lines,
+            Err(message) => {
+                self.set_status(message);
+                return;
+            }
+        };
+
+        match self.clipboard.copy(&lines.join("\n")) {
+            Ok(()) => self.set_status(format!("Copied {} paths", lines.len())),
+            Err(e) => self.report_error(format!("Copy failed: {}", e)),
+        }
+    }
+
+    fn path_for_copy(&self, path: &Path, relative: bool) -> Result<String, String> {
+        if !relative {
+            return Ok(path.to_string_lossy().to_string());
+        }
+        let base = git::repo_root(path)
+            .or_else(|| self.root.clone())
+            .unwrap_or_else(|| self.start_path.clone());
+        relative_to(path, &base)
+            .map(|relative| relative.to_string_lossy().to_string())
+            .ok_or_else(|| format!("{} is outside {}", path.display(), base.display()))
+    }
+
+    fn go_to_parent(&mut self) -> AppResult<()> {
+        if let Some(parent) = self.current_path.parent() {
+            if !self.is_within_root(parent) {
+                self.set_status("Already at the root directory");
+                return Ok(());
+            }
+            self.current_path = parent.to_path_buf();
+            self.refresh_files()?;
+        }
+        Ok(())
+    }
+
+    fn duplicate_selected(&mut self) -> AppResult<()> {
+        let Some(file) = self.files.get(self.selected_index) else {
+            return Ok(());
+        };
+        if file.name == ".." {
+            return Ok(());
+        }
+
+        match fileops::duplicate(&file.path) {
+            Ok(copy) => {
+                self.refresh_files()?;
+                self.select_entry(&copy);
+                self.set_status(format!("Created {}", copy.display()));
+            }
+            Err(e) => self.report_error(format!("Could not duplicate {}: {}", file.name, e)),
+        }
+        Ok(())
+    }
+
+    /// `path` as the user would write it: under the symlinked form they
+    /// started from, if any. Everything else works on the real path.
+    fn display_path(&self, path: &Path) -> PathBuf {
+        match &self.path_alias {
+            Some((real, typed)) => match path.strip_prefix(real) {
+                // Joining an empty path would add a trailing slash
+                Ok(rest) if rest.as_os_str().is_empty() => typed.clone(),
+                Ok(rest) => typed.join(rest),
+                Err(_) => path.to_path_buf(),
+            },
+            None => path.to_path_buf(),
+        }
+    }
+
+    fn pin_directory(&mut self, slot: usize) {
+        self.pinned_dirs[slot] = Some(self.current_path.clone());
+        self.set_status(format!(
+            "Pinned {} to slot {} (Alt+{} jumps back)",
+            self.display_path(&self.current_path).display(),
+            slot + 1,
+            slot + 1
+        ));
+    }
+
+    fn jump_to_pinned(&mut self, slot: usize) -> AppResult<()> {
+        let Some(path) = self.pinned_dirs[slot].clone() else {
+            self.set_status(format!(
+                "Slot {} is empty (press {} to pin)",
+                slot + 1,
+                slot + 1
+            ));
+            return Ok(());
+        };
+        if !path.is_dir() {
+            self.report_error(format!("{} no longer exists", path.display()));
+            return Ok(());
+        }
+
+        self.current_path = path;
+        self.refresh_files()
+    }
+
+    fn set_position_mark(&mut self) {
+        self.position_mark = Some((self.current_path.clone(), self.selected_index));
+        self.set_status("Position marked (` jumps back)");
+    }
+
+    /// Goes back to the directory listed before this one, like `cd -`.
+    /// Doing it again returns here.
+    fn swap_to_previous_dir(&mut self) -> AppResult<()> {
+        let Some(path) = self.previous_path.clone() else {
+            self.set_status("No previous directory yet");
+            return Ok(());
+        };
+        if !path.is_dir() {
+            self.report_error(format!("{} no longer exists", path.display()));
+            return Ok(());
+        }
+        self.current_path = path;
+        self.refresh_files()
+    }
+
+    fn jump_to_position_mark(&mut self) -> AppResult<()> {
+        let Some((path, index)) = self.position_mark.clone() else {
+            self.set_status("No position marked (press m to mark)");
+            return Ok(());
+        };
+        if !path.is_dir() {
+            self.report_error(format!("{} no longer exists", path.display()));
+            return Ok(());
+        }
+
+        if path != self.current_path {
+            self.current_path = path;
+            self.refresh_files()?;
+        }
+        // Entries may have come or gone since, so the row is clamped
+        let index = index.min(self.files.len().saturating_sub(1));
+        self.selected_index = index;
+        self.list_state.select(Some(index));
+        self.scroll_state = self.scroll_state.position(index);
+        Ok(())
+    }
+
+    fn enter_directory(&mut self) -> AppResult<()> {
+        if let Some(selected_file) = self.files.get(self.selected_index) {
+            if selected_file.is_dir {
+                if !self.is_within_root(&selected_file.path) {
+                    if selected_file.name == ".." {
+                        self.set_status("Already at the root directory");
+                    } else {
+                        self.set_status("That directory leads outside the root");
+                    }
+                    return Ok(());
+                }
+                // Going up through a chain must not bounce back down it
+                let going_up = selected_file.name == "..";
+                self.current_path = selected_file.path.clone();
+                if self.auto_descend && !self.flat_mode && !going_up {
+                    self.descend_single_child_chain();
+                }
+                self.load_directory()?;
+            } else {
+                let (name, path) = (selected_file.name.clone(), selected_file.path.clone());
+                self.open_entry(name, path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens a file the way Enter on it in the listing does: by its
+    /// configured action, as a media, PDF or shortcut view, or as text.
+    fn open_entry(&mut self, name: String, path: PathBuf) {
+        match self.config.action_for(&path) {
+            Some(FileAction::View) => self.view_file(name, path),
+            Some(FileAction::Open) => {
+                if let Err(e) = launch::open_externally(&path) {
+                    self.report_error(format!("Could not open {}: {}", path.display(), e));
+                }
+            }
+            Some(FileAction::Run) => self.pending_run = Some(path),
+            None if self.config.media_info && media::is_media(&path) => {
+                self.show_media_info(name, path);
+            }
+            None if pdf::is_pdf(&path) => self.show_pdf_text(name, path),
+            None if shortcut::is_shortcut(&path) => self.show_shortcut(name, path),
+            // Try to open as text file
+            None if is_text_path(&path) => self.view_file(name, path),
+            None => {}
+        }
+    }
+
+    fn start_prompt(&mut self, kind: PromptKind, input: String) {
+        let input = match self.parked_prompt.take() {
+            Some(parked) if parked.kind == kind => parked.input,
+            _ => input,
+        };
+        self.prompt = Some(Prompt { kind, input });
+    }
+
+    /// Closes the prompt without acting on it, keeping what was typed for
+    /// next time. A typed bulk confirmation is never kept.
+    fn dismiss_prompt(&mut self) {
+        self.parked_prompt = self.prompt.take().filter(|prompt| {
+            !matches!(
+                prompt.kind,
+                PromptKind::ConfirmDelete { .. } | PromptKind::ConfirmMove { .. }
+            )
+        });
+    }
+
+    fn start_save_as(&mut self) {
+        if let Some(tab) = self.tab_manager.get_active_tab() {
+            let input = tab.path.display().to_string();
+            self.start_prompt(PromptKind::SaveAs, input);
+        }
+    }
+
+    /// Hands the selected entry (or the listed directory, on `..`) to the
+    /// system file manager.
+    fn reveal_in_file_manager(&mut self) {
+        let path = match self.files.get(self.selected_index) {
+            Some(file) if file.name != ".." => file.path.clone(),
+            _ => self.current_path.clone(),
+        };
+        match launch::reveal_in_file_manager(&path) {
+            Ok(()) => self.set_status(format!("Showing {} in the file manager", path.display())),
+            Err(e) => self.report_error(format!("Could not open the file manager: {}", e)),
+        }
+    }
+
+    /// Opens a copy of the selected file in an untitled buffer, leaving the
+    /// original alone; saving asks where to put it.
+    fn open_as_untitled(&mut self) {
+        let Some(file) = self.files.get(self.selected_index) else {
+            return;
+        };
+        if file.is_dir {
+            return;
+        }
+
+        let path = file.path.clone();
+        match encoding::read_file(&path) {
+            Ok(decoded) => {
+                self.tab_manager.add_untitled_tab(decoded.text);
+                if let Some(tab) = self.tab_manager.get_active_tab_mut() {
+                    tab.encoding = decoded.encoding;
+                    tab.has_bom = decoded.has_bom;
+                    tab.lossy = decoded.lossy;
+                }
+                if decoded.lossy {
+                    self.set_status(format!(
+                        "Copied {} into a new buffer; it didn't decode cleanly, so saving writes � for the bytes that didn't",
+                        path.display()
+                    ));
+                } else {
+                    self.set_status(format!("Copied {} into a new buffer", path.display()));
+                }
+            }
+            Err(e) => self.report_error(format!("Could not read {}: {}", path.display(), e)),
+        }
+    }
+
+    fn start_rename(&mut self) {
+        if let Some(file) = self.files.get(self.selected_index)
+            && file.name != ".."
+        {
+            let (path, name) = (file.path.clone(), file.name.clone());
+            self.start_prompt(PromptKind::Rename(path), name);
+        }
+    }
+
+    /// Edits the footer prompt while it's open; Enter submits, Esc cancels.
+    fn handle_prompt_key(&mut self, key: KeyEvent) -> AppResult<bool> {
+        let Some(prompt) = &mut self.prompt else {
+            return Ok(false);
+        };
+
+        match key.code {
+            KeyCode::Esc => self.dismiss_prompt(),
+            KeyCode::Enter => {
+                if let Some(prompt) = self.prompt.take() {
+                    self.submit_prompt(prompt)?;
+                }
+            }
+            KeyCode::Backspace => {
+                prompt.input.pop();
+            }
+            KeyCode::Tab => {
+                if let Some(base) = prompt.kind.completion_base(&self.current_path)
+                    && let Some(completed) = paths::complete(&base, &prompt.input)
+                {
+                    prompt.input = completed;
+                }
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                prompt.input.push(c);
+            }
+            // Ctrl+Q still force-quits
+            _ => return Ok(!key.modifiers.contains(KeyModifiers::CONTROL)),
+        }
+        Ok(true)
+    }
+
+    /// A typed path: relative to the listed directory, `~` for home.
+    fn resolve_input(&self, input: &str) -> PathBuf {
+        self.current_path.join(paths::expand_home(Path::new(input)))
+    }
+
+    fn submit_prompt(&mut self, prompt: Prompt) -> AppResult<()> {
+        let input = prompt.input.trim();
+        if input.is_empty() {
+            return Ok(());
+        }
+
+        match prompt.kind {
+            PromptKind::SaveAs => {
+                let path = self.resolve_input(input);
+                let Some(tab) = self.tab_manager.get_active_tab() else {
+                    return Ok(());
+                };
+                let contents = match tab.encoded_content() {
+                    Ok(contents) => contents,
+                    Err(e) => {
+                        self.report_error(format!("Could not save {}: {}", path.display(), e));
+                        return Ok(());
+                    }
+                };
+                self.write_guarded(
+                    fileops::WriteOp::Write { path, contents },
+                    WriteFollowUp::SaveAs,
+                )
+            }
+            PromptKind::Rename(from) => {
+                let Some(dir) = from.parent() else {
+                    return Ok(());
+                };
+                let to = dir.join(paths::expand_home(Path::new(input)));
+                self.write_guarded(fileops::WriteOp::Rename { from, to }, WriteFollowUp::Reveal)
+            }
+            PromptKind::NewDirectory => {
+                match fileops::create_dirs(&self.current_path, input) {
+                    Ok(created) => {
+                        self.refresh_files()?;
+                        self.select_entry(&created);
+                        self.set_status(format!("Created {}", input));
+                    }
+                    Err(e) => self.report_error(format!("Could not create {}: {}", input, e)),
+                }
+                Ok(())
+            }
+            PromptKind::SetSyntax => {
+                let Some(syntax) = resolve_syntax(&self.syntax_set, input) else {
+                    self.report_error(format!("Unknown syntax {}", input));
+                    return Ok(());
+                };
+                let name = syntax.name.clone();
+                if let Some(tab) = self.tab_manager.get_active_tab_mut() {
+                    tab.syntax_override = Some(name.clone());
+                }
+                self.set_status(format!("Highlighting as {}", name));
+                Ok(())
+            }
+            PromptKind::Export => {
+                let path = self.resolve_input(input);
+                let Some(format) = export::ExportFormat::from_path(&path) else {
+                    self.report_error(format!("{}: use .txt, .csv, .json or .md", input));
+                    return Ok(());
+                };
+                let contents = export::render(self, format).into_bytes();
+                self.write_guarded(
+                    fileops::WriteOp::Write { path, contents },
+                    WriteFollowUp::Reveal,
+                )
+            }
+            PromptKind::ExpectedChecksum => {
+                self.expect_checksum(input);
+                Ok(())
+            }
+            PromptKind::GoToName => {
+                self.go_to_name(input);
+                Ok(())
+            }
+            PromptKind::Symlink(original) => {
+                let link = self.resolve_input(input);
+                self.create_symlink(&original, &link)
+            }
+            PromptKind::SendTo(source, kind) => self.send_to(source, input, kind),
+            PromptKind::PasteToFile(text) => {
+                let path = self.resolve_input(input);
+                self.write_guarded(
+                    fileops::WriteOp::Write {
+                        path,
+                        contents: text.into_bytes(),
+                    },
+                    WriteFollowUp::Open,
+                )
+            }
+            PromptKind::ConfirmDelete { paths, phrase } => {
+                if input == phrase {
+                    self.delete_paths(paths)
+                } else {
+                    self.set_status("Delete cancelled");
+                    Ok(())
+                }
+            }
+            PromptKind::ConfirmMove { sources, phrase } => {
+                if input == phrase {
+                    self.transfer_sources(TransferKind::Move, sources)
+                } else {
+                    self.set_status("Move cancelled");
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Deletes the marked entries, or the selected one when nothing is
+    /// marked, after confirming: y/n normally, or typing the configured
+    /// phrase once more than `bulk_confirm_threshold` are marked (unless
+    /// the phrase is empty).
+    fn delete_selected(&mut self) {
+        let mut paths: Vec<PathBuf> = self.marked_paths.iter().cloned().collect();
+        if paths.is_empty() {
+            match self.files.get(self.selected_index) {
+                Some(file) if file.name != ".." => paths.push(file.path.clone()),
+                _ => return,
+            }
+        }
+        paths.sort();
+
+        if self.needs_bulk_confirm(paths.len()) {
+            let phrase = self.config.bulk_confirm_phrase.clone();
+            self.start_prompt(PromptKind::ConfirmDelete { paths, phrase }, String::new());
+        } else {
+            self.pending_delete = Some(paths);
+        }
+    }
+
+    /// Answers the "delete N entries?" prompt: y deletes, anything else
+    /// cancels.
+    fn handle_delete_prompt_key(&mut self, key: KeyEvent) -> AppResult<bool> {
+        let Some(paths) = self.pending_delete.take() else {
+            return Ok(false);
+        };
+
+        if matches!(key.code, KeyCode::Char('y')) {
+            self.delete_paths(paths)?;
+        } else {
+            self.set_status("Delete cancelled");
+        }
+        Ok(true)
+    }
+
+    /// Whether acting on `count` entries at once asks for the typed phrase.
+    fn needs_bulk_confirm(&self, count: usize) -> bool {
+        count > self.config.bulk_confirm_threshold && !self.config.bulk_confirm_phrase.is_empty()
+    }
+
+    /// Copies or moves the marked entries into the listed directory. With
+    /// several name collisions, one choice up front covers them all. Moving
+    /// a large batch first asks for the bulk confirmation phrase.
+    fn start_transfer(&mut self, kind: TransferKind) -> AppResult<()> {
+        if self.marked_paths.is_empty() {
+            self.set_status(format!(
+                "Mark entries with Space to {} them here",
+                kind.label()
+            ));
+            return Ok(());
+        }
+        let mut sources: Vec<PathBuf> = self.marked_paths.iter().cloned().collect();
+        sources.sort();
+
+        if kind == TransferKind::Move && self.needs_bulk_confirm(sources.len()) {
+            let phrase = self.config.bulk_confirm_phrase.clone();
+            self.start_prompt(PromptKind::ConfirmMove { sources, phrase }, String::new());
+            return Ok(());
+        }
+        self.transfer_sources(kind, sources)
+    }
+
+    fn transfer_sources(&mut self, kind: TransferKind, sources: Vec<PathBuf>) -> AppResult<()> {
+        let transfer = Transfer::new(kind, sources, self.current_path.clone());
+        match transfer.conflicts() {
+            0 | 1 => self.continue_transfer(transfer, |transfer| transfer.run()),
+            conflicts => {
+                self.transfer = Some((transfer, TransferPrompt::Policy(conflicts)));
+                Ok(())
+            }
+        }
+    }
+
+    /// Asks where to copy or move the selected entry, without marking it.
+    fn start_send_to(&mut self, kind: TransferKind) {
+        let Some(source) = self
+            .files
+            .get(self.selected_index)
+            .filter(|file| file.name != "..")
+            .map(|file| file.path.clone())
+        else {
+            return;
+        };
+        self.start_prompt(PromptKind::SendTo(source, kind), String::new());
+    }
+
+    /// Copies or moves `source` to the typed destination: into it when it's
+    /// a directory (or ends in a slash), otherwise to that name. Replacing
+    /// something asks first, like any other write.
+    fn send_to(&mut self, source: PathBuf, input: &str, kind: TransferKind) -> AppResult<()> {
+        let dest = self.resolve_input(input);
+        let target = if dest.is_dir() || input.ends_with(std::path::is_separator) {
+            match source.file_name() {
+                Some(name) => dest.join(name),
+                None => return Ok(()),
+            }
+        } else {
+            dest
+        };
+
+        // Compare where the paths lead, not how they're spelled, so `..`
+        // and symlinks can't sneak the source in as its own destination
+        let (real_source, real_target) = (fileops::resolve(&source), fileops::resolve(&target));
+        if real_target == real_source {
+            self.set_status(format!("{} is already there", source.display()));
+            return Ok(());
+        }
+        if source.is_dir() && real_target.starts_with(&real_source) {
+            self.report_error(format!(
+                "Could not {} {}: a directory can't go inside itself",
+                kind.label(),
+                source.display()
+            ));
+            return Ok(());
+        }
+        let op = match kind {
+            TransferKind::Copy => fileops::WriteOp::Copy {
+                from: source,
+                to: target,
+            },
+            TransferKind::Move => fileops::WriteOp::Rename {
+                from: source,
+                to: target,
+            },
+        };
+        self.write_guarded(op, WriteFollowUp::Reveal)
+    }
+
+    /// Asks for a name to link the marked entry under in the listed
+    /// directory, suggesting its own.
+    fn start_symlink(&mut self) {
+        let original = match self.marked_paths.len() {
+            1 => self.marked_paths.iter().next().cloned(),
+            _ => None,
+        };
+        let Some(original) = original else {
+            self.set_status("Mark one entry with Space to link to it here");
+            return;
+        };
+        let name = original
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.start_prompt(PromptKind::Symlink(original), name);
+    }
+
+    fn create_symlink(&mut self, original: &Path, link: &Path) -> AppResult<()> {
+        if !self.target_within_root(link) {
+            self.report_error(format!("{} is outside the root directory", link.display()));
+            return Ok(());
+        }
+        match fileops::symlink(original, link) {
+            Ok(()) => {
+                self.refresh_files()?;
+                self.select_entry(link);
+                self.set_status(format!(
+                    "Linked {} -> {}",
+                    link.display(),
+                    original.display()
+                ));
+            }
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied && cfg!(windows) => self
+                .report_error(format!(
+                    "Could not link {}: {} (Windows needs Developer Mode or an administrator)",
+                    link.display(),
+                    e
+                )),
+            Err(e) => self.report_error(format!("Could not link {}: {}", link.display(), e)),
+        }
+        Ok(())
+    }
+
+    fn continue_transfer(
+        &mut self,
+        mut transfer: Transfer,
+        step: impl FnOnce(&mut Transfer) -> transfer::Step,
+    ) -> AppResult<()> {
+        match step(&mut transfer) {
+            transfer::Step::Ask(target) => {
+                self.transfer = Some((transfer, TransferPrompt::Conflict(target)));
+                Ok(())
+            }
+            transfer::Step::Done => self.finish_transfer(transfer),
+        }
+    }
+
+    fn finish_transfer(&mut self, transfer: Transfer) -> AppResult<()> {
+        // Moved entries are gone from where they were marked; copies stay
+        // marked so they can be pasted somewhere else too
+        if transfer.kind == TransferKind::Move {
+            for source in &transfer.finished {
+                self.marked_paths.remove(source);
+            }
+        }
+        self.update_marked_size();
+        self.refresh_files()?;
+        for error in &transfer.errors {
+            self.report_error(error.clone());
+        }
+
+        let mut message = format!(
+            "{} {} entries into {}",
+            transfer.kind.past_tense(),
+            transfer.finished.len(),
+            transfer.dest.display()
+        );
+        if transfer.skipped > 0 {
+            message.push_str(&format!(", skipped {}", transfer.skipped));
+        }
+        if transfer.errors.is_empty() {
+            self.set_status(message);
+        } else {
+            self.report_error(message);
+        }
+        Ok(())
+    }
+
+    /// Answers a paused copy or move: the policy for the whole batch, or
+    /// what to do about one collision (capitals apply to the rest too).
+    fn handle_transfer_key(&mut self, key: KeyEvent) -> AppResult<bool> {
+        let Some((mut transfer, prompt)) = self.transfer.take() else {
+            return Ok(false);
+        };
+        let resolution = match key.code {
+            KeyCode::Char('o' | 'O') => Some(Resolution::Overwrite),
+            KeyCode::Char('s' | 'S') => Some(Resolution::Skip),
+            KeyCode::Char('r' | 'R') => Some(Resolution::Rename),
+            _ => None,
+        };
+
+        match (prompt, key.code, resolution) {
+            (TransferPrompt::Policy(_), _, Some(resolution)) => {
+                transfer.set_policy(ConflictPolicy::All(resolution));
+                self.continue_transfer(transfer, |transfer| transfer.run())?;
+            }
+            (TransferPrompt::Policy(_), KeyCode::Char('a'), None) => {
+                self.continue_transfer(transfer, |transfer| transfer.run())?;
+            }
+            (TransferPrompt::Conflict(_), KeyCode::Char(c), Some(resolution)) => {
+                if c.is_ascii_uppercase() {
+                    transfer.set_policy(ConflictPolicy::All(resolution));
+                }
+                self.continue_transfer(transfer, |transfer| transfer.resolve(resolution))?;
+            }
+            (TransferPrompt::Policy(_), KeyCode::Esc, _) => {
+                self.set_status(format!("Cancelled the {}", transfer.kind.label()));
+            }
+            (TransferPrompt::Conflict(_), KeyCode::Esc, _) => self.finish_transfer(transfer)?,
+            // Anything else leaves the question up
+            (prompt, _, _) => self.transfer = Some((transfer, prompt)),
+        }
+        Ok(true)
+    }
+
+    fn delete_paths(&mut self, paths: Vec<PathBuf>) -> AppResult<()> {
+        let mut deleted = 0;
+        for path in &paths {
+            match fileops::remove(path) {
+                Ok(()) => {
+                    deleted += 1;
+                    self.marked_paths.remove(path);
+                }
+                Err(e) => self.report_error(format!("Could not delete {}: {}", path.display(), e)),
+            }
+        }
+        self.update_marked_size();
+        let previous = self.selected_index;
+        self.refresh_files()?;
+        // Keep the selection where the deleted entries were
+        if let Some(file) = self
+            .files
+            .get(previous.min(self.files.len().saturating_sub(1)))
+        {
+            let path = file.path.clone();
+            self.select_entry(&path);
+        }
+        if deleted == paths.len() && deleted == 1 {
+            self.set_status(format!("Deleted {}", paths[0].display()));
+        } else if deleted == paths.len() {
+            self.set_status(format!("Deleted {} entries", deleted));
+        } else {
+            self.report_error(format!("Deleted {} of {} entries", deleted, paths.len()));
+        }
+        Ok(())
+    }
+
+    /// Every write that could replace a file goes through here, so an
+    /// existing one is only overwritten after the user says yes.
+    fn write_guarded(&mut self, op: fileops::WriteOp, follow_up: WriteFollowUp) -> AppResult<()> {
+        let target = op.target().to_path_buf();
+        if !self.target_within_root(&target) {
+            self.report_error(format!(
+                "{} is outside the root directory",
+                target.display()
+            ));
+            return Ok(());
+        }
+        match self.overwrite_guard.write(op, follow_up) {
+            Ok(Some((op, follow_up))) => self.finish_write(op, follow_up),
+            Ok(None) => Ok(()),
+            Err(e) => {
+                self.report_error(format!("Could not write {}: {}", target.display(), e));
+                Ok(())
+            }
+        }
+    }
+
+    /// Answers the "overwrite?" question for a parked write.
+    fn handle_overwrite_key(&mut self, key: KeyEvent) -> AppResult<bool> {
+        let Some(target) = self
+            .overwrite_guard
+            .pending()
+            .map(|op| op.target().to_path_buf())
+        else {
+            return Ok(false);
+        };
+
+        if key.code == KeyCode::Char('y') {
+            match self.overwrite_guard.confirm() {
+                Ok(Some((op, follow_up))) => self.finish_write(op, follow_up)?,
+                Ok(None) => {}
+                Err(e) => self.report_error(format!("Could not write {}: {}", target.display(), e)),
+            }
+        } else {
+            self.overwrite_guard.cancel();
+            self.set_status(format!("Kept the existing {}", target.display()));
+        }
+        Ok(true)
+    }
+
+    fn finish_write(&mut self, op: fileops::WriteOp, follow_up: WriteFollowUp) -> AppResult<()> {
+        let target = op.target().to_path_buf();
+        let name = target
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        // Renaming an open file keeps its tab pointed at it
+        if let fileops::WriteOp::Rename { from, .. } = &op
+            && let Some(index) = self.tab_manager.find_tab_by_path(from)
+            && let Some(tab) = self.tab_manager.get_tab_mut(index)
+        {
+            tab.path = target.clone();
+            tab.name = name.clone();
+        }
+        // and a marked entry stays marked under its new name
+        if let fileops::WriteOp::Rename { from, .. } = &op
+            && self.marked_paths.remove(from)
+        {
+            self.marked_paths.insert(target.clone());
+        }
+
+        match follow_up {
+            WriteFollowUp::SaveAs => {
+                let mut lossy = false;
+                if let Some(tab) = self.tab_manager.get_active_tab_mut() {
+                    tab.path = target.clone();
+                    tab.name = name;
+                    tab.mark_clean();
+                    // What's on disk now is exactly the text shown
+                    lossy = std::mem::take(&mut tab.lossy);
+                }
+                if lossy {
+                    self.set_status(format!(
+                        "Saved {}, with � where the original didn't decode",
+                        target.display()
+                    ));
+                } else {
+                    self.set_status(format!("Saved {}", target.display()));
+                }
+                self.refresh_files()?;
+            }
+            WriteFollowUp::Reveal => {
+                let previous = self.selected_index;
+                self.refresh_files()?;
+                // Sent to another directory: stay about where the listing was
+                if !self.select_entry(&target)
+                    && let Some(file) = self
+                        .files
+                        .get(previous.min(self.files.len().saturating_sub(1)))
+                {
+                    let path = file.path.clone();
+                    self.select_entry(&path);
+                }
+                match op {
+                    fileops::WriteOp::Copy { .. } => {
+                        self.set_status(format!("Copied to {}", target.display()))
+                    }
+                    _ => self.set_status(format!("Now {}", target.display())),
+                }
+            }
+            WriteFollowUp::Open => {
+                self.refresh_files()?;
+                self.select_entry(&target);
+                match encoding::read_file(&target) {
+                    Ok(decoded) => {
+                        self.add_file_tab(name, target.clone(), decoded);
+                        self.set_status(format!("Saved {}", target.display()));
+                    }
+                    Err(e) => {
+                        self.report_error(format!("Could not open {}: {}", target.display(), e))
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Answers the "run this file?" prompt: y/Enter runs it in the terminal,
+    /// anything else cancels.
+    fn handle_run_prompt_key(&mut self, key: KeyEvent) -> AppResult<bool> {
+        let Some(path) = self.pending_run.take() else {
+            return Ok(false);
+        };
+
+        if matches!(key.code, KeyCode::Char('y') | KeyCode::Enter) {
+            if !self.show_terminal {
+                self.open_terminal()?;
+            }
+            let command = format!("{}\r\n", launch::shell_quote(&path));
+            self.send_to_terminal(&command)?;
+        }
+        Ok(true)
+    }
+
+    /// Answers the "open this PDF externally?" prompt the same way.
+    fn handle_open_prompt_key(&mut self, key: KeyEvent) -> bool {
+        let Some((path, _)) = self.pending_open.take() else {
+            return false;
+        };
+
+        if matches!(key.code, KeyCode::Char('y') | KeyCode::Enter)
+            && let Err(e) = launch::open_externally(&path)
+        {
+            self.report_error(format!("Could not open {}: {}", path.display(), e));
+        }
+        true
+    }
+
+    /// Saves the clipboard text to a new file, asking for its name, and
+    /// opens it.
+    fn paste_into_new_file(&mut self) {
+        match self.clipboard.paste() {
+            Ok(text) if text.is_empty() => self.set_status("The clipboard is empty"),
+            Ok(text) if text.len() > PASTE_CONFIRM_BYTES => self.pending_paste = Some(text),
+            Ok(text) => self.start_prompt(PromptKind::PasteToFile(text), String::new()),
+            Err(e) => self.report_error(format!("Could not read the clipboard: {}", e)),
+        }
+    }
+
+    /// Answers "save this much clipboard text?": y/Enter goes on to ask
+    /// for the file name.
+    fn handle_paste_prompt_key(&mut self, key: KeyEvent) -> bool {
+        let Some(text) = self.pending_paste.take() else {
+            return false;
+        };
+        if matches!(key.code, KeyCode::Char('y') | KeyCode::Enter) {
+            self.start_prompt(PromptKind::PasteToFile(text), String::new());
+        }
+        true
+    }
+
+    fn toggle_mark(&mut self) {
+        if let Some(file) = self.files.get(self.selected_index)
+            && file.name != ".."
+            && !self.marked_paths.remove(&file.path)
+        {
+            self.marked_paths.insert(file.path.clone());
+        }
+        self.update_marked_size();
+        self.navigate_down();
+    }
+
+    /// Marks every listed entry, or unmarks them all if they already are.
+    /// Works on the listing as filtered, so hidden entries are never picked up.
+    fn toggle_mark_all(&mut self) {
+        let visible: Vec<PathBuf> = self
+            .files
+            .iter()
+            .filter(|file| file.name != "..")
+            .map(|file| file.path.clone())
+            .collect();
+
+        if visible.iter().all(|path| self.marked_paths.contains(path)) {
+            for path in &visible {
+                self.marked_paths.remove(path);
+            }
+            self.set_status("Selection cleared");
+        } else {
+            self.marked_paths.extend(visible);
+            self.set_status(format!("{} selected", self.marked_paths.len()));
+        }
+        self.update_marked_size();
+    }
+
+    /// Flips the mark on every listed entry, for "everything but these".
+    /// Marks outside the listing are left as they are.
+    fn invert_marks(&mut self) {
+        for file in self.files.iter().filter(|file| file.name != "..") {
+            if !self.marked_paths.remove(&file.path) {
+                self.marked_paths.insert(file.path.clone());
+            }
+        }
+        self.update_marked_size();
+        self.set_status(format!("{} selected", self.marked_paths.len()));
+    }
+
+    fn clear_marks(&mut self) {
+        self.marked_paths.clear();
+        self.marked_size = 0;
+        self.marked_dirs = 0;
+        self.set_status("Selection cleared");
+    }
+
+    /// Marks can span directories, so entries outside the listing are
+    /// looked up on disk. Directories add nothing to the size: walking them
+    /// on every mark would stall the UI, so the header says they're left out.
+    fn update_marked_size(&mut self) {
+        let listed: HashMap<&Path, (bool, u64)> = self
+            .files
+            .iter()
+            .map(|file| (file.path.as_path(), (file.is_dir, file.size)))
+            .collect();
+        self.marked_size = 0;
+        self.marked_dirs = 0;
+        for path in &self.marked_paths {
+            let (is_dir, size) = match listed.get(path.as_path()) {
+                Some(entry) => *entry,
+                None => fs::symlink_metadata(path).map_or((false, 0), |m| (m.is_dir(), m.len())),
+            };
+            if is_dir {
+                self.marked_dirs += 1;
+            } else {
+                self.marked_size += size;
+            }
+        }
+    }
+
+    fn cycle_age_filter(&mut self) -> AppResult<()> {
+        self.newer_than = filter::next_age_window(self.newer_than);
+        self.load_directory().map_err(anyhow::Error::from)
+    }
+
+    /// Starts typing a name filter for the listing, picking up the current
+    /// one if there is one. The directory is read once, unfiltered, for the
+    /// keystrokes to narrow down.
+    fn start_name_filter(&mut self) -> AppResult<()> {
+        self.name_filter_dir = self.current_path.clone();
+        let kept = std::mem::take(&mut self.name_filter);
+        if !kept.is_empty() {
+            self.refresh_files()?;
+        }
+        self.name_filter_mode = true;
+        self.unfiltered_files = self.files.clone();
+        self.name_filter = kept;
+        if !self.name_filter.is_empty() {
+            self.apply_name_filter();
+        }
+        Ok(())
+    }
+
+    /// Drops the name filter and lists everything again, keeping the
+    /// selection where it was.
+    fn clear_name_filter(&mut self) -> AppResult<()> {
+        self.name_filter_mode = false;
+        self.name_filter.clear();
+        self.unfiltered_files.clear();
+        let selected = self.files.get(self.selected_index).map(|f| f.path.clone());
+        self.refresh_files()?;
+        if let Some(selected) = selected {
+            self.select_entry(&selected);
+        }
+        Ok(())
+    }
+
+    /// Typing narrows the listing as it goes; Enter keeps the filter and
+    /// goes back to browsing, Esc drops it (and so does Esc later on, while
+    /// a kept filter is showing). The arrow keys still move through what's
+    /// left.
+    fn handle_name_filter_key(&mut self, key: KeyEvent) -> AppResult<bool> {
+        if !self.name_filter_mode || self.editor_focused() || self.show_terminal {
+            return Ok(false);
+        }
+        match key.code {
+            KeyCode::Enter => {
+                self.name_filter_mode = false;
+                self.unfiltered_files.clear();
+            }
+            KeyCode::Esc => self.clear_name_filter()?,
+            KeyCode::Backspace => {
+                self.name_filter.pop();
+                self.apply_name_filter();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.name_filter.push(c);
+                self.apply_name_filter();
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Filters the listing read when typing started by the name filter,
+    /// with the first match selected. Rows under a directory that's
+    /// filtered out go with it, as they would reading the disk.
+    fn apply_name_filter(&mut self) {
+        let mut files = Vec::new();
+        let mut dropped_at: Option<usize> = None;
+        let mut dropped = 0;
+        for file in &self.unfiltered_files {
+            if dropped_at.is_some_and(|depth| file.depth > depth) {
+                continue;
+            }
+            if file.name == ".." || filter::name_matches(&file.name, &self.name_filter) {
+                dropped_at = None;
+                files.push(file.clone());
+            } else {
+                dropped_at = Some(file.depth);
+                if file.depth == 0 {
+                    dropped += 1;
+                }
+            }
+        }
+        self.files = files;
+        if let Some(counts) = &mut self.filter_counts {
+            counts.name = dropped;
+        }
+        self.scroll_state = self.scroll_state.content_length(self.files.len());
+
+        let index = self
+            .files
+            .iter()
+            .position(|file| file.name != "..")
+            .unwrap_or(0);
+        self.selected_index = index;
+        self.list_state.select(Some(index));
+        self.scroll_state = self.scroll_state.position(index);
+    }
+
+    fn toggle_inodes(&mut self) {
+        if !cfg!(unix) {
+            self.set_status("Inode numbers are only available on Unix");
+            return;
+        }
+        self.show_inodes = !self.show_inodes;
+    }
+
+    /// Closes the filter overlay on `?` or Esc. Every other key goes through
+    /// so the filters can be changed while watching the counts.
+    fn handle_filters_key(&mut self, key: KeyEvent) -> bool {
+        if !self.show_filters || self.editor_focused() {
+            return false;
+        }
+        match key.code {
+            KeyCode::Char('?') | KeyCode::Esc => self.show_filters = false,
+            _ => return false,
+        }
+        true
+    }
+
+    /// What the filters, sorting and view modes currently do to the
+    /// listing, one line each.
+    fn filter_report(&self) -> Vec<Line<'static>> {
+        let dim = Style::default().fg(Color::DarkGray);
+        let row = |label: String, count: String, note: String| {
+            Line::from(vec![
+                Span::raw(format!("{:<30}{:>8}  ", label, count)),
+                Span::styled(note, dim),
+            ])
+        };
+        let removed = |count: usize, on: bool| {
+            if on {
+                format!("-{}", count)
+            } else {
+                "off".to_string()
+            }
+        };
+
+        let mut lines = vec![
+            Line::from(Span::styled(
+                self.current_path.display().to_string(),
+                Style::default().fg(Color::Cyan),
+            )),
+            Line::from(""),
+        ];
+        match self.filter_counts {
+            Some(counts) => {
+                lines.push(row(
+                    "Entries in the directory".to_string(),
+                    counts.read.to_string(),
+                    String::new(),
+                ));
+                lines.push(row(
+                    "  Build and cache directories".to_string(),
+                    removed(counts.clutter, self.clutter == ClutterMode::Hide),
+                    format!("{} (I cycles)", self.clutter.label()),
+                ));
+                lines.push(row(
+                    "  Hidden files".to_string(),
+                    removed(counts.hidden, !self.show_hidden),
+                    format!(
+                        "{} (a toggles)",
+                        if self.show_hidden {
+                            "shown"
+                        } else {
+                            "left out"
+                        }
+                    ),
+                ));
+                let window = self.newer_than.map(filter::format_duration);
+                lines.push(row(
+                    match &window {
+                        Some(window) => format!("  Older than {}", window),
+                        None => "  Modification age".to_string(),
+                    },
+                    removed(counts.age, window.is_some()),
+                    "(R cycles)".to_string(),
+                ));
+                lines.push(row(
+                    if self.name_filter.is_empty() {
+                        "  Name".to_string()
+                    } else {
+                        format!("  Names without \"{}\"", self.name_filter)
+                    },
+                    removed(counts.name, !self.name_filter.is_empty()),
+                    "(/ types it)".to_string(),
+                ));
+                lines.push(row(
+                    "Listed".to_string(),
+                    counts.shown().to_string(),
+                    String::new(),
+                ));
+                let nested = self.files.iter().filter(|file| file.depth > 0).count();
+                if nested > 0 {
+                    lines.push(row(
+                        "  plus rows of open directories".to_string(),
+                        nested.to_string(),
+                        "filtered the same way".to_string(),
+                    ));
+                }
+            }
+            None => lines.push(Line::from(format!(
+                "Flat view (F): every file below here, {} so far; hidden files are {}",
+                self.files.len(),
+                if self.show_hidden {
+                    "included"
+                } else {
+                    "left out"
+                }
+            ))),
+        }
+
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::raw(format!(
+                "Sorted by {} {}, ",
+                self.sort_mode.key.label(),
+                self.sort_mode.arrow()
+            )),
+            Span::raw(self.dir_order.label().to_lowercase()),
+            Span::styled("  (s, S, o)", dim),
+        ]));
+        if self.group_by_type {
+            lines.push(Line::from(vec![
+                Span::raw("Grouped by type"),
+                Span::styled("  (G)", dim),
+            ]));
+        }
+        lines
+    }
+
+    fn toggle_hidden_summary(&mut self) {
+        self.show_hidden_summary = !self.show_hidden_summary;
+        if self.show_hidden_summary && !self.show_hidden {
+            self.set_status("Hidden size summary on (press 'a' to show hidden files)");
+        }
+    }
+
+    /// Combined size of the listed entries, rounded and exact.
+    fn total_size_label(&self) -> String {
+        let total = self
+            .files
+            .iter()
+            .filter(|file| file.name != "..")
+            .map(|file| file.size)
+            .sum();
+        format!("Total: {}", size::format_total(total, self.size_units))
+    }
+
+    /// With files open, shows the listing beside them and moves the focus
+    /// between the two, so another file can be found without closing any.
+    fn toggle_browser_focus(&mut self) {
+        self.show_file_pane = true;
+        self.browser_focused = !self.browser_focused;
+    }
+
+    /// Lists the active file's directory beside it with the file selected,
+    /// and moves the focus there.
+    fn reveal_active_file(&mut self) {
+        let Some(tab) = self.tab_manager.get_active_tab() else {
+            return;
+        };
+        let path = tab.path.clone();
+        if tab.is_untitled() || !path.exists() {
+            self.set_status("This file isn't saved on disk yet");
+            return;
+        }
+        if path
+            .parent()
+            .is_none_or(|parent| !self.is_within_root(parent))
+        {
+            self.set_status("The file is outside the root directory");
+            return;
+        }
+
+        self.list_parent_of(&path);
+        self.show_file_pane = true;
+        self.browser_focused = true;
+        if self
+            .files
+            .get(self.selected_index)
+            .is_none_or(|file| file.path != path)
+        {
+            self.set_status("The file is hidden by the current filters");
+        }
+    }
+
+    fn hide_file_pane(&mut self) {
+        self.show_file_pane = false;
+        self.browser_focused = false;
+    }
+
+    /// Whether keys go to the open file rather than the listing.
+    fn editor_focused(&self) -> bool {
+        self.tab_manager.has_tabs() && !self.browser_focused
+    }
+
+    fn focus_other_pane(&mut self) {
+        if !self.tab_manager.focus_other_pane() {
+            self.set_status("No split; use Open in Split from Ctrl+P");
+        }
+    }
+
+    /// Whether a buffer of `len` bytes is drawn with syntax colors.
+    fn highlights(&self, len: usize) -> bool {
+        self.syntax_highlighting && len <= self.config.highlight_max_bytes
+    }
+
+    /// Opens the config file in a tab, writing the commented defaults first
+    /// if it doesn't exist yet. Saving it applies it.
+    fn edit_config(&mut self) {
+        let path = match Config::create_if_missing() {
+            Ok(path) => path,
+            Err(e) => {
+                self.report_error(format!("Could not create the config file: {}", e));
+                return;
+            }
+        };
+        match encoding::read_file(&path) {
+            Ok(decoded) => self.add_file_tab("config.toml".to_string(), path, decoded),
+            Err(e) => self.report_error(format!("Could not read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Applies the config file again after it's saved. Settings that the
+    /// session can change are only taken over when the file changed them,
+    /// so a toggle made with a key (or a command-line flag) survives an
+    /// unrelated edit.
+    fn reload_config(&mut self, path: &Path) -> AppResult<()> {
+        let config = match Config::load_from(path) {
+            Ok(config) => config,
+            Err(e) => {
+                self.report_error(format!("Config not applied: {}", e));
+                return Ok(());
+            }
+        };
+        let old = std::mem::replace(&mut self.config, config);
+
+        if self.config.theme_dir != old.theme_dir {
+            let (theme_set, theme_errors) = themes::load(self.config.theme_dir.as_deref());
+            self.theme_set = theme_set;
+            for error in theme_errors {
+                self.report_error(error);
+            }
+        }
+        if self.config.theme != old.theme || self.config.theme_dir != old.theme_dir {
+            self.use_configured_theme();
+        }
+        if self.config.media_info != old.media_info {
+            self.ffprobe_available = self.config.media_info && media::ffprobe_available();
+        }
+        if self.config.size_units != old.size_units {
+            self.size_units = self.config.size_units;
+        }
+        if self.config.auto_descend != old.auto_descend {
+            self.auto_descend = self.config.auto_descend;
+        }
+        if self.config.syntax_highlighting != old.syntax_highlighting {
+            self.syntax_highlighting = self.config.syntax_highlighting;
+        }
+        let mut relist = self.config.clutter_dirs != old.clutter_dirs;
+        if self.config.dir_order != old.dir_order {
+            self.dir_order = self.config.dir_order;
+            relist = true;
+        }
+        if self.config.clutter != old.clutter {
+            self.clutter = self.config.clutter;
+            relist = true;
+        }
+        if relist {
+            let selected = self.files.get(self.selected_index).map(|f| f.path.clone());
+            self.refresh_files()?;
+            if let Some(selected) = selected {
+                self.select_entry(&selected);
+            }
+        }
+        self.set_status("Config reloaded");
+        Ok(())
+    }
+
+    fn is_config_file(path: &Path) -> bool {
+        Config::path().is_some_and(|config| config == path)
+    }
+
+    /// Switches to the configured theme, or to the default one with an
+    /// error if no theme has that name.
+    fn use_configured_theme(&mut self) {
+        if self.theme_set.themes.contains_key(&self.config.theme) {
+            self.current_theme = self.config.theme.clone();
+        } else {
+            self.current_theme = themes::DEFAULT_THEME.to_string();
+            self.report_error(format!(
+                "No theme named {}; using {}",
+                self.config.theme,
+                themes::DEFAULT_THEME
+            ));
+        }
+    }
+
+    fn cycle_theme(&mut self) {
+        self.current_theme = themes::next(&self.theme_set, &self.current_theme).to_string();
+        self.set_status(format!("Theme: {}", self.current_theme));
+    }
+
+    fn toggle_syntax_highlighting(&mut self) {
+        self.syntax_highlighting = !self.syntax_highlighting;
+        self.set_status(if self.syntax_highlighting {
+            "Syntax highlighting on"
+        } else {
+            "Syntax highlighting off"
+        });
+    }
+
+    fn toggle_editor_wrap(&mut self) {
+        self.editor_wrap = !self.editor_wrap;
+        if let Some(tab) = self.tab_manager.get_active_tab_mut() {
+            tab.horizontal_scroll = 0;
+        }
+        self.set_status(if self.editor_wrap {
+            "Editor wraps long lines"
+        } else {
+            "Editor no longer wraps; the view follows the cursor sideways"
+        });
+    }
+
+    /// Count and total size of hidden vs. visible entries, for the header.
+    fn hidden_summary_label(&self) -> Option<String> {
+        if !self.show_hidden_summary || !self.show_hidden {
+            return None;
+        }
+
+        let (hidden, visible): (Vec<&FileItem>, Vec<&FileItem>) = self
+            .files
+            .iter()
+            .filter(|file| file.name != "..")
+            .partition(|file| file.is_hidden);
+        let total = |files: &[&FileItem]| {
+            size::format_size(
+                files.iter().map(|file| file.size).sum(),
+                self.human_readable,
+                self.size_units,
+            )
+        };
+        Some(format!(
+            "visible {} ({}) · hidden {} ({})",
+            visible.len(),
+            total(&visible),
+            hidden.len(),
+            total(&hidden)
+        ))
+    }
+
+    fn toggle_hidden(&mut self) -> AppResult<()> {
+        self.show_hidden = !self.show_hidden;
+        self.load_directory().map_err(anyhow::Error::from)
+    }
+
+    fn name_column_width(&self, total_width: usize) -> usize {
+        let max = self.name_max_width.max(self.name_min_width);
+        let mut fixed = ROW_FIXED_COLUMNS;
+        if self.show_size_bars {
+            fixed += size::BAR_WIDTH + 1;
+        }
+        total_width
+            .saturating_sub(fixed)
+            .clamp(self.name_min_width, max)
+    }
+
+    /// The git state of a listed entry. `..` holds the listing itself, so it
+    /// never gets one.
+    fn git_state(&self, file: &FileItem) -> Option<git::GitState> {
+        if file.name == ".." {
+            return None;
+        }
+        self.git_status.get(&file.path).copied()
+    }
+
+    /// Whether any listed entry has a git state to show. Clean listings
+    /// skip the marker column entirely.
+    fn shows_git_column(&self) -> bool {
+        !self.git_status.is_empty() && self.files.iter().any(|file| self.git_state(file).is_some())
+    }
+
+    fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+        self.help_scroll = 0;
+    }
+
+    /// Scrolls the help popup. The upper bound is applied when drawing, since
+    /// only the renderer knows how tall the popup is.
+    fn handle_help_key(&mut self, key: KeyEvent) -> bool {
+        if !self.show_help || key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+
+        let page = self.help_page_height.max(1);
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.help_scroll = self.help_scroll.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.help_scroll += 1,
+            KeyCode::PageUp => self.help_scroll = self.help_scroll.saturating_sub(page),
+            KeyCode::PageDown => self.help_scroll += page,
+            KeyCode::Home => self.help_scroll = 0,
+            KeyCode::End => self.help_scroll = usize::MAX,
+            _ => return false,
+        }
+        true
+    }
+
+    fn open_file(&mut self) -> io::Result<()> {
+        if let Some(selected_file) = self.files.get(self.selected_index)
+            && self.is_text_file(selected_file)
+        {
+            let file_name = selected_file.name.clone();
+            let file_path = selected_file.path.clone();
+            self.view_file(file_name, file_path);
+        }
+        Ok(())
+    }
+
+    fn view_file(&mut self, file_name: String, file_path: PathBuf) {
+        match encoding::read_file(&file_path) {
+            Ok(decoded) => {
+                self.add_file_tab(file_name, file_path.clone(), decoded);
+
+                // Initialize LSP for Go files
+                if LspClient::is_go_file(&file_path) {
+                    let rt = tokio::runtime::Runtime::new().unwrap();
+                    let _ = rt.block_on(self.open_file_with_lsp(&file_path));
+                }
+            }
+            Err(e) => self.report_error(format!("Could not read {}: {}", file_path.display(), e)),
+        }
+    }
+
+    /// Opens the text of a PDF in the pager, a page indicator in the title.
+    /// When there's no text to be had, offers the default application.
+    fn show_pdf_text(&mut self, file_name: String, file_path: PathBuf) {
+        match pdf::extract_pages(&file_path) {
+            Ok(pages) => {
+                let (content, page_starts) = pdf::paginate(&pages);
+                self.record_open(&file_path);
+                self.tab_manager.add_generated_tab(
+                    format!("{} (text)", file_name),
+                    file_path,
+                    content,
+                );
+                if let Some(tab) = self.tab_manager.get_active_tab_mut() {
+                    tab.pdf_pages = page_starts;
+                }
+            }
+            Err(reason) => self.pending_open = Some((file_path, reason)),
+        }
+    }
+
+    /// Opens a read-only tab with what a .desktop file or Windows shortcut
+    /// says, then offers to follow it.
+    fn show_shortcut(&mut self, file_name: String, file_path: PathBuf) {
+        match shortcut::read(&file_path) {
+            Ok(link) => {
+                let content = link.describe(&file_path);
+                self.tab_manager.add_generated_tab(
+                    format!("{} (shortcut)", file_name),
+                    file_path,
+                    content,
+                );
+                self.pending_shortcut = link.target;
+            }
+            Err(e) => self.report_error(format!("{}: {}", file_path.display(), e)),
+        }
+    }
+
+    /// Answers "follow this shortcut?": only y runs its command, opens its
+    /// URL, or closes the description and lists its target. Any other key,
+    /// Enter included, leaves it be, since a launcher's command is whatever
+    /// the file says.
+    fn handle_shortcut_prompt_key(&mut self, key: KeyEvent) -> bool {
+        let Some(target) = self.pending_shortcut.take() else {
+            return false;
+        };
+        if key.code != KeyCode::Char('y') {
+            self.set_status("Not followed");
+            return true;
+        }
+
+        match target {
+            shortcut::Target::Command(command) => match launch::run_detached(&command) {
+                Ok(()) => self.set_status(format!("Started {}", command)),
+                Err(e) => self.report_error(format!("Could not run {}: {}", command, e)),
+            },
+            shortcut::Target::Url(url) => {
+                if let Err(e) = launch::open_externally(Path::new(&url)) {
+                    self.report_error(format!("Could not open {}: {}", url, e));
+                }
+            }
+            shortcut::Target::Path(path) if !path.exists() => {
+                self.report_error(format!("{} doesn't exist on this system", path.display()));
+            }
+            shortcut::Target::Path(path) => {
+                self.close_file();
+                if !path.is_dir() {
+                    self.list_parent_of(&path);
+                } else if !self.is_within_root(&path) {
+                    self.set_status("The target is outside the root directory");
+                } else {
+                    let previous = std::mem::replace(&mut self.current_path, path);
+                    if let Err(e) = self.load_directory() {
+                        self.report_error(format!("Could not list the target: {}", e));
+                        self.current_path = previous;
+                        let _ = self.load_directory();
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Opens a read-only tab with the selected entry's extended attributes
+    /// and file flags, e.g. to see why an immutable file won't change.
+    fn show_attributes(&mut self) {
+        let Some(file) = self.files.get(self.selected_index) else {
+            return;
+        };
+        let (name, path) = if file.name == ".." {
+            let name = self
+                .current_path
+                .file_name()
+                .map_or_else(|| "/".to_string(), |n| n.to_string_lossy().into_owned());
+            (name, self.current_path.clone())
+        } else {
+            (file.name.clone(), file.path.clone())
+        };
+        let content = attributes::read(&path).describe(&path);
+        self.tab_manager
+            .add_generated_tab(format!("{} (attributes)", name), path, content);
+    }
+
+    /// Opens a read-only tab describing a GIF or video: resolution, frame
+    /// count and duration, as far as they can be found out.
+    fn show_media_info(&mut self, file_name: String, file_path: PathBuf) {
+        match media::probe(&file_path, self.ffprobe_available) {
+            Ok(info) => {
+                let content = info.describe(&file_path);
+                self.tab_manager.add_generated_tab(
+                    format!("{} (info)", file_name),
+                    file_path,
+                    content,
+                );
+            }
+            Err(e) => self.report_error(format!("{}: {}", file_path.display(), e)),
+        }
+    }
+
+    /// Pastes go to whatever is taking text input. Otherwise a pasted path
+    /// (or a file dragged onto the terminal) navigates to it.
+    fn handle_paste(&mut self, text: &str) -> AppResult<()> {
+        if let Some(prompt) = &mut self.prompt {
+            prompt.input.push_str(text.lines().next().unwrap_or(""));
+            return Ok(());
+        }
+        if self.search_mode {
+            self.search_query
+                .push_str(text.lines().next().unwrap_or(""));
+            self.search_in_content();
+            return Ok(());
+        }
+        if self.grep_mode {
+            self.grep_query.push_str(text.lines().next().unwrap_or(""));
+            return Ok(());
+        }
+        if self.show_terminal {
+            return self.send_to_terminal(text);
+        }
+        if self.command_palette_mode
+            || self.file_finder_mode
+            || self.file_tree_mode
+            || self.tab_manager.show_close_confirmation
+        {
+            return Ok(());
+        }
+        if let Some(tab) = self.tab_manager.get_active_tab_mut()
+            && !tab.read_only
+            && !tab.markdown_preview
+        {
+            // A paste undoes in one step
+            let before = tab.snapshot();
+            for ch in text.chars() {
+                edit_tab(tab, ch, self.config.tab_width);
+            }
+            tab.record_edit(before, undo::Edit::Other);
+            return Ok(());
+        }
+
+        match pasted_path(text, &self.current_path) {
+            Some(path) if self.is_within_root(&path) => self.reveal_path(&path),
+            Some(_) => {
+                self.set_status("Pasted path is outside the root directory");
+                Ok(())
+            }
+            None => {
+                self.set_status("Pasted text is not an existing path");
+                Ok(())
+            }
+        }
+    }
+
+    /// Navigates to `path`: directories are entered, files are selected in
+    /// their parent directory and opened when they are text.
+    fn reveal_path(&mut self, path: &Path) -> AppResult<()> {
+        if path.is_dir() {
+            self.current_path = path.to_path_buf();
+            return self.refresh_files();
+        }
+
+        let Some(parent) = path.parent() else {
+            return Ok(());
+        };
+        self.current_path = parent.to_path_buf();
+        self.refresh_files()?;
+
+        if self.select_entry(path) {
+            self.open_file()?;
+        }
+        Ok(())
+    }
+
+    /// Moves the selection to `path` if it's in the listing.
+    /// Selects the entry called exactly `name`, or else the first one whose
+    /// name starts with it. Unlike the filter and the finder nothing is
+    /// matched loosely, so the same input always lands on the same entry.
+    fn go_to_name(&mut self, name: &str) {
+        let names = || self.files.iter().filter(|file| file.name != "..");
+        let found = names()
+            .find(|file| file.name == name)
+            .or_else(|| names().find(|file| file.name.eq_ignore_ascii_case(name)));
+        let (path, others) = match found {
+            Some(file) => (file.path.clone(), 0),
+            None => {
+                let mut prefixed = names().filter(|file| file.name.starts_with(name));
+                let Some(first) = prefixed.next() else {
+                    self.report_error(format!("No entry named {} here", name));
+                    return;
+                };
+                (first.path.clone(), prefixed.count())
+            }
+        };
+        self.select_entry(&path);
+        if others > 0 {
+            self.set_status(format!("{} more entries start with {}", others, name));
+        }
+    }
+
+    fn select_entry(&mut self, path: &Path) -> bool {
+        let Some(index) = self.files.iter().position(|file| file.path == path) else {
+            return false;
+        };
+        self.selected_index = index;
+        self.list_state.select(Some(index));
+        self.scroll_state = self.scroll_state.position(index);
+        true
+    }
+
+    fn add_file_tab(&mut self, name: String, path: PathBuf, decoded: encoding::Decoded) {
+        self.record_open(&path);
+        // Opening from the listing pane hands the keyboard to the new file
+        self.browser_focused = false;
+        let beside = if std::mem::take(&mut self.open_in_split) {
+            self.tab_manager.get_active_tab().map(|tab| tab.id)
+        } else {
+            None
+        };
+        // A modeline knows better than the extension
+        let modeline_syntax = modeline::language(&decoded.text)
+            .and_then(|language| resolve_syntax(&self.syntax_set, &language))
+            .map(|syntax| syntax.name.clone());
+        let writable = fileops::is_writable(&path);
+        let reopened = self.tab_manager.find_tab_by_path(&path).is_some();
+        let history = if self.config.persistent_undo && !reopened {
+            undo::store_dir().and_then(|dir| undo::load(&dir, &path, &decoded.text))
+        } else {
+            None
+        };
+        self.tab_manager.add_tab(name, path, decoded.text);
+        if let Some(tab) = self.tab_manager.get_active_tab_mut() {
+            if let Some(history) = history {
+                tab.undo = history;
+            }
+            tab.syntax_override = modeline_syntax;
+            tab.writable = writable;
+            // A lossy decode can't be written back without mangling the file
+            tab.read_only = self.view_only || decoded.lossy || !writable;
+            tab.encoding = decoded.encoding;
+            tab.has_bom = decoded.has_bom;
+            tab.lossy = decoded.lossy;
+        }
+        if let Some(id) = beside {
+            self.tab_manager.split_with(id);
+        }
+    }
+
+    /// Ctrl+E: switches the active tab between viewing and editing, unless
+    /// what's on screen couldn't be saved back.
+    fn toggle_edit_mode(&mut self) {
+        let Some(tab) = self.tab_manager.get_active_tab() else {
+            return;
+        };
+        let refusal = if tab.generated {
+            Some(format!("{} can't be edited", tab.name))
+        } else if tab.lossy && !tab.is_untitled() {
+            Some(format!(
+                "{} didn't decode cleanly; editing it would mangle the file",
+                tab.name
+            ))
+        } else if !tab.writable {
+            Some(format!(
+                "{} isn't writable; use Make Writable from the command palette to chmod it",
+                tab.path.display()
+            ))
+        } else if tab.markdown_preview {
+            Some("Leave the preview with Ctrl+R first".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = refusal {
+            self.set_status(message);
+            return;
+        }
+        if let Some(tab) = self.tab_manager.get_active_tab_mut() {
+            tab.read_only = !tab.read_only;
+            let mode = if tab.read_only { "Viewing" } else { "Editing" };
+            let message = format!("{} {}", mode, tab.name);
+            self.set_status(message);
+        }
+    }
+
+    /// Adds owner write permission to the active tab's file and lets it be
+    /// edited, for files opened read-only because they weren't writable.
+    fn make_writable(&mut self) {
+        let Some(tab) = self.tab_manager.get_active_tab() else {
+            return;
+        };
+        let path = tab.path.clone();
+        if tab.generated || tab.is_untitled() {
+            self.set_status(format!("{} isn't a file that can be edited", tab.name));
+        } else if tab.writable {
+            self.set_status(format!("{} is already writable", path.display()));
+        } else if let Err(e) = fileops::make_writable(&path) {
+            self.report_error(format!("Could not make {} writable: {}", path.display(), e));
+        } else if !fileops::is_writable(&path) {
+            // chmod worked but something else (a read-only mount, an ACL) still says no
+            self.report_error(format!("{} is still not writable", path.display()));
+        } else if let Some(tab) = self.tab_manager.get_active_tab_mut() {
+            tab.writable = true;
+            tab.read_only = tab.lossy;
+            self.set_status(format!("Made {} writable", path.display()));
+        }
+    }
+
+    fn record_open(&mut self, path: &Path) {
+        self.frecency.record(path, frecency::now());
+        if let Some(store) = &self.frecency_path
+            && let Err(e) = self.frecency.save_to(store)
+        {
+            self.report_error(format!("Could not save open history: {}", e));
+        }
+    }
+
+    fn toggle_markdown_preview(&mut self) {
+        if let Some(tab) = self.tab_manager.get_active_tab_mut()
+            && markdown::is_markdown_path(&tab.path)
+        {
+            tab.markdown_preview = !tab.markdown_preview;
+            tab.scroll_offset = 0;
+        }
+    }
+
+    /// Handles less-style keys for a read-only tab or a Markdown preview.
+    /// Returns false for keys the regular handlers should still see (closing,
+    /// Ctrl and Alt shortcuts).
+    fn handle_pager_key(&mut self, key: KeyEvent) -> bool {
+        if self.search_mode
+            || self.grep_mode
+            || self.show_terminal
+            || self.browser_focused
+            || self.command_palette_mode
+            || self.file_finder_mode
+            || self.file_tree_mode
+            || self.tab_manager.show_close_confirmation
+            || key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return false;
+        }
+
+        let page = self.content_viewport_height.max(1);
+        let Some(tab) = self.tab_manager.get_active_tab_mut() else {
+            return false;
+        };
+        if !tab.read_only && !tab.markdown_preview {
+            return false;
+        }
+        // Scrolling moves through the lines left after folding, so a
+        // collapsed block counts as one row
+        let visible = if tab.markdown_preview {
+            (0..markdown::render(&tab.content).len()).collect()
+        } else {
+            tab.folds.visible_lines(tab.content.lines().count())
+        };
+        let max_row = visible.len().saturating_sub(page);
+        let row = visible.partition_point(|&line| line < tab.scroll_offset);
+        let scroll_to = |row: usize| visible.get(row.min(max_row)).copied().unwrap_or(0);
+
+        match key.code {
+            KeyCode::Char(' ') | KeyCode::PageDown => tab.scroll_offset = scroll_to(row + page),
+            KeyCode::Char('b') | KeyCode::PageUp => {
+                tab.scroll_offset = scroll_to(row.saturating_sub(page));
+            }
+            KeyCode::Char('j') | KeyCode::Down => tab.scroll_offset = scroll_to(row + 1),
+            KeyCode::Char('k') | KeyCode::Up => {
+                tab.scroll_offset = scroll_to(row.saturating_sub(1));
+            }
+            KeyCode::Char('g') | KeyCode::Home => tab.scroll_offset = 0,
+            KeyCode::Char('G') | KeyCode::End => tab.scroll_offset = scroll_to(max_row),
+            KeyCode::Char('z') if !tab.markdown_preview => {
+                let lines: Vec<&str> = tab.content.lines().collect();
+                let bottom = visible.get(row + page).copied().unwrap_or(lines.len());
+                if tab
+                    .folds
+                    .fold_first(&lines, tab.scroll_offset, bottom)
+                    .is_none()
+                {
+                    self.set_status("Nothing to fold in view");
+                }
+            }
+            KeyCode::Char('Z') if !tab.markdown_preview => tab.folds.clear(),
+            KeyCode::Char('F') if tab.tail.is_some() => {
+                let following = tab.tail.as_mut().map(|follow| {
+                    follow.following = !follow.following;
+                    follow.following
+                });
+                if following == Some(true) {
+                    scroll_to_end(tab, page);
+                    self.set_status("Following the end of the file");
+                } else {
+                    self.set_status("Paused; F follows again");
+                }
+            }
+            KeyCode::Char('w') => {
+                tab.horizontal_scroll = 0;
+                self.viewer_wrap = !self.viewer_wrap;
+                self.set_status(if self.viewer_wrap {
+                    "Viewer wraps long lines"
+                } else {
+                    "Viewer no longer wraps; ←/→ scroll sideways"
+                });
+            }
+            KeyCode::Char('h') | KeyCode::Left if !self.viewer_wrap => {
+                tab.horizontal_scroll = tab.horizontal_scroll.saturating_sub(HORIZONTAL_STEP);
+            }
+            KeyCode::Char('l') | KeyCode::Right if !self.viewer_wrap => {
+                let longest = tab
+                    .content
+                    .lines()
+                    .map(|line| line.chars().count())
+                    .max()
+                    .unwrap_or(0);
+                tab.horizontal_scroll = (tab.horizontal_scroll + HORIZONTAL_STEP).min(longest);
+            }
+            KeyCode::Char('T') => self.cycle_theme(),
+            KeyCode::Char('/') => self.toggle_search(),
+            KeyCode::Char('n') => self.next_search_match(),
+            KeyCode::Char('N') => self.previous_search_match(),
+            KeyCode::Char('q') | KeyCode::Esc | KeyCode::Tab | KeyCode::BackTab => return false,
+            // Everything else is swallowed so nothing reaches the editor
+            _ => {}
+        }
+        true
+    }
+
+    /// Routes typing into the find prompt. Enter keeps the matches around for
+    /// F3 / n / N; Esc drops them.
+    fn handle_search_key(&mut self, key: KeyEvent) -> bool {
+        if !self.search_mode || key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Esc => self.toggle_search(),
+            KeyCode::Enter => {
+                self.search_in_content();
+                self.jump_to_search_match();
+                self.search_mode = false;
+            }
+            KeyCode::Backspace => {
+                self.search_query.pop();
+                self.search_in_content();
+            }
+            KeyCode::Char(c) => {
+                self.search_query.push(c);
+                self.search_in_content();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Keeps the undo history of the tab at `index` for next time, if
+    /// persistent_undo is on and the buffer is what's on disk.
+    fn save_undo_history(&mut self, index: usize) {
+        if !self.config.persistent_undo {
+            return;
+        }
+        let Some(tab) = self.tab_manager.get_tab(index) else {
+            return;
+        };
+        // A generated view's empty history would wipe the file's own
+        if tab.is_untitled() || tab.generated || tab.has_unsaved_changes {
+            return;
+        }
+        let Some(dir) = undo::store_dir() else {
+            return;
+        };
+        if let Err(e) = undo::save(&dir, &tab.path, &tab.content, &tab.undo) {
+            self.report_error(format!("Could not save undo history: {}", e));
+        }
+    }
+
+    fn close_file(&mut self) {
+        if self.tab_manager.has_tabs() {
+            self.save_undo_history(self.tab_manager.get_active_tab_index());
+        }
+        match self.tab_manager.close_active_tab() {
+            // Unsaved changes put up the close confirmation instead
+            Ok(()) | Err(TabError::HasUnsavedChanges | TabError::NoTabs) => {}
+            Err(e) => self.report_error(format!("Could not close tab: {}", e)),
+        }
+    }
+
+    fn actually_close_file(&mut self) {
+        // This method is now handled by TabManager
+        if self.tab_manager.tab_to_close.is_some() {
+            self.tab_manager.confirm_close_tab();
+        }
+        // Cursor position is now managed by individual tabs
+        self.cursor_blink_state = true;
+        self.cursor_blink_changed = std::time::Instant::now();
+        // Queries stay so reopening search, the finder or the palette
+        // picks up where it left off
+        self.search_mode = false;
+        self.search_matches.clear();
+        self.current_search_match = 0;
+        self.file_finder_mode = false;
         self.file_finder_results.clear();
         self.file_finder_all_files.clear();
         self.file_finder_selected = 0;
         self.command_palette_mode = false;
-        self.command_palette_query.clear();
         self.command_palette_results.clear();
         self.command_palette_selected = 0;
         self.file_tree_mode = false;
@@ -676,18 +3977,35 @@ impl App {
         self.multi_cursor_mode = false;
     }
 
-    fn toggle_edit_mode(&mut self) {
-        // Edit mode is now determined by whether we have tabs open
-        // Individual tab editing state could be added to Tab struct if needed
-    }
-
     fn save_file(&mut self) -> AppResult<()> {
-        if let Some(tab) = self.tab_manager.get_active_tab() {
-            if tab.has_unsaved_changes {
-                fs::write(&tab.path, &tab.content)?;
-                self.tab_manager
-                    .save_active_tab()
-                    .map_err(|e| anyhow::anyhow!(e))?;
+        if self
+            .tab_manager
+            .get_active_tab()
+            .is_some_and(|tab| tab.is_untitled())
+        {
+            self.start_save_as();
+            return Ok(());
+        }
+
+        let Some(tab) = self.tab_manager.get_active_tab() else {
+            return Ok(());
+        };
+        let path = tab.path.clone();
+        if tab.read_only {
+            self.set_status(format!("{} is open read-only", path.display()));
+        } else if !tab.has_unsaved_changes {
+            self.set_status(format!("No changes to save in {}", path.display()));
+        } else if let Err(e) = tab
+            .encoded_content()
+            .and_then(|contents| fs::write(&path, contents).map_err(|e| e.to_string()))
+        {
+            // The tab stays dirty so nothing is lost; the user can retry or Save As
+            self.report_error(format!("Could not save {}: {}", path.display(), e));
+        } else {
+            match self.tab_manager.save_active_tab() {
+                Ok(_) if Self::is_config_file(&path) => self.reload_config(&path)?,
+                Ok(_) => self.set_status(format!("Saved {}", path.display())),
+                Err(e) => self.report_error(format!("Could not save {}: {}", path.display(), e)),
             }
         }
         Ok(())
@@ -695,91 +4013,210 @@ impl App {
 
     fn handle_file_edit(&mut self, ch: char) {
         if let Some(tab) = self.tab_manager.get_active_tab_mut() {
-            let chars: Vec<char> = tab.content.chars().collect();
-            let mut new_chars = chars.clone();
-            let cursor_position = Self::get_cursor_position_from_tab(tab);
-
-            match ch {
-                '\n' => {
-                    new_chars.insert(cursor_position, '\n');
-                    tab.cursor_line += 1;
-                    tab.cursor_col = 0;
-                }
-                '\t' => {
-                    // Insert 4 spaces for tab
-                    for i in 0..4 {
-                        new_chars.insert(cursor_position + i, ' ');
-                    }
-                    tab.cursor_col += 4;
-                }
-                '\u{8}' | '\u{7f}' => {
-                    // Backspace
-                    if cursor_position > 0 {
-                        new_chars.remove(cursor_position - 1);
-                        if tab.cursor_col > 0 {
-                            tab.cursor_col -= 1;
-                        } else if tab.cursor_line > 0 {
-                            tab.cursor_line -= 1;
-                            // Find the length of the previous line
-                            let lines: Vec<&str> = tab.content.lines().collect();
-                            if tab.cursor_line < lines.len() {
-                                tab.cursor_col = lines[tab.cursor_line].len();
-                            }
-                        }
-                    }
-                }
-                c if c.is_control() => {
-                    // Ignore other control characters
-                }
-                _ => {
-                    new_chars.insert(cursor_position, ch);
-                    tab.cursor_col += 1;
-                }
-            }
-
-            tab.content = new_chars.into_iter().collect();
-            tab.mark_dirty();
-
-            // Auto-scroll to keep cursor visible
-            let visible_lines = 30;
-            let total_lines = tab.content.lines().count();
-
-            if tab.cursor_line >= tab.scroll_offset + visible_lines {
-                tab.scroll_offset = tab.cursor_line.saturating_sub(visible_lines - 1);
-            } else if tab.cursor_line < tab.scroll_offset {
-                tab.scroll_offset = tab.cursor_line;
+            if tab.read_only || tab.markdown_preview {
+                return;
             }
+            let before = tab.snapshot();
+            edit_tab(tab, ch, self.config.tab_width);
+            let edit = match ch {
+                '\u{8}' | '\u{7f}' => undo::Edit::Deleting,
+                '\n' | '\t' => undo::Edit::Other,
+                _ => undo::Edit::Typing,
+            };
+            tab.record_edit(before, edit);
+        }
+    }
 
-            // Ensure we don't scroll past the end of file
-            let max_scroll = total_lines.saturating_sub(visible_lines);
-            tab.scroll_offset = tab.scroll_offset.min(max_scroll);
+    /// Ctrl+Z: steps the active file back one edit.
+    fn undo(&mut self) {
+        let undone = self
+            .tab_manager
+            .get_active_tab_mut()
+            .is_some_and(|tab| !tab.read_only && tab.undo());
+        if !undone {
+            self.set_status("Nothing to undo");
         }
     }
 
-    fn get_cursor_position_from_tab(tab: &Tab) -> usize {
-        let lines: Vec<&str> = tab.content.lines().collect();
-        let mut position = 0;
-        for (i, line) in lines.iter().enumerate() {
-            if i < tab.cursor_line {
-                position += line.len() + 1; // +1 for newline
-            } else if i == tab.cursor_line {
-                position += tab.cursor_col;
-                break;
-            }
+    /// Ctrl+Y: puts back what the last undo took away.
+    fn redo(&mut self) {
+        let redone = self
+            .tab_manager
+            .get_active_tab_mut()
+            .is_some_and(|tab| !tab.read_only && tab.redo());
+        if !redone {
+            self.set_status("Nothing to redo");
         }
-        position
     }
 
     fn update_cursor_position(&mut self) {
         self.cursor_blink_state = true;
-        self.cursor_blink_timer = 0;
+        self.cursor_blink_changed = std::time::Instant::now();
+    }
+
+    /// The caret only blinks in an editable tab; everywhere else it's
+    /// either hidden or irrelevant, so there's nothing to animate.
+    fn cursor_blinking(&self) -> bool {
+        self.config.cursor_blink
+            && self
+                .tab_manager
+                .get_active_tab()
+                .is_some_and(|tab| !tab.read_only && !tab.markdown_preview)
     }
 
     fn update_cursor_blink(&mut self) {
-        self.cursor_blink_timer += 1;
-        if self.cursor_blink_timer >= 5 {
+        if !self.cursor_blinking() {
+            self.cursor_blink_state = true;
+        } else if self.cursor_blink_changed.elapsed() >= self.config.cursor_blink_interval() {
             self.cursor_blink_state = !self.cursor_blink_state;
-            self.cursor_blink_timer = 0;
+            self.cursor_blink_changed = std::time::Instant::now();
+        }
+    }
+
+    /// How long the event loop may wait for input before redrawing. Short
+    /// while terminal output or search results stream in, timed to the next
+    /// blink or toast expiry otherwise, and long when nothing changes.
+    fn poll_timeout(&self) -> std::time::Duration {
+        if self.show_terminal
+            || self.grep_receiver.is_some()
+            || self.flat_receiver.is_some()
+            || self
+                .checksum
+                .as_ref()
+                .is_some_and(checksum::Job::is_running)
+            || (0..self.tab_manager.tab_count()).any(|index| {
+                self.tab_manager
+                    .get_tab(index)
+                    .is_some_and(|tab| tab.tail.is_some())
+            })
+        {
+            return ACTIVE_POLL;
+        }
+
+        let mut timeout = IDLE_POLL;
+        if self.cursor_blinking() {
+            let until_blink = self
+                .config
+                .cursor_blink_interval()
+                .saturating_sub(self.cursor_blink_changed.elapsed());
+            timeout = timeout.min(until_blink);
+        }
+        if let Some(remaining) = self.notifications.toast_remaining() {
+            timeout = timeout.min(remaining);
+        }
+        if let Some(remaining) = self.gutter_due {
+            timeout = timeout.min(remaining);
+        }
+        if let Some(idle) = self.idle_timeout {
+            timeout = timeout.min(idle.saturating_sub(self.last_key.elapsed()));
+        }
+        timeout
+    }
+
+    /// Whether --idle-timeout should quit now. Unsaved changes hold it off
+    /// (and restart the wait) unless --idle-force was given.
+    fn idle_quit_due(&mut self) -> bool {
+        let Some(idle) = self.idle_timeout else {
+            return false;
+        };
+        if self.last_key.elapsed() < idle {
+            return false;
+        }
+        let unsaved = self.tab_manager.has_unsaved_changes() || self.file_has_unsaved_changes;
+        if unsaved && !self.idle_force {
+            self.last_key = std::time::Instant::now();
+            self.set_status("Idle timeout reached, but there are unsaved changes");
+            return false;
+        }
+        true
+    }
+
+    /// Opens the selected file read-only, following what gets appended to
+    /// it like `tail -f`.
+    fn follow_selected_file(&mut self) {
+        let Some(file) = self.files.get(self.selected_index) else {
+            return;
+        };
+        if file.is_dir {
+            return;
+        }
+        let (name, path) = (file.name.clone(), file.path.clone());
+        // Following it again goes back to the view that already does
+        let following = (0..self.tab_manager.tab_count()).find(|&index| {
+            self.tab_manager
+                .get_tab(index)
+                .is_some_and(|tab| tab.tail.is_some() && tab.path == path)
+        });
+        if let Some(index) = following {
+            let _ = self.tab_manager.switch_to_tab(index);
+            self.browser_focused = false;
+            return;
+        }
+
+        match tail::Tail::open(&path) {
+            Ok((follow, content)) => {
+                self.record_open(&path);
+                self.tab_manager
+                    .add_generated_tab(format!("{} (tail)", name), path, content);
+                self.browser_focused = false;
+                let page = self.content_viewport_height.max(1);
+                if let Some(tab) = self.tab_manager.get_active_tab_mut() {
+                    tab.tail = Some(follow);
+                    scroll_to_end(tab, page);
+                }
+            }
+            Err(e) => self.report_error(format!("Could not read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Adds whatever the followed files gained since the last look. A file
+    /// that's gone is waited for, since log rotation briefly removes it.
+    fn poll_tails(&mut self) {
+        let page = self.content_viewport_height.max(1);
+        let mut errors = Vec::new();
+        for index in 0..self.tab_manager.tab_count() {
+            let Some(tab) = self.tab_manager.get_tab_mut(index) else {
+                continue;
+            };
+            let Some(follow) = &mut tab.tail else {
+                continue;
+            };
+            match follow.poll(&tab.path) {
+                Ok(tail::Update::Nothing) => continue,
+                Ok(tail::Update::Appended(text)) => tab.content.push_str(&text),
+                Ok(tail::Update::Restarted(text)) => tab.content = text,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    errors.push(format!("Stopped following {}: {}", tab.path.display(), e));
+                    tab.tail = None;
+                    continue;
+                }
+            }
+            tail::trim_front(&mut tab.content, tail::MAX_BYTES);
+            tab.original_content.clone_from(&tab.content);
+            if tab.tail.as_ref().is_some_and(|follow| follow.following) {
+                scroll_to_end(tab, page);
+            }
+        }
+        for error in errors {
+            self.report_error(error);
+        }
+    }
+
+    /// Rediffs the change markers of the tabs on screen once edits settle.
+    fn refresh_gutters(&mut self) {
+        let now = std::time::Instant::now();
+        let on_screen = match self.tab_manager.split_panes() {
+            Some((left, right)) => vec![left, right],
+            None => vec![self.tab_manager.get_active_tab_index()],
+        };
+        self.gutter_due = None;
+        for index in on_screen {
+            if let Some(tab) = self.tab_manager.get_tab_mut(index)
+                && let Some(wait) = tab.gutter.refresh(&tab.original_content, &tab.content, now)
+            {
+                self.gutter_due = Some(self.gutter_due.map_or(wait, |due| due.min(wait)));
+            }
         }
     }
 
@@ -789,6 +4226,7 @@ impl App {
         }
 
         if let Some(tab) = self.tab_manager.get_active_tab_mut() {
+            tab.undo.break_run();
             let lines: Vec<&str> = tab.content.lines().collect();
             let total_lines = lines.len();
 
@@ -797,7 +4235,7 @@ impl App {
                     if tab.cursor_line > 0 {
                         tab.cursor_line -= 1;
                         let line_len = if tab.cursor_line < lines.len() {
-                            lines[tab.cursor_line].len()
+                            lines[tab.cursor_line].chars().count()
                         } else {
                             0
                         };
@@ -808,7 +4246,7 @@ impl App {
                     if tab.cursor_line < lines.len().saturating_sub(1) {
                         tab.cursor_line += 1;
                         let line_len = if tab.cursor_line < lines.len() {
-                            lines[tab.cursor_line].len()
+                            lines[tab.cursor_line].chars().count()
                         } else {
                             0
                         };
@@ -821,7 +4259,7 @@ impl App {
                     } else if tab.cursor_line > 0 {
                         tab.cursor_line -= 1;
                         tab.cursor_col = if tab.cursor_line < lines.len() {
-                            lines[tab.cursor_line].len()
+                            lines[tab.cursor_line].chars().count()
                         } else {
                             0
                         };
@@ -829,7 +4267,7 @@ impl App {
                 }
                 CursorDirection::Right => {
                     let current_line_len = if tab.cursor_line < lines.len() {
-                        lines[tab.cursor_line].len()
+                        lines[tab.cursor_line].chars().count()
                     } else {
                         0
                     };
@@ -863,24 +4301,19 @@ impl App {
             tab.revert_changes();
         }
         self.search_mode = false;
-        self.search_query.clear();
         self.search_matches.clear();
         self.current_search_match = 0;
         self.multi_cursors.clear();
         self.multi_cursor_mode = false;
     }
 
-    fn discard_changes(&mut self) {
-        if let Some(tab) = self.tab_manager.get_active_tab_mut() {
-            tab.revert_changes();
-        }
-        self.tab_manager.cancel_close_tab();
-    }
-
+    /// Opens or closes the search bar. Closing keeps the query, so opening
+    /// it again brings back the last search and its matches.
     fn toggle_search(&mut self) {
         self.search_mode = !self.search_mode;
-        if !self.search_mode {
-            self.search_query.clear();
+        if self.search_mode {
+            self.search_in_content();
+        } else {
             self.search_matches.clear();
             self.current_search_match = 0;
         }
@@ -899,10 +4332,10 @@ impl App {
                 while let Some(pos) = line[start..].find(&self.search_query) {
                     self.search_matches.push(SearchMatch {
                         line: line_idx,
-                        col: start + pos,
+                        col: line[..start + pos].chars().count(),
                         text: self.search_query.clone(),
                     });
-                    start += pos + 1;
+                    start += pos + self.search_query.len();
                 }
             }
         }
@@ -913,17 +4346,7 @@ impl App {
     fn next_search_match(&mut self) {
         if !self.search_matches.is_empty() {
             self.current_search_match = (self.current_search_match + 1) % self.search_matches.len();
-            let match_item = &self.search_matches[self.current_search_match];
-            self.cursor_line = match_item.line;
-            self.cursor_col = match_item.col;
-
-            // Auto-scroll to match
-            let visible_lines = 30;
-            if self.cursor_line >= self.file_content_scroll + visible_lines {
-                self.file_content_scroll = self.cursor_line.saturating_sub(visible_lines / 2);
-            } else if self.cursor_line < self.file_content_scroll {
-                self.file_content_scroll = self.cursor_line.saturating_sub(visible_lines / 2);
-            }
+            self.jump_to_search_match();
         }
     }
 
@@ -934,30 +4357,62 @@ impl App {
             } else {
                 self.current_search_match - 1
             };
-            let match_item = &self.search_matches[self.current_search_match];
-            self.cursor_line = match_item.line;
-            self.cursor_col = match_item.col;
+            self.jump_to_search_match();
+        }
+    }
+
+    fn jump_to_search_match(&mut self) {
+        let Some(match_item) = self.search_matches.get(self.current_search_match) else {
+            return;
+        };
+        let (line, col) = (match_item.line, match_item.col);
+        let visible_lines = self.content_viewport_height;
+
+        if let Some(tab) = self.tab_manager.get_active_tab_mut() {
+            tab.cursor_line = line;
+            tab.cursor_col = col;
+            tab.undo.break_run();
+            tab.folds.reveal(line);
 
             // Auto-scroll to match
-            let visible_lines = 30;
-            if self.cursor_line >= self.file_content_scroll + visible_lines {
-                self.file_content_scroll = self.cursor_line.saturating_sub(visible_lines / 2);
-            } else if self.cursor_line < self.file_content_scroll {
-                self.file_content_scroll = self.cursor_line.saturating_sub(visible_lines / 2);
+            if line >= tab.scroll_offset + visible_lines || line < tab.scroll_offset {
+                tab.scroll_offset = line.saturating_sub(visible_lines / 2);
             }
         }
     }
 
+    /// Moves the cursor from a bracket to its partner, scrolling it into
+    /// view. Does nothing off a bracket or when it has no partner.
+    fn jump_to_matching_bracket(&mut self) {
+        let visible_lines = self.content_viewport_height;
+        let Some(tab) = self.tab_manager.get_active_tab_mut() else {
+            return;
+        };
+        let Some((line, col)) =
+            editor::matching_bracket(&tab.content, tab.cursor_line, tab.cursor_col)
+        else {
+            return;
+        };
+
+        tab.cursor_line = line;
+        tab.cursor_col = col;
+        tab.undo.break_run();
+        if line >= tab.scroll_offset + visible_lines || line < tab.scroll_offset {
+            tab.scroll_offset = line.saturating_sub(visible_lines / 2);
+        }
+    }
+
     fn toggle_file_finder(&mut self) {
         self.file_finder_mode = !self.file_finder_mode;
+        self.open_in_split = false;
         if self.file_finder_mode {
             if self.file_finder_all_files.is_empty() {
                 self.scan_files();
             } else {
-                self.file_finder_results = self.file_finder_all_files.clone();
+                self.filter_file_results();
             }
         } else {
-            self.file_finder_query.clear();
+            // The query stays for next time; opening a file clears it
             self.file_finder_selected = 0;
         }
     }
@@ -967,8 +4422,7 @@ impl App {
         let current_path = self.current_path.clone();
         self.scan_directory_recursive(&current_path);
         self.file_finder_all_files.sort();
-        self.file_finder_results = self.file_finder_all_files.clone();
-        self.file_finder_selected = 0;
+        self.filter_file_results();
     }
 
     fn scan_directory_recursive(&mut self, dir: &PathBuf) {
@@ -976,113 +4430,228 @@ impl App {
             for entry in entries.flatten() {
                 let path = entry.path();
                 if path.is_file() {
-                    if let Some(file_name) = path.file_name() {
-                        if let Some(name_str) = file_name.to_str() {
-                            if !name_str.starts_with('.') {
-                                self.file_finder_all_files.push(path);
-                            }
-                        }
-                    }
-                } else if path.is_dir() {
-                    if let Some(dir_name) = path.file_name() {
-                        if let Some(name_str) = dir_name.to_str() {
-                            if !name_str.starts_with('.')
-                                && name_str != "target"
-                                && name_str != "node_modules"
-                            {
-                                self.scan_directory_recursive(&path);
-                            }
-                        }
+                    if let Some(file_name) = path.file_name()
+                        && let Some(name_str) = file_name.to_str()
+                        && !name_str.starts_with('.')
+                    {
+                        self.file_finder_all_files.push(path);
                     }
+                } else if path.is_dir()
+                    && let Some(dir_name) = path.file_name()
+                    && let Some(name_str) = dir_name.to_str()
+                    && !name_str.starts_with('.')
+                    && name_str != "target"
+                    && name_str != "node_modules"
+                {
+                    self.scan_directory_recursive(&path);
                 }
             }
         }
     }
 
+    /// Fuzzy-matches file names against the query and ranks the matches by
+    /// match quality plus a bonus for files opened often and recently. An
+    /// empty query lists everything, most used first.
     fn filter_file_results(&mut self) {
-        if self.file_finder_query.is_empty() {
-            self.file_finder_results = self.file_finder_all_files.clone();
-            self.file_finder_selected = 0;
-            return;
-        }
-
-        let query = self.file_finder_query.to_lowercase();
-        self.file_finder_results = self
+        let matcher = SkimMatcherV2::default();
+        let now = frecency::now();
+        let mut scored: Vec<(i64, &PathBuf)> = self
             .file_finder_all_files
             .iter()
-            .filter(|path| {
-                if let Some(file_name) = path.file_name() {
-                    if let Some(name_str) = file_name.to_str() {
-                        return name_str.to_lowercase().contains(&query);
-                    }
-                }
-                false
+            .filter_map(|path| {
+                let name = path.file_name()?.to_str()?;
+                let match_score = if self.file_finder_query.is_empty() {
+                    0
+                } else {
+                    matcher.fuzzy_match(name, &self.file_finder_query)?
+                };
+                // Logarithmic so heavy use can't bury a much better match
+                let bonus = (self.frecency.score(path, now).ln_1p() * 20.0) as i64;
+                Some((match_score + bonus, path))
             })
-            .cloned()
             .collect();
+        // Stable, so equal scores keep path order
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+        self.file_finder_results = scored.into_iter().map(|(_, path)| path.clone()).collect();
         self.file_finder_selected = 0;
     }
 
+    fn toggle_grep(&mut self) {
+        self.grep_mode = !self.grep_mode;
+    }
+
+    fn start_grep(&mut self) {
+        self.grep_results.clear();
+        self.grep_selected = 0;
+        self.grep_results_query = self.grep_query.clone();
+        self.grep_receiver = if self.grep_query.is_empty() {
+            None
+        } else {
+            Some(grep::spawn_search(
+                self.current_path.clone(),
+                self.grep_query.clone(),
+                is_text_path,
+            ))
+        };
+    }
+
+    /// Collects whatever the background search has found since the last
+    /// frame. Called once per event loop iteration.
+    fn poll_grep_results(&mut self) {
+        let Some(receiver) = &self.grep_receiver else {
+            return;
+        };
+        loop {
+            match receiver.try_recv() {
+                Ok(grep_match) => {
+                    self.grep_results.push(grep_match);
+                    if self.grep_results.len() >= MAX_GREP_RESULTS {
+                        self.grep_receiver = None;
+                        return;
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.grep_receiver = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    fn open_grep_result(&mut self) {
+        let Some(grep_match) = self.grep_results.get(self.grep_selected).cloned() else {
+            return;
+        };
+        let Ok(decoded) = encoding::read_file(&grep_match.path) else {
+            self.report_error(format!("Could not read {}", grep_match.path.display()));
+            return;
+        };
+
+        let file_name = grep_match
+            .path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Untitled")
+            .to_string();
+        self.add_file_tab(file_name, grep_match.path.clone(), decoded);
+        self.goto_line(grep_match.line);
+        self.grep_mode = false;
+        self.list_parent_of(&grep_match.path);
+    }
+
+    /// Lists the directory `path` is in with it selected, so `..` and the
+    /// rest of the browser follow a file opened from a search result.
+    fn list_parent_of(&mut self, path: &Path) {
+        let Some(parent) = path.parent() else {
+            return;
+        };
+        if !self.is_within_root(parent) {
+            return;
+        }
+        if parent != self.current_path {
+            let previous = std::mem::replace(&mut self.current_path, parent.to_path_buf());
+            if let Err(e) = self.load_directory() {
+                self.report_error(format!("Could not list {}: {}", parent.display(), e));
+                self.current_path = previous;
+                let _ = self.load_directory();
+                return;
+            }
+        }
+        self.select_entry(path);
+    }
+
+    /// Puts the active tab's cursor on `line` (0-based, clamped to the file)
+    /// and scrolls it to the middle of the view.
+    fn goto_line(&mut self, line: usize) {
+        let visible_lines = self.content_viewport_height;
+        if let Some(tab) = self.tab_manager.get_active_tab_mut() {
+            let line = line.min(tab.content.lines().count().saturating_sub(1));
+            tab.cursor_line = line;
+            tab.cursor_col = 0;
+            tab.undo.break_run();
+            tab.folds.reveal(line);
+            tab.scroll_offset = line.saturating_sub(visible_lines / 2);
+        }
+    }
+
+    /// Scrolls tab `index` to the lines minimap row `row` (of `rows`) stands
+    /// for, with the cursor at the first of them.
+    fn minimap_jump(&mut self, index: usize, row: usize, rows: usize) {
+        let visible_lines = self.content_viewport_height;
+        if let Some(tab) = self.tab_manager.get_tab_mut(index) {
+            let total = tab.content.lines().count();
+            let line = minimap::line_at(row, total, rows);
+            tab.cursor_line = line;
+            tab.cursor_col = 0;
+            tab.undo.break_run();
+            tab.folds.reveal(line);
+            tab.scroll_offset = line
+                .saturating_sub(visible_lines / 2)
+                .min(total.saturating_sub(visible_lines));
+        }
+    }
+
+    /// Typing edits the query; Enter searches, or opens the selected match
+    /// once the results are for the current query.
+    fn handle_grep_key(&mut self, key: KeyEvent) -> bool {
+        if !self.grep_mode {
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Esc => self.grep_mode = false,
+            KeyCode::Enter if self.grep_query != self.grep_results_query => self.start_grep(),
+            KeyCode::Enter => self.open_grep_result(),
+            KeyCode::Up => self.grep_selected = self.grep_selected.saturating_sub(1),
+            KeyCode::Down if self.grep_selected + 1 < self.grep_results.len() => {
+                self.grep_selected += 1;
+            }
+            KeyCode::PageUp => self.grep_selected = self.grep_selected.saturating_sub(10),
+            KeyCode::PageDown => {
+                self.grep_selected =
+                    (self.grep_selected + 10).min(self.grep_results.len().saturating_sub(1));
+            }
+            KeyCode::Backspace => {
+                self.grep_query.pop();
+            }
+            // Leave Ctrl shortcuts (Ctrl+G to close, Ctrl+Q to quit) to the main loop
+            _ if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Char(c) => self.grep_query.push(c),
+            _ => {}
+        }
+        true
+    }
+
     fn toggle_command_palette(&mut self) {
         self.command_palette_mode = !self.command_palette_mode;
         if self.command_palette_mode {
             self.populate_command_palette();
         } else {
-            self.command_palette_query.clear();
+            // The query stays for next time; running a command clears it
             self.command_palette_selected = 0;
         }
     }
 
     fn populate_command_palette(&mut self) {
-        self.command_palette_results = vec![
-            "Open File".to_string(),
-            "New Tab".to_string(),
-            "Close Tab".to_string(),
-            "Close All Tabs".to_string(),
-            "Save".to_string(),
-            "Save All".to_string(),
-            "Show File Tree".to_string(),
-            "Show Terminal".to_string(),
-            "Toggle Hidden Files".to_string(),
-            "Refresh".to_string(),
-            "Go to Parent Directory".to_string(),
-            "Exit".to_string(),
-        ];
         self.filter_command_results();
     }
 
     fn filter_command_results(&mut self) {
-        if self.command_palette_query.is_empty() {
-            self.populate_command_palette();
-            return;
-        }
-
         let query = self.command_palette_query.to_lowercase();
-        let all_commands = vec![
-            "Open File".to_string(),
-            "New Tab".to_string(),
-            "Close Tab".to_string(),
-            "Close All Tabs".to_string(),
-            "Save".to_string(),
-            "Save All".to_string(),
-            "Show File Tree".to_string(),
-            "Show Terminal".to_string(),
-            "Toggle Hidden Files".to_string(),
-            "Refresh".to_string(),
-            "Go to Parent Directory".to_string(),
-            "Exit".to_string(),
-        ];
-
-        self.command_palette_results = all_commands
-            .into_iter()
+        self.command_palette_results = COMMAND_PALETTE_COMMANDS
+            .iter()
             .filter(|cmd| cmd.to_lowercase().contains(&query))
+            .map(|cmd| cmd.to_string())
             .collect();
         self.command_palette_selected = 0;
     }
 
     fn execute_command(&mut self) -> AppResult<()> {
         if self.command_palette_selected < self.command_palette_results.len() {
-            let command = &self.command_palette_results[self.command_palette_selected];
+            self.command_palette_query.clear();
+            let command = self.command_palette_results[self.command_palette_selected].clone();
             match command.as_str() {
                 "Open File" => {
                     self.command_palette_mode = false;
@@ -1092,15 +4661,23 @@ impl App {
                     self.command_palette_mode = false;
                     self.toggle_file_finder();
                 }
+                "Open in Split" => {
+                    self.command_palette_mode = false;
+                    self.toggle_file_finder();
+                    self.open_in_split = self.file_finder_mode && self.tab_manager.has_tabs();
+                }
+                "Close Split" => {
+                    self.command_palette_mode = false;
+                    self.tab_manager.close_split();
+                }
                 "Close Tab" => {
                     self.command_palette_mode = false;
-                    if self.tab_manager.has_tabs() {
-                        let _ = self.tab_manager.close_active_tab();
-                    }
+                    self.close_file();
                 }
                 "Close All Tabs" => {
                     self.command_palette_mode = false;
                     while self.tab_manager.has_tabs() {
+                        self.save_undo_history(0);
                         let _ = self.tab_manager.force_close_tab(0);
                     }
                 }
@@ -1108,21 +4685,112 @@ impl App {
                     self.command_palette_mode = false;
                     self.save_file()?;
                 }
+                "Save As" => {
+                    self.command_palette_mode = false;
+                    self.start_save_as();
+                }
+                "Rename" => {
+                    self.command_palette_mode = false;
+                    self.start_rename();
+                }
+                "New Directory" => {
+                    self.command_palette_mode = false;
+                    self.start_prompt(PromptKind::NewDirectory, String::new());
+                }
+                "Set Syntax" => {
+                    self.command_palette_mode = false;
+                    if let Some(tab) = self.tab_manager.get_active_tab() {
+                        let current = tab_syntax(&self.syntax_set, tab).name.clone();
+                        self.start_prompt(PromptKind::SetSyntax, current);
+                    }
+                }
+                "Paste Into New File" => {
+                    self.command_palette_mode = false;
+                    self.paste_into_new_file();
+                }
+                "Checksum" => {
+                    self.command_palette_mode = false;
+                    self.open_checksum();
+                }
+                "Make Writable" => {
+                    self.command_palette_mode = false;
+                    self.make_writable();
+                }
                 "Save All" => {
                     self.command_palette_mode = false;
                     let saved_files = self.tab_manager.save_all_tabs();
+                    let count = saved_files.len();
+                    let mut failed = 0;
+                    let mut config_saved = None;
                     for (path, content) in saved_files {
-                        let _ = fs::write(&path, &content);
+                        let written = content.and_then(|content| {
+                            fs::write(&path, content).map_err(|e| e.to_string())
+                        });
+                        if let Err(e) = written {
+                            failed += 1;
+                            // Still unsaved, so closing it keeps asking first
+                            if let Some(index) = self.tab_manager.find_tab_by_path(&path)
+                                && let Some(tab) = self.tab_manager.get_tab_mut(index)
+                            {
+                                tab.mark_dirty();
+                            }
+                            self.report_error(format!("Could not save {}: {}", path.display(), e));
+                        } else if Self::is_config_file(&path) {
+                            config_saved = Some(path);
+                        }
+                    }
+                    if failed == 0 {
+                        self.set_status(format!("Saved {} file(s)", count));
+                    }
+                    if let Some(path) = config_saved {
+                        self.reload_config(&path)?;
                     }
                 }
                 "Show File Tree" => {
                     self.command_palette_mode = false;
                     self.toggle_file_tree();
                 }
+                "Show Log" => {
+                    self.command_palette_mode = false;
+                    self.toggle_log();
+                }
+                "Recent Files" => {
+                    self.command_palette_mode = false;
+                    // Files outside --root are kept, just not offered
+                    let recent = self
+                        .frecency
+                        .recent()
+                        .into_iter()
+                        .filter(|(path, _)| self.is_within_root(path))
+                        .collect();
+                    self.history = Some(recent);
+                    self.history_selected = 0;
+                    self.history_confirm_clear = false;
+                }
                 "Show Terminal" => {
                     self.command_palette_mode = false;
                     self.show_terminal = !self.show_terminal;
                 }
+                "Toggle Auto-Descend" => {
+                    self.command_palette_mode = false;
+                    self.toggle_auto_descend();
+                }
+                "Edit Config" => {
+                    self.command_palette_mode = false;
+                    self.edit_config();
+                }
+                "Revert Changes" => {
+                    self.command_palette_mode = false;
+                    self.revert_changes();
+                }
+                "Next Theme" => {
+                    self.command_palette_mode = false;
+                    self.cycle_theme();
+                }
+                "Go To Name" => {
+                    self.command_palette_mode = false;
+                    self.start_prompt(PromptKind::GoToName, String::new());
+                }
                 "Toggle Hidden Files" => {
                     self.command_palette_mode = false;
                     self.show_hidden = !self.show_hidden;
@@ -1132,12 +4800,21 @@ impl App {
                     self.command_palette_mode = false;
                     self.refresh_files()?;
                 }
+                "Copy Path" => {
+                    self.command_palette_mode = false;
+                    self.copy_focused_path(false);
+                }
+                "Copy Relative Path" => {
+                    self.command_palette_mode = false;
+                    self.copy_focused_path(true);
+                }
+                "Search in Files" => {
+                    self.command_palette_mode = false;
+                    self.grep_mode = true;
+                }
                 "Go to Parent Directory" => {
                     self.command_palette_mode = false;
-                    if let Some(parent) = self.current_path.parent() {
-                        self.current_path = parent.to_path_buf();
-                        self.refresh_files()?;
-                    }
+                    self.go_to_parent()?;
                 }
                 "Exit" => {
                     self.command_palette_mode = false;
@@ -1175,7 +4852,10 @@ impl App {
                 match (a_is_dir, b_is_dir) {
                     (true, false) => std::cmp::Ordering::Less,
                     (false, true) => std::cmp::Ordering::Greater,
-                    _ => a.file_name().cmp(&b.file_name()),
+                    _ => natural_cmp(
+                        &a.file_name().to_string_lossy(),
+                        &b.file_name().to_string_lossy(),
+                    ),
                 }
             });
 
@@ -1184,10 +4864,11 @@ impl App {
                 let is_dir = entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
 
                 // Skip hidden files unless show_hidden is true
-                if let Some(name) = entry_path.file_name().and_then(|n| n.to_str()) {
-                    if name.starts_with('.') && !self.show_hidden {
-                        continue;
-                    }
+                if let Some(name) = entry_path.file_name().and_then(|n| n.to_str())
+                    && name.starts_with('.')
+                    && !self.show_hidden
+                {
+                    continue;
                 }
 
                 self.file_tree_items
@@ -1226,18 +4907,15 @@ impl App {
                 self.refresh_files()?;
             } else if self.is_text_file_path(path) {
                 // Open file as tab
-                match fs::read_to_string(path) {
-                    Ok(content) => {
-                        let file_name = path
-                            .file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or("Untitled")
-                            .to_string();
-
-                        self.tab_manager.add_tab(file_name, path.clone(), content);
-                        self.file_tree_mode = false;
-                    }
-                    Err(_) => {}
+                if let Ok(decoded) = encoding::read_file(path) {
+                    let file_name = path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("Untitled")
+                        .to_string();
+
+                    self.add_file_tab(file_name, path.clone(), decoded);
+                    self.file_tree_mode = false;
                 }
             }
         }
@@ -1247,70 +4925,28 @@ impl App {
     fn open_selected_file(&mut self) -> AppResult<()> {
         if self.file_finder_selected < self.file_finder_results.len() {
             let file_path = &self.file_finder_results[self.file_finder_selected];
-            if self.is_text_file_path(file_path) {
-                match fs::read_to_string(file_path) {
-                    Ok(content) => {
-                        // Open as new tab instead of replacing file content
-                        let file_name = file_path
-                            .file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or("Untitled")
-                            .to_string();
-
-                        self.tab_manager
-                            .add_tab(file_name, file_path.clone(), content);
-                        self.file_finder_mode = false;
-                        self.file_finder_query.clear();
-                    }
-                    Err(_) => {}
-                }
+            if self.is_text_file_path(file_path)
+                && let Ok(decoded) = encoding::read_file(file_path)
+            {
+                // Open as new tab instead of replacing file content
+                let file_name = file_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("Untitled")
+                    .to_string();
+
+                let file_path = file_path.clone();
+                self.add_file_tab(file_name, file_path.clone(), decoded);
+                self.file_finder_mode = false;
+                self.file_finder_query.clear();
+                self.list_parent_of(&file_path);
             }
         }
         Ok(())
     }
 
-    fn is_text_file_path(&self, path: &PathBuf) -> bool {
-        if let Some(ext) = path.extension() {
-            if let Some(ext_str) = ext.to_str() {
-                return matches!(
-                    ext_str.to_lowercase().as_str(),
-                    "txt"
-                        | "md"
-                        | "rs"
-                        | "py"
-                        | "js"
-                        | "ts"
-                        | "html"
-                        | "css"
-                        | "json"
-                        | "xml"
-                        | "yaml"
-                        | "yml"
-                        | "toml"
-                        | "cfg"
-                        | "conf"
-                        | "log"
-                        | "sh"
-                        | "bash"
-                        | "zsh"
-                        | "fish"
-                        | "c"
-                        | "cpp"
-                        | "h"
-                        | "hpp"
-                        | "java"
-                        | "go"
-                        | "php"
-                        | "rb"
-                        | "pl"
-                        | "lua"
-                        | "vim"
-                        | "sql"
-                        | "csv"
-                );
-            }
-        }
-        false
+    fn is_text_file_path(&self, path: &Path) -> bool {
+        is_text_path(path)
     }
 
     fn toggle_multi_cursor(&mut self) {
@@ -1337,18 +4973,18 @@ impl App {
     }
 
     fn delete_confirmed_file(&mut self) -> AppResult<()> {
-        if let Some(file_path) = &self.file_to_delete {
-            if file_path.exists() {
-                fs::remove_file(file_path)?;
-                // Remove from our cached lists
-                self.file_finder_all_files.retain(|p| p != file_path);
-                self.file_finder_results.retain(|p| p != file_path);
-                // Adjust selection if needed
-                if self.file_finder_selected >= self.file_finder_results.len()
-                    && self.file_finder_selected > 0
-                {
-                    self.file_finder_selected -= 1;
-                }
+        if let Some(file_path) = &self.file_to_delete
+            && file_path.exists()
+        {
+            fs::remove_file(file_path)?;
+            // Remove from our cached lists
+            self.file_finder_all_files.retain(|p| p != file_path);
+            self.file_finder_results.retain(|p| p != file_path);
+            // Adjust selection if needed
+            if self.file_finder_selected >= self.file_finder_results.len()
+                && self.file_finder_selected > 0
+            {
+                self.file_finder_selected -= 1;
             }
         }
         self.show_delete_confirmation = false;
@@ -1370,92 +5006,33 @@ impl App {
         }
     }
 
-    fn scroll_file_up(&mut self) {
-        if let Some(tab) = self.tab_manager.get_active_tab_mut() {
-            if tab.scroll_offset > 0 {
-                tab.scroll_offset -= 1;
-            }
-        }
+    fn is_text_file(&self, file: &FileItem) -> bool {
+        !file.is_dir && is_text_path(&file.path)
     }
 
-    fn scroll_file_down(&mut self) {
-        if let Some(tab) = self.tab_manager.get_active_tab_mut() {
-            let total_lines = tab.content.lines().count();
-            let visible_lines = 30;
-            let max_scroll = total_lines.saturating_sub(visible_lines);
-            if tab.scroll_offset < max_scroll {
-                tab.scroll_offset += 1;
+    /// Where a new terminal starts: the listed directory, or with
+    /// `terminal_in_selection` the selected directory (a file's parent).
+    fn terminal_cwd(&self) -> PathBuf {
+        if self.config.terminal_in_selection
+            && !self.editor_focused()
+            && let Some(file) = self.files.get(self.selected_index)
+        {
+            if file.is_dir {
+                return file.path.clone();
             }
-        }
-    }
-
-    fn is_text_file(&self, file: &FileItem) -> bool {
-        if file.is_dir {
-            return false;
-        }
-
-        if let Some(ext) = file.path.extension() {
-            if let Some(ext_str) = ext.to_str() {
-                matches!(
-                    ext_str.to_lowercase().as_str(),
-                    "txt"
-                        | "md"
-                        | "rs"
-                        | "py"
-                        | "js"
-                        | "ts"
-                        | "html"
-                        | "css"
-                        | "json"
-                        | "xml"
-                        | "yaml"
-                        | "yml"
-                        | "toml"
-                        | "cfg"
-                        | "conf"
-                        | "log"
-                        | "sh"
-                        | "bash"
-                        | "zsh"
-                        | "fish"
-                        | "c"
-                        | "cpp"
-                        | "h"
-                        | "hpp"
-                        | "java"
-                        | "go"
-                        | "php"
-                        | "rb"
-                        | "pl"
-                        | "lua"
-                        | "vim"
-                        | "sql"
-                        | "csv"
-                )
-            } else {
-                false
+            if let Some(parent) = file.path.parent() {
+                return parent.to_path_buf();
             }
-        } else {
-            // Check if filename suggests it's a text file
-            let name = file.name.to_lowercase();
-            matches!(
-                name.as_str(),
-                "readme"
-                    | "license"
-                    | "changelog"
-                    | "makefile"
-                    | "dockerfile"
-                    | "gitignore"
-                    | "gitattributes"
-                    | "editorconfig"
-            )
         }
+        self.current_path.clone()
     }
 
     fn toggle_terminal(&mut self) -> AppResult<()> {
         if self.show_terminal {
             // Close terminal
             self.show_terminal = false;
+            self.terminal_maximized = false;
+            self.terminal_scroll = 0;
 
             // Clean up PTY resources
             if let Some(pty) = self.terminal_pty.take() {
@@ -1465,6 +5042,7 @@ impl App {
                     let _ = writer.flush();
                 }
             }
+            self.terminal_child = None;
             self.terminal_receiver = None;
 
             // Clear terminal state
@@ -1534,10 +5112,10 @@ impl App {
             }
         });
         let mut cmd = CommandBuilder::new(&shell);
-        cmd.cwd(&self.current_path);
+        cmd.cwd(self.terminal_cwd());
 
         let pty_pair = pty_system.openpty(pty_size)?;
-        let _child = pty_pair.slave.spawn_command(cmd)?;
+        let child = pty_pair.slave.spawn_command(cmd)?;
 
         // Setup reader thread with proper error handling
         let reader = pty_pair.master.try_clone_reader()?;
@@ -1558,12 +5136,7 @@ impl App {
                         let text = String::from_utf8_lossy(&buffer[..n]);
                         if let Ok(mut output) = terminal_output.lock() {
                             output.push_str(&text);
-                            // Keep only last 1000 characters to prevent memory issues
-                            if output.len() > 5000 {
-                                let truncated =
-                                    output.chars().skip(output.len() - 1000).collect::<String>();
-                                *output = format!("...[truncated]...\n{}", truncated);
-                            }
+                            tail::trim_front(&mut output, TERMINAL_SCROLLBACK_BYTES);
                         }
                         let _ = sender.send(text.to_string());
                     }
@@ -1580,6 +5153,7 @@ impl App {
         });
 
         self.terminal_pty = Some(pty_pair.master);
+        self.terminal_child = Some(child);
         self.terminal_receiver = Some(receiver);
 
         Ok(())
@@ -1615,6 +5189,20 @@ impl App {
         Ok(())
     }
 
+    /// Interrupts (or quits) the terminal's foreground command with a real
+    /// signal, falling back to the control byte without one.
+    fn signal_terminal(&mut self, signal: signals::TerminalSignal) -> AppResult<()> {
+        let delivered = self.terminal_pty.as_deref().is_some_and(|pty| {
+            let shell_pid = self.terminal_child.as_ref().and_then(|c| c.process_id());
+            signals::send(pty, shell_pid, signal)
+        });
+        if !delivered {
+            self.send_to_terminal(signal.control_byte())?;
+        }
+        self.terminal_input.clear();
+        Ok(())
+    }
+
     fn handle_terminal_input(&mut self, ch: char) -> AppResult<()> {
         match ch {
             '\r' | '\n' => {
@@ -1629,21 +5217,16 @@ impl App {
 
                 self.terminal_input.clear();
             }
-            '\u{8}' | '\u{7f}' => {
+            '\u{8}' | '\u{7f}'
                 // Backspace
-                if !self.terminal_input.is_empty() {
+                if !self.terminal_input.is_empty() => {
                     self.terminal_input.pop();
                     // Only send backspace to PTY if we have one
                     if self.terminal_pty.is_some() {
                         let _ = self.send_to_terminal("\u{8} \u{8}");
                     }
                 }
-            }
-            '\u{3}' => {
-                // Ctrl+C - send interrupt signal
-                self.send_to_terminal("\u{3}")?;
-                self.terminal_input.clear();
-            }
+            '\u{3}' => self.signal_terminal(signals::TerminalSignal::Interrupt)?,
             '\u{4}' => {
                 // Ctrl+D - send EOF
                 self.send_to_terminal("\u{4}")?;
@@ -1707,7 +5290,7 @@ impl App {
         }
     }
 
-    async fn open_file_with_lsp(&mut self, path: &PathBuf) -> AppResult<()> {
+    async fn open_file_with_lsp(&mut self, path: &Path) -> AppResult<()> {
         if LspClient::is_go_file(path) {
             self.start_lsp_for_go().await?;
 
@@ -1723,112 +5306,96 @@ impl App {
     }
 
     async fn update_file_with_lsp(&mut self) -> AppResult<()> {
-        if let Some(tab) = self.tab_manager.get_active_tab_mut() {
-            if LspClient::is_go_file(&tab.path) {
-                if let Some(ref mut lsp) = self.lsp_client {
-                    let uri = format!("file://{}", tab.path.to_string_lossy());
-                    tab.file_version += 1;
-                    lsp.did_change(&uri, tab.file_version, &tab.content).await?;
-                }
-            }
+        if let Some(tab) = self.tab_manager.get_active_tab_mut()
+            && LspClient::is_go_file(&tab.path)
+            && let Some(ref mut lsp) = self.lsp_client
+        {
+            let uri = format!("file://{}", tab.path.to_string_lossy());
+            tab.file_version += 1;
+            lsp.did_change(&uri, tab.file_version, &tab.content).await?;
         }
         Ok(())
     }
 
     async fn request_completions(&mut self) -> AppResult<()> {
-        if let Some(tab) = self.tab_manager.get_active_tab() {
-            if LspClient::is_go_file(&tab.path) {
-                if let Some(ref mut lsp) = self.lsp_client {
-                    let uri = format!("file://{}", tab.path.to_string_lossy());
-                    lsp.completion(&uri, tab.cursor_line as u32, tab.cursor_col as u32)
-                        .await?;
-
-                    // In a real implementation, you'd need to handle the LSP response
-                    // For now, we'll add some context-aware mock completions
-                    let lines: Vec<&str> = tab.content.lines().collect();
-                    let current_line = if tab.cursor_line < lines.len() {
-                        lines[tab.cursor_line]
-                    } else {
-                        ""
-                    };
+        if let Some(tab) = self.tab_manager.get_active_tab()
+            && LspClient::is_go_file(&tab.path)
+            && let Some(ref mut lsp) = self.lsp_client
+        {
+            let uri = format!("file://{}", tab.path.to_string_lossy());
+            lsp.completion(&uri, tab.cursor_line as u32, tab.cursor_col as u32)
+                .await?;
 
-                    let prefix = &current_line[..tab.cursor_col.min(current_line.len())];
-
-                    if let Ok(mut completions) = lsp.completions.lock() {
-                        completions.clear();
-
-                        // Context-specific completions
-                        if prefix.ends_with("fmt.") {
-                            completions.push(CompletionCandidate {
-                                label: "Println".to_string(),
-                                detail: Some(
-                                    "func(a ...interface{}) (n int, err error)".to_string(),
-                                ),
-                                kind: Some("Function".to_string()),
-                                insert_text: Some("Println(".to_string()),
-                            });
-                            completions.push(CompletionCandidate {
-                                label: "Printf".to_string(),
-                                detail: Some(
-                                    "func(format string, a ...interface{}) (n int, err error)"
-                                        .to_string(),
-                                ),
-                                kind: Some("Function".to_string()),
-                                insert_text: Some("Printf(".to_string()),
-                            });
-                            completions.push(CompletionCandidate {
-                                label: "Sprintf".to_string(),
-                                detail: Some(
-                                    "func(format string, a ...interface{}) string".to_string(),
-                                ),
-                                kind: Some("Function".to_string()),
-                                insert_text: Some("Sprintf(".to_string()),
-                            });
-                        } else if prefix.ends_with("strings.") {
-                            completions.push(CompletionCandidate {
-                                label: "ToLower".to_string(),
-                                detail: Some("func(s string) string".to_string()),
-                                kind: Some("Function".to_string()),
-                                insert_text: Some("ToLower(".to_string()),
-                            });
-                            completions.push(CompletionCandidate {
-                                label: "ToUpper".to_string(),
-                                detail: Some("func(s string) string".to_string()),
-                                kind: Some("Function".to_string()),
-                                insert_text: Some("ToUpper(".to_string()),
-                            });
-                            completions.push(CompletionCandidate {
-                                label: "Contains".to_string(),
-                                detail: Some("func(s, substr string) bool".to_string()),
-                                kind: Some("Function".to_string()),
-                                insert_text: Some("Contains(".to_string()),
-                            });
-                        } else {
-                            // General Go keywords and common patterns
-                            completions.push(CompletionCandidate {
-                                label: "func".to_string(),
-                                detail: Some("Function declaration".to_string()),
-                                kind: Some("Keyword".to_string()),
-                                insert_text: Some("func ".to_string()),
-                            });
-                            completions.push(CompletionCandidate {
-                                label: "if".to_string(),
-                                detail: Some("Conditional statement".to_string()),
-                                kind: Some("Keyword".to_string()),
-                                insert_text: Some("if ".to_string()),
-                            });
-                            completions.push(CompletionCandidate {
-                                label: "for".to_string(),
-                                detail: Some("Loop statement".to_string()),
-                                kind: Some("Keyword".to_string()),
-                                insert_text: Some("for ".to_string()),
-                            });
-                        }
-                    }
+            // In a real implementation, you'd need to handle the LSP response
+            // For now, we'll add some context-aware mock completions
+            let lines: Vec<&str> = tab.content.lines().collect();
+            let current_line = if tab.cursor_line < lines.len() {
+                lines[tab.cursor_line]
+            } else {
+                ""
+            };
+
+            let prefix: String = current_line.chars().take(tab.cursor_col).collect();
 
-                    self.completions = lsp.completions.lock().unwrap().clone();
+            if let Ok(mut completions) = lsp.completions.lock() {
+                completions.clear();
+
+                // Context-specific completions
+                if prefix.ends_with("fmt.") {
+                    completions.push(CompletionCandidate {
+                        label: "Println".to_string(),
+                        detail: Some("func(a ...interface{}) (n int, err error)".to_string()),
+                        insert_text: Some("Println(".to_string()),
+                    });
+                    completions.push(CompletionCandidate {
+                        label: "Printf".to_string(),
+                        detail: Some(
+                            "func(format string, a ...interface{}) (n int, err error)".to_string(),
+                        ),
+                        insert_text: Some("Printf(".to_string()),
+                    });
+                    completions.push(CompletionCandidate {
+                        label: "Sprintf".to_string(),
+                        detail: Some("func(format string, a ...interface{}) string".to_string()),
+                        insert_text: Some("Sprintf(".to_string()),
+                    });
+                } else if prefix.ends_with("strings.") {
+                    completions.push(CompletionCandidate {
+                        label: "ToLower".to_string(),
+                        detail: Some("func(s string) string".to_string()),
+                        insert_text: Some("ToLower(".to_string()),
+                    });
+                    completions.push(CompletionCandidate {
+                        label: "ToUpper".to_string(),
+                        detail: Some("func(s string) string".to_string()),
+                        insert_text: Some("ToUpper(".to_string()),
+                    });
+                    completions.push(CompletionCandidate {
+                        label: "Contains".to_string(),
+                        detail: Some("func(s, substr string) bool".to_string()),
+                        insert_text: Some("Contains(".to_string()),
+                    });
+                } else {
+                    // General Go keywords and common patterns
+                    completions.push(CompletionCandidate {
+                        label: "func".to_string(),
+                        detail: Some("Function declaration".to_string()),
+                        insert_text: Some("func ".to_string()),
+                    });
+                    completions.push(CompletionCandidate {
+                        label: "if".to_string(),
+                        detail: Some("Conditional statement".to_string()),
+                        insert_text: Some("if ".to_string()),
+                    });
+                    completions.push(CompletionCandidate {
+                        label: "for".to_string(),
+                        detail: Some("Loop statement".to_string()),
+                        insert_text: Some("for ".to_string()),
+                    });
                 }
             }
+
+            self.completions = lsp.completions.lock().unwrap().clone();
         }
         Ok(())
     }
@@ -1862,8 +5429,8 @@ impl App {
             let lines: Vec<&str> = self.file_content.lines().collect();
             if self.cursor_line < lines.len() {
                 let current_line = lines[self.cursor_line];
-                let before_cursor = &current_line[..self.cursor_col.min(current_line.len())];
-                let after_cursor = &current_line[self.cursor_col.min(current_line.len())..];
+                let before_cursor: String = current_line.chars().take(self.cursor_col).collect();
+                let after_cursor: String = current_line.chars().skip(self.cursor_col).collect();
 
                 let new_line = format!("{}{}{}", before_cursor, insert_text, after_cursor);
 
@@ -1871,7 +5438,7 @@ impl App {
                 new_lines[self.cursor_line] = &new_line;
                 self.file_content = new_lines.join("\n");
 
-                self.cursor_col += insert_text.len();
+                self.cursor_col += insert_text.chars().count();
                 self.file_has_unsaved_changes = true;
 
                 // Update LSP with changes
@@ -1891,19 +5458,19 @@ impl App {
         }
 
         // Only trigger autocomplete if LSP is ready and we're in a Go file
-        if let Some(tab) = self.tab_manager.get_active_tab() {
-            if LspClient::is_go_file(&tab.path) {
-                if let Some(ref lsp) = self.lsp_client {
-                    if lsp.status == LspStatus::Running {
-                        // Check if cursor is after a potential completion trigger
-                        let lines: Vec<&str> = tab.content.lines().collect();
-                        if tab.cursor_line < lines.len() {
-                            let current_line = lines[tab.cursor_line];
-                            let before_cursor =
-                                &current_line[..tab.cursor_col.min(current_line.len())];
-
-                            // Check for various completion triggers
-                            let should_trigger =
+        if let Some(tab) = self.tab_manager.get_active_tab()
+            && LspClient::is_go_file(&tab.path)
+            && let Some(ref lsp) = self.lsp_client
+            && lsp.status == LspStatus::Running
+        {
+            // Check if cursor is after a potential completion trigger
+            let lines: Vec<&str> = tab.content.lines().collect();
+            if tab.cursor_line < lines.len() {
+                let current_line = lines[tab.cursor_line];
+                let before_cursor: String = current_line.chars().take(tab.cursor_col).collect();
+
+                // Check for various completion triggers
+                let should_trigger =
                                 // After a dot (package.function)
                                 before_cursor.ends_with('.') ||
                                 // After typing at least 2 characters of an identifier
@@ -1912,14 +5479,11 @@ impl App {
                                 // Inside function call context
                                 (before_cursor.contains('(') && !before_cursor.contains(')'));
 
-                            if should_trigger {
-                                self.last_completion_trigger = now;
-                                self.request_completions().await?;
-                                if !self.completions.is_empty() {
-                                    self.show_autocomplete();
-                                }
-                            }
-                        }
+                if should_trigger {
+                    self.last_completion_trigger = now;
+                    self.request_completions().await?;
+                    if !self.completions.is_empty() {
+                        self.show_autocomplete();
                     }
                 }
             }
@@ -1928,6 +5492,17 @@ impl App {
     }
 }
 
+#[cfg(unix)]
+fn inode_and_links(metadata: &Metadata) -> (u64, u64) {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.ino(), metadata.nlink())
+}
+
+#[cfg(not(unix))]
+fn inode_and_links(_metadata: &Metadata) -> (u64, u64) {
+    (0, 0)
+}
+
 fn format_permissions(metadata: &Metadata) -> String {
     #[cfg(unix)]
     {
@@ -1968,6 +5543,7 @@ fn format_permissions(metadata: &Metadata) -> String {
 
 fn ui(f: &mut Frame, app: &mut App) {
     let size = f.size();
+    app.file_list_area = None;
 
     // Create main layout - adjust based on whether tabs are open and terminal visibility
     let chunks = if app.tab_manager.has_tabs() {
@@ -2007,241 +5583,210 @@ fn ui(f: &mut Frame, app: &mut App) {
         } else {
             Layout::default()
                 .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(3), // Header
-                    Constraint::Min(0),    // File list
-                    Constraint::Length(3), // Footer
-                ])
-                .split(size)
-        }
-    };
-
-    // Header with LSP status for Go files
-    let header_text = if app.tab_manager.has_tabs() {
-        if let Some(tab) = app.tab_manager.get_active_tab() {
-            if LspClient::is_go_file(&tab.path) {
-                let lsp_indicator = if let Some(ref lsp) = app.lsp_client {
-                    match lsp.status {
-                        LspStatus::Running => "🟢 LSP",
-                        LspStatus::Starting => "🟡 LSP",
-                        LspStatus::Failed(_) => "🔴 LSP",
-                        _ => "⚪ LSP",
-                    }
-                } else {
-                    "⚪ LSP"
-                };
-                format!(
-                    "📁 {} | 🐹 Go {} Ready | {}",
-                    app.current_path.display(),
-                    lsp_indicator,
-                    app.tab_manager.get_tabs_info()
-                )
-            } else {
-                format!(
-                    "📁 {} | {}",
-                    app.current_path.display(),
-                    app.tab_manager.get_tabs_info()
-                )
-            }
-        } else {
-            format!("📁 {}", app.current_path.display())
-        }
-    } else {
-        format!("📁 {}", app.current_path.display())
-    };
-
-    let header = Paragraph::new(header_text)
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::Cyan));
-    f.render_widget(header, chunks[0]);
-
-    if app.tab_manager.has_tabs() {
-        // Render tabs
-        app.tab_manager.render_tabs(f, chunks[1]);
-
-        // Render active tab content
-        if let Some(tab) = app.tab_manager.get_active_tab() {
-            let content_area = chunks[2];
-            let content_lines: Vec<&str> = tab.content.lines().collect();
-            let total_lines = content_lines.len();
-            let max_visible = (content_area.height as usize).saturating_sub(2); // Account for borders
-
-            // Calculate visible lines
-            let visible_lines = content_lines
-                .iter()
-                .skip(tab.scroll_offset)
-                .take(max_visible);
-
-            // Prepare syntax highlighting
-            let syntax = app
-                .syntax_set
-                .find_syntax_for_file(&tab.path)
-                .ok()
-                .flatten()
-                .unwrap_or_else(|| app.syntax_set.find_syntax_plain_text());
-
-            let theme = &app.theme_set.themes["base16-ocean.dark"];
-            let mut highlighter = HighlightLines::new(syntax, theme);
-
-            let mut lines: Vec<Line> = Vec::new();
-            let line_number_width = total_lines.to_string().len().max(3);
-
-            for (line_idx, line_text) in visible_lines.enumerate() {
-                let actual_line_idx = line_idx + tab.scroll_offset;
-                let line_number = actual_line_idx + 1;
-
-                // Create line number span
-                let line_num_str = format!("{:width$} ", line_number, width = line_number_width);
-                let line_num_span =
-                    Span::styled(line_num_str, Style::default().fg(Color::DarkGray));
-
-                let mut spans = vec![line_num_span];
-
-                if actual_line_idx == tab.cursor_line {
-                    // This line contains the cursor - highlight background
-                    match highlighter.highlight_line(line_text, &app.syntax_set) {
-                        Ok(highlighted) => {
-                            let line_chars: Vec<char> = line_text.chars().collect();
-                            let mut char_idx = 0;
-
-                            for (style, text) in highlighted {
-                                let fg_color = style.foreground;
-                                let color = Color::Rgb(fg_color.r, fg_color.g, fg_color.b);
-                                let mut modifier = Modifier::empty();
-                                if style
-                                    .font_style
-                                    .contains(syntect::highlighting::FontStyle::BOLD)
-                                {
-                                    modifier |= Modifier::BOLD;
-                                }
-
-                                for ch in text.chars() {
-                                    if char_idx == tab.cursor_col && app.cursor_blink_state {
-                                        // Insert cursor before this character
-                                        spans.push(Span::styled(
-                                            "█",
-                                            Style::default().fg(Color::White).bg(Color::DarkGray),
-                                        ));
-                                    }
-
-                                    spans.push(Span::styled(
-                                        ch.to_string(),
-                                        Style::default()
-                                            .fg(color)
-                                            .add_modifier(modifier)
-                                            .bg(Color::DarkGray),
-                                    ));
-                                    char_idx += 1;
-                                }
-                            }
+                .constraints([
+                    Constraint::Length(3), // Header
+                    Constraint::Min(0),    // File list
+                    Constraint::Length(3), // Footer
+                ])
+                .split(size)
+        }
+    };
 
-                            // If cursor is at end of line
-                            if tab.cursor_col >= line_chars.len() && app.cursor_blink_state {
-                                spans.push(Span::styled(
-                                    "█",
-                                    Style::default().fg(Color::White).bg(Color::DarkGray),
-                                ));
-                            }
-                        }
-                        Err(_) => {
-                            spans.push(Span::styled(
-                                *line_text,
-                                Style::default().bg(Color::DarkGray),
-                            ));
-                        }
+    // Header with LSP status for Go files
+    let header_text = if app.tab_manager.has_tabs() {
+        if let Some(tab) = app.tab_manager.get_active_tab() {
+            if LspClient::is_go_file(&tab.path) {
+                let lsp_indicator = if let Some(ref lsp) = app.lsp_client {
+                    match lsp.status {
+                        LspStatus::Running => "🟢 LSP",
+                        LspStatus::Starting => "🟡 LSP",
+                        LspStatus::Failed(_) => "🔴 LSP",
+                        _ => "⚪ LSP",
                     }
                 } else {
-                    // Regular line with syntax highlighting
-                    match highlighter.highlight_line(line_text, &app.syntax_set) {
-                        Ok(highlighted) => {
-                            for (style, text) in highlighted {
-                                let fg_color = style.foreground;
-                                let color = Color::Rgb(fg_color.r, fg_color.g, fg_color.b);
-                                let mut modifier = Modifier::empty();
-                                if style
-                                    .font_style
-                                    .contains(syntect::highlighting::FontStyle::BOLD)
-                                {
-                                    modifier |= Modifier::BOLD;
-                                }
-                                spans.push(Span::styled(
-                                    text,
-                                    Style::default().fg(color).add_modifier(modifier),
-                                ));
-                            }
-                        }
-                        Err(_) => {
-                            spans.push(Span::raw(*line_text));
-                        }
-                    }
-                }
-
-                lines.push(Line::from(spans));
-            }
-
-            let edit_title = if tab.has_unsaved_changes {
-                format!(" {} (EDITING - UNSAVED) ", tab.name)
+                    "⚪ LSP"
+                };
+                format!(
+                    "📁 {} | 🐹 Go {} Ready | {}",
+                    app.display_path(&app.current_path).display(),
+                    lsp_indicator,
+                    app.tab_manager.get_tabs_info()
+                )
             } else {
-                format!(" {} (EDITING) ", tab.name)
-            };
-
-            let content_paragraph = Paragraph::new(lines)
-                .block(
-                    Block::default()
-                        .title(edit_title)
-                        .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::Green)),
+                format!(
+                    "📁 {} | {}",
+                    app.display_path(&app.current_path).display(),
+                    app.tab_manager.get_tabs_info()
                 )
-                .wrap(Wrap { trim: false });
+            }
+        } else {
+            format!("📁 {}", app.display_path(&app.current_path).display())
+        }
+    } else {
+        format!("📁 {}", app.display_path(&app.current_path).display())
+    };
 
-            f.render_widget(content_paragraph, content_area);
+    let header_text = if app.marked_paths.is_empty() {
+        header_text
+    } else {
+        format!(
+            "{} | ✔ {} selected, {}{}",
+            header_text,
+            app.marked_paths.len(),
+            size::format_size(app.marked_size, true, app.size_units),
+            match app.marked_dirs {
+                0 => "",
+                1 => " in files (folder contents not counted)",
+                _ => " in files (folders' contents not counted)",
+            }
+        )
+    };
+    let header_text = match app.newer_than {
+        Some(window) => format!(
+            "{} | 🕒 modified in last {}",
+            header_text,
+            filter::format_duration(window)
+        ),
+        None => header_text,
+    };
+    let header_text = if app.name_filter.is_empty() {
+        header_text
+    } else {
+        format!("{} | 🔎 \"{}\"", header_text, app.name_filter)
+    };
+    let header_text = match &app.descended {
+        Some((dir, chain)) if *dir == app.current_path => {
+            format!("{} | ⤵ {}", header_text, chain.display())
         }
+        _ => header_text,
+    };
+    let header_text = if app.flat_mode {
+        let scanning = if app.flat_receiver.is_some() {
+            ", scanning…"
+        } else {
+            ""
+        };
+        format!(
+            "{} | 🗂 flat: {} files{}",
+            header_text,
+            app.files.iter().filter(|file| !file.is_dir).count(),
+            scanning
+        )
     } else {
-        // File list (when no tabs are open)
-        let items: Vec<ListItem> = app
-            .files
-            .iter()
-            .map(|file| {
-                let icon = file.get_icon();
-                let size_str = FileItem::format_size(file.size, app.human_readable);
-                let date_str = file.format_date();
-
-                let style = if file.is_dir {
-                    Style::default().fg(Color::Blue)
-                } else if app.is_text_file(file) {
-                    Style::default().fg(Color::Green)
-                } else {
-                    Style::default().fg(Color::White)
-                };
+        header_text
+    };
+    let pinned: Vec<String> = (1..)
+        .zip(&app.pinned_dirs)
+        .filter(|(_, dir)| dir.is_some())
+        .map(|(slot, _)| slot.to_string())
+        .collect();
+    let header_text = if pinned.is_empty() {
+        header_text
+    } else {
+        format!("{} | 📌 {}", header_text, pinned.join(" "))
+    };
+    let header_text = match app.hidden_summary_label() {
+        Some(summary) => format!("{} | {}", header_text, summary),
+        None => header_text,
+    };
+    let header_text = match &app.git_summary {
+        Some(summary) => format!("{} | {}", header_text, summary.header_label()),
+        None => header_text,
+    };
+    let header_text = format!("{} | {}", header_text, app.total_size_label());
+    let header = Paragraph::new(header_text)
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Cyan));
+    f.render_widget(header, chunks[0]);
 
-                let content = format!(
-                    "{} {:30} {:>10} {} {}",
-                    icon, file.name, size_str, file.permissions, date_str
-                );
-                ListItem::new(content).style(style)
+    if app.tab_manager.has_tabs() {
+        // The listing pane takes the left third, next to the tab bar and file
+        let (tab_bar, content_area) = if app.show_file_pane {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .split(chunks[1].union(chunks[2]));
+            let border = if app.browser_focused {
+                Color::Cyan
+            } else {
+                Color::DarkGray
+            };
+            render_file_list(f, app, columns[0], Style::default().fg(border));
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Min(0)])
+                .split(columns[1]);
+            (rows[0], rows[1])
+        } else {
+            (chunks[1], chunks[2])
+        };
+
+        // Render tabs
+        app.tab_manager.render_tabs(f, tab_bar);
+
+        // Render the active tab, or both sides of a split
+        let active = app.tab_manager.get_active_tab_index();
+        let panes = match app.tab_manager.split_panes() {
+            Some((left, right)) => {
+                let halves = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(content_area);
+                vec![(left, halves[0]), (right, halves[1])]
+            }
+            None => vec![(active, content_area)],
+        };
+        let panes: Vec<(usize, Rect, Option<Rect>)> = panes
+            .into_iter()
+            .map(|(index, area)| {
+                let (area, minimap) = app
+                    .tab_manager
+                    .get_tab(index)
+                    .map_or((area, None), |tab| minimap_split(app, tab, area));
+                (index, area, minimap)
             })
             .collect();
+        app.minimap_hits = panes
+            .iter()
+            .filter_map(|(index, _, minimap)| minimap.map(|area| (area, *index)))
+            .collect();
+        let content_area = panes
+            .iter()
+            .find(|(index, _, _)| *index == active)
+            .map_or(content_area, |(_, area, _)| *area);
+        let max_visible = (content_area.height as usize).saturating_sub(2); // Account for borders
+        app.content_viewport_height = max_visible;
+
+        // Unwrapped editing scrolls sideways to keep the cursor in view
+        if !app.editor_wrap
+            && let Some(tab) = app.tab_manager.get_active_tab_mut()
+            && !tab.read_only
+            && !tab.markdown_preview
+        {
+            let gutter = tab.content.lines().count().to_string().len().max(3) + 1;
+            let text_width = (content_area.width as usize)
+                .saturating_sub(2 + gutter + 1)
+                .max(1);
+            let cursor_line = tab.content.lines().nth(tab.cursor_line).unwrap_or("");
+            let cursor = editor::display_column(cursor_line, tab.cursor_col, app.config.tab_width);
+            if cursor < tab.horizontal_scroll {
+                tab.horizontal_scroll = cursor;
+            } else if cursor >= tab.horizontal_scroll + text_width {
+                tab.horizontal_scroll = cursor + 1 - text_width;
+            }
+        }
 
-        let files_list = List::new(items)
-            .block(Block::default().borders(Borders::ALL))
-            .highlight_style(Style::default().bg(Color::Yellow).fg(Color::Black))
-            .highlight_symbol("➤ ");
-
-        f.render_stateful_widget(files_list, chunks[1], &mut app.list_state);
-
-        // Scrollbar
-        let scrollbar = Scrollbar::default()
-            .orientation(ScrollbarOrientation::VerticalRight)
-            .begin_symbol(Some("↑"))
-            .end_symbol(Some("↓"));
-        f.render_stateful_widget(
-            scrollbar,
-            chunks[1].inner(&Margin {
-                vertical: 1,
-                horizontal: 1,
-            }),
-            &mut app.scroll_state,
-        );
+        for (index, area, minimap) in panes {
+            if let Some(tab) = app.tab_manager.get_tab(index) {
+                render_tab_pane(f, app, tab, area, index == active && !app.browser_focused);
+                if let Some(minimap) = minimap {
+                    render_minimap(f, app, tab, minimap);
+                }
+            }
+        }
+    } else {
+        app.minimap_hits.clear();
+        render_file_list(f, app, chunks[1], Style::default());
     }
 
     // Terminal (if enabled, show in its own section)
@@ -2253,14 +5798,27 @@ fn ui(f: &mut Frame, app: &mut App) {
             "Terminal output unavailable".to_string()
         };
 
-        // Show last 10 lines for bottom terminal (increased from 8)
-        let lines: Vec<&str> = terminal_content.lines().collect();
-        let visible_lines = if lines.len() > 10 {
-            &lines[lines.len() - 10..]
+        let terminal_chunk = if app.terminal_maximized {
+            // Everything between the header and the footer
+            Rect {
+                y: 3,
+                height: size.height.saturating_sub(6),
+                ..size
+            }
+        } else if app.tab_manager.has_tabs() {
+            chunks[3]
         } else {
-            &lines[..]
+            chunks[2]
         };
 
+        // Whatever fits above the input line, ending where it's scrolled to
+        let lines: Vec<&str> = terminal_content.lines().collect();
+        let rows = (terminal_chunk.height as usize).saturating_sub(3).max(1);
+        app.terminal_page_height = rows;
+        app.terminal_scroll = app.terminal_scroll.min(lines.len().saturating_sub(rows));
+        let end = lines.len() - app.terminal_scroll;
+        let visible_lines = &lines[end.saturating_sub(rows)..end];
+
         let mut terminal_lines: Vec<Line> = visible_lines
             .iter()
             .map(|&line| {
@@ -2280,7 +5838,7 @@ fn ui(f: &mut Frame, app: &mut App) {
             .collect();
 
         // Add current input line with cursor indicator
-        let cursor_indicator = if terminal_lines.len() % 2 == 0 {
+        let cursor_indicator = if terminal_lines.len().is_multiple_of(2) {
             "█"
         } else {
             " "
@@ -2291,10 +5849,19 @@ fn ui(f: &mut Frame, app: &mut App) {
             Style::default().fg(Color::Green),
         )));
 
-        let terminal_title = if app.terminal_pty.is_some() {
-            "Terminal (Ctrl+T to close, Ctrl+C to interrupt)"
+        let terminal_title = if app.terminal_maximized {
+            if app.terminal_scroll > 0 {
+                format!(
+                    "Terminal - {} lines back (↑↓ PgUp/PgDn scroll, End newest, Esc restore)",
+                    app.terminal_scroll
+                )
+            } else {
+                "Terminal (↑↓ PgUp/PgDn scroll back, Alt+M or Esc to restore)".to_string()
+            }
+        } else if app.terminal_pty.is_some() {
+            "Terminal (Ctrl+T to close, Ctrl+C to interrupt, Alt+M to maximize)".to_string()
         } else {
-            "Terminal - Fallback Mode (Ctrl+T to close)"
+            "Terminal - Fallback Mode (Ctrl+T to close)".to_string()
         };
 
         let terminal_paragraph = Paragraph::new(terminal_lines)
@@ -2302,22 +5869,26 @@ fn ui(f: &mut Frame, app: &mut App) {
             .wrap(Wrap { trim: false })
             .style(Style::default().fg(Color::White));
 
-        let terminal_chunk = if app.tab_manager.has_tabs() {
-            chunks[3]
-        } else {
-            chunks[2]
-        };
+        if app.terminal_maximized {
+            f.render_widget(Clear, terminal_chunk);
+        }
         f.render_widget(terminal_paragraph, terminal_chunk);
     }
 
     // Footer
-    let footer_text = if app.show_help {
-        "Help: ↑↓/jk=Navigate  Enter=Open  a=Toggle hidden  h=Help  Ctrl+T=Terminal  Ctrl+P=Command Palette  q/Esc=Quit  Ctrl+Q=Force quit"
+    let hints = if app.show_help {
+        "Help: ↑↓/jk=Scroll  PgUp/PgDn=Page  Home/End=Top/bottom  h/q/Esc=Close help  Ctrl+Q=Force quit"
     } else if app.show_terminal {
         "Terminal active - Type commands and press Enter  |  Ctrl+T to close  |  Esc to quit  |  Ctrl+Q force quit"
-    } else if app.tab_manager.has_tabs() {
+    } else if app.browser_focused && app.tab_manager.has_tabs() {
+        "FILES - ↑↓ Navigate  Enter Open in a new tab  Ctrl+B Back to the file  Esc Hide this pane"
+    } else if app.editor_focused() {
         if let Some(tab) = app.tab_manager.get_active_tab() {
-            if LspClient::is_go_file(&tab.path) {
+            if tab.markdown_preview {
+                "PREVIEW - Space/b page | g/G top/bottom | Ctrl+R show source | q close"
+            } else if tab.read_only {
+                "VIEW - Space/b page | g/G top/bottom | / search | n/N next/prev | Ctrl+E edit | q close"
+            } else if LspClient::is_go_file(&tab.path) {
                 if app.show_lsp_status {
                     &app.lsp_status_message
                 } else if app.lsp_client.is_some() {
@@ -2348,9 +5919,158 @@ fn ui(f: &mut Frame, app: &mut App) {
     } else {
         "Press 'h' for help  |  ↑↓ Navigate  Enter Open  Ctrl+O File Finder  Ctrl+P Command Palette  Ctrl+T Terminal  Esc Quit  Ctrl+Q Force quit"
     };
-    let footer = Paragraph::new(footer_text)
+    let footer_text = footer::render(&app.config.footer_format, |name| {
+        footer_value(app, name, hints)
+    });
+    let encoding_span = match app.tab_manager.get_active_tab() {
+        Some(tab) if !app.show_help && !app.show_terminal => Span::styled(
+            format!(" | {}", tab.encoding_label()),
+            Style::default().fg(if tab.lossy { Color::Red } else { Color::Cyan }),
+        ),
+        _ => Span::raw(""),
+    };
+    let line_ending_span = match app.tab_manager.get_active_tab() {
+        Some(tab) if !app.show_help && !app.show_terminal => {
+            match editor::line_ending_label(&tab.content) {
+                // Enter adds a bare LF, so a CRLF file turns mixed as it's edited
+                Some(label) => Span::styled(
+                    format!(" {}", label),
+                    Style::default().fg(if label == "Mixed" {
+                        Color::Yellow
+                    } else {
+                        Color::Cyan
+                    }),
+                ),
+                None => Span::raw(""),
+            }
+        }
+        _ => Span::raw(""),
+    };
+    let highlight_span = match app.tab_manager.get_active_tab() {
+        Some(tab) if !app.show_help && !app.show_terminal && app.syntax_highlighting => {
+            if app.highlights(tab.content.len()) {
+                Span::styled(
+                    format!(" | {}", app.current_theme),
+                    Style::default().fg(Color::DarkGray),
+                )
+            } else {
+                Span::styled(
+                    " | no highlighting (large file)",
+                    Style::default().fg(Color::DarkGray),
+                )
+            }
+        }
+        _ => Span::raw(""),
+    };
+    let (footer_line, footer_color) = if let Some(op) = app.overwrite_guard.pending() {
+        (
+            Line::from(format!(
+                "File exists — overwrite {}? [y/n]",
+                op.target().display()
+            )),
+            Color::Red,
+        )
+    } else if let Some(prompt) = &app.prompt {
+        (
+            Line::from(vec![
+                Span::raw(format!("{}: ", prompt.kind.label())),
+                Span::styled(prompt.input.clone(), Style::default().fg(Color::White)),
+                Span::raw("█"),
+                Span::styled(
+                    "  (Enter confirm, Esc cancel)",
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]),
+            Color::Cyan,
+        )
+    } else if app.name_filter_mode && !app.editor_focused() && !app.show_terminal {
+        (
+            Line::from(vec![
+                Span::raw("Filter: "),
+                Span::styled(app.name_filter.clone(), Style::default().fg(Color::White)),
+                Span::raw("█"),
+                Span::styled(
+                    "  (↑↓ move, Enter keep, Esc clear)",
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]),
+            Color::Cyan,
+        )
+    } else if let Some(path) = &app.pending_run {
+        (
+            Line::from(format!("Run {} in the terminal? (y/n)", path.display())),
+            Color::Yellow,
+        )
+    } else if let Some((path, reason)) = &app.pending_open {
+        (
+            Line::from(format!(
+                "Couldn't read text from {}: {}. Open it in the default app? (y/n)",
+                path.display(),
+                reason
+            )),
+            Color::Yellow,
+        )
+    } else if let Some(target) = &app.pending_shortcut {
+        let question = match target {
+            shortcut::Target::Command(command) => {
+                format!("Run the command line shown at the end, `{}`?", command)
+            }
+            shortcut::Target::Path(path) => format!("Go to {}?", path.display()),
+            shortcut::Target::Url(url) => format!("Open {}?", url),
+        };
+        (
+            Line::from(format!("{} (y to follow, any other key cancels)", question)),
+            Color::Yellow,
+        )
+    } else if let Some((transfer, prompt)) = &app.transfer {
+        let text = match prompt {
+            TransferPrompt::Policy(conflicts) => format!(
+                "{} names already taken in {}: (o)verwrite all, (s)kip all, (r)ename all, (a)sk each, Esc cancels",
+                conflicts,
+                transfer.dest.display()
+            ),
+            TransferPrompt::Conflict(target) => format!(
+                "{} exists: (o)verwrite, (s)kip, (r)ename; O/S/R for the rest too, Esc stops",
+                target.display()
+            ),
+        };
+        (Line::from(text), Color::Yellow)
+    } else if let Some(text) = &app.pending_paste {
+        (
+            Line::from(format!(
+                "The clipboard holds {} of text. Save it to a new file? (y/n)",
+                size::format_size(text.len() as u64, true, app.size_units)
+            )),
+            Color::Yellow,
+        )
+    } else if let Some(paths) = &app.pending_delete {
+        (
+            Line::from(match paths.as_slice() {
+                [path] => format!("Delete {}? (y/n)", path.display()),
+                _ => format!("Delete {} marked entries? (y/n)", paths.len()),
+            }),
+            Color::Red,
+        )
+    } else if let Some(toast) = app.notifications.toast() {
+        let color = match toast.level {
+            notifications::Level::Info => Color::Yellow,
+            notifications::Level::Error => Color::Red,
+        };
+        (Line::from(toast.message.clone()), color)
+    } else {
+        (
+            Line::from(vec![
+                Span::raw(footer_text),
+                encoding_span,
+                line_ending_span,
+                highlight_span,
+            ]),
+            Color::Gray,
+        )
+    };
+    let footer = Paragraph::new(footer_line)
         .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::Gray));
+        .style(Style::default().fg(footer_color));
 
     let footer_chunk = if app.show_terminal {
         if app.tab_manager.has_tabs() {
@@ -2381,8 +6101,55 @@ fn ui(f: &mut Frame, app: &mut App) {
             Line::from(""),
             Line::from("Commands:"),
             Line::from("  a       - Toggle hidden files"),
+            Line::from("  A       - Show hidden vs. visible totals in the header"),
+            Line::from("  Space   - Mark/unmark the selected entry"),
+            Line::from("  Ctrl+A  - Mark all (again to unmark), Ctrl+D clears"),
+            Line::from("  *       - Invert the marks in this listing"),
+            Line::from("  Delete  - Delete the marked entries, or the selected one (asks first)"),
+            Line::from("  y       - Copy the selected path (or all marked paths)"),
+            Line::from("  Y       - Copy the path(s) relative to the git root"),
+            Line::from("  R       - Cycle the recently modified filter (1h/1d/1w/off)"),
+            Line::from("  D       - Duplicate the selected file or directory"),
+            Line::from("  p / X   - Copy / move the marked entries into this directory"),
+            Line::from(
+                "  C / K   - Copy / move the selected entry to a typed path (Tab completes)",
+            ),
+            Line::from("  L       - Create a symbolic link here to the one marked entry"),
+            Line::from("  F2      - Rename the selected entry (asks before overwriting)"),
+            Line::from("  F7      - New directory; a/b/c creates the nested path"),
+            Line::from("  F       - Flat view: every file below this directory"),
+            Line::from("  M       - Color dates by age (bright = recently modified)"),
+            Line::from("  G       - Group entries by type under section headers"),
+            Line::from("  I       - Dim, hide or show build and cache directories"),
+            Line::from(
+                "  s / S   - Sort by the next column / reverse the order (or click a title)",
+            ),
+            Line::from("  o       - Directories first, files first, or mixed"),
+            Line::from("  P       - Jump to one of the [places] from the config file"),
+            Line::from("  H       - Checksum the selected file (MD5, SHA-1, SHA-256)"),
+            Line::from("  i       - Show inode numbers and hard-link counts (Unix)"),
+            Line::from("  B       - Show file sizes as bars scaled to the largest file"),
+            Line::from("  m / `   - Mark this directory and row / jump back to the mark"),
+            Line::from("  -       - Swap back to the previous directory, like cd -"),
+            Line::from("  → / ←   - Expand a directory in place / collapse it (tree view)"),
+            Line::from("  z / Z   - Fold the first block in view / unfold all (pager)"),
+            Line::from("  w       - Toggle line wrapping in the pager; ←/→ scroll when off"),
+            Line::from("  T       - Next syntax theme (pager; the footer names it)"),
+            Line::from("  ?       - Show what the filters leave out of the listing, and why"),
+            Line::from("  :       - Go to an entry by its exact name (or its start)"),
+            Line::from("  /       - Filter the listing by name as you type; Esc clears it"),
+            Line::from("  ,       - Edit the config file (applied on save)"),
+            Line::from("  f       - Follow the selected file as it grows (tail -f); F pauses"),
+            Line::from("  U       - Open a copy of the file as an untitled buffer"),
+            Line::from("  V       - Save the clipboard text as a new file and open it"),
+            Line::from("  O       - Show the selection in the system file manager"),
+            Line::from("  x       - Show extended attributes and flags (immutable, ...)"),
+            Line::from("  E       - Export the listing as text, CSV, JSON or Markdown"),
+            Line::from("  1-4     - Pin this directory to a slot, Alt+1-4 jumps back"),
             Line::from("  h       - Toggle this help"),
             Line::from("  Ctrl+T  - Toggle integrated terminal"),
+            Line::from("  Ctrl+G  - Search file contents under this directory"),
+            Line::from("  Ctrl+L  - Show the log of messages and errors"),
             Line::from("  q/Esc   - Quit or close popup"),
             Line::from("  Ctrl+Q  - Force quit (bypasses all dialogs)"),
             Line::from(""),
@@ -2392,18 +6159,30 @@ fn ui(f: &mut Frame, app: &mut App) {
             Line::from("  Ctrl+S to save changes"),
             Line::from("  View mode: ↑↓ to scroll"),
             Line::from("  Edit mode: ↑↓←→ to move cursor"),
-            Line::from("  Edit mode: Type to insert, Tab for 4 spaces"),
+            Line::from("  Edit mode: Type to insert, Tab for spaces (tab_width)"),
             Line::from("  Go files: Ctrl+Space for autocomplete, Tab to accept"),
-            Line::from("  Edit mode: Backspace to delete, Ctrl+Z to revert"),
+            Line::from("  Edit mode: Backspace to delete, Ctrl+Z undo, Ctrl+Y redo"),
             Line::from("  Ctrl+F to search, F3/Shift+F3 for next/prev"),
             Line::from("  Ctrl+O for file finder, Ctrl+D for multi-cursor"),
-            Line::from("  Ctrl+W to close tab, Ctrl+Tab to switch tabs"),
+            Line::from("  Ctrl+W to close tab, Ctrl+Tab or Ctrl+PgDn/PgUp to switch tabs"),
+            Line::from("  Ctrl+R on Markdown files toggles the rendered preview"),
+            Line::from("  Ctrl+] jumps to the bracket matching the one under the cursor"),
+            Line::from("  Alt+W toggles line wrapping while editing"),
+            Line::from("  Alt+H turns syntax highlighting off and on"),
+            Line::from("  Alt+R lists the open file's directory beside it, file selected"),
+            Line::from("  Ctrl+P → Open in Split shows a second file beside this one"),
+            Line::from("  F6 switches between the two sides of a split"),
+            Line::from("  Click the minimap beside a long file to jump there"),
+            Line::from(
+                "  Ctrl+B shows the file list beside open files and moves focus to it and back",
+            ),
             Line::from("  Press Esc to close file view or go back to browser"),
             Line::from(""),
             Line::from("Terminal:"),
             Line::from("  Opens at bottom of screen"),
             Line::from("  Type commands and press Enter"),
             Line::from("  Ctrl+T to close terminal"),
+            Line::from("  Alt+M maximizes it; ↑↓ PgUp/PgDn then scroll back, Esc restores"),
             Line::from(""),
             Line::from("Go Language Server (LSP):"),
             Line::from("  🟢 Green dot = LSP running and ready"),
@@ -2413,15 +6192,90 @@ fn ui(f: &mut Frame, app: &mut App) {
             Line::from("  Ctrl+Space to trigger autocomplete"),
             Line::from("  Tab to accept completion, Esc to close"),
         ];
+
+        // Clamp here so End and over-scrolling settle on the last page
+        app.help_page_height = (popup_area.height as usize).saturating_sub(2);
+        let max_scroll = help_text.len().saturating_sub(app.help_page_height);
+        app.help_scroll = app.help_scroll.min(max_scroll);
+        let help_title = if max_scroll > 0 {
+            format!(
+                " Help ({}-{} of {}) ",
+                app.help_scroll + 1,
+                (app.help_scroll + app.help_page_height).min(help_text.len()),
+                help_text.len()
+            )
+        } else {
+            " Help ".to_string()
+        };
+
         let help_popup = Paragraph::new(help_text)
             .block(
                 Block::default()
-                    .title(" Help ")
+                    .title(help_title)
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(Color::Green)),
             )
-            .wrap(Wrap { trim: false });
+            .wrap(Wrap { trim: false })
+            .scroll((app.help_scroll as u16, 0));
         f.render_widget(help_popup, popup_area);
+
+        if max_scroll > 0 {
+            let mut help_scroll_state = ScrollbarState::default()
+                .content_length(max_scroll + 1)
+                .position(app.help_scroll);
+            f.render_stateful_widget(
+                Scrollbar::default()
+                    .orientation(ScrollbarOrientation::VerticalRight)
+                    .begin_symbol(Some("↑"))
+                    .end_symbol(Some("↓")),
+                popup_area.inner(&Margin {
+                    vertical: 1,
+                    horizontal: 0,
+                }),
+                &mut help_scroll_state,
+            );
+        }
+    }
+
+    if app.show_log {
+        let popup_area = centered_rect(70, 50, size);
+        f.render_widget(Clear, popup_area);
+
+        let log_lines: Vec<Line> = app
+            .notifications
+            .entries()
+            .map(|entry| {
+                let color = match entry.level {
+                    notifications::Level::Info => Color::White,
+                    notifications::Level::Error => Color::Red,
+                };
+                Line::from(vec![
+                    Span::styled(
+                        entry.time.format("%H:%M:%S ").to_string(),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(entry.message.clone(), Style::default().fg(color)),
+                ])
+            })
+            .collect();
+
+        app.log_page_height = (popup_area.height as usize).saturating_sub(2);
+        let max_scroll = log_lines.len().saturating_sub(app.log_page_height);
+        app.log_scroll = app.log_scroll.min(max_scroll);
+        let log_title = format!(" Log ({}) - Esc to close ", log_lines.len());
+        let log_popup = Paragraph::new(if log_lines.is_empty() {
+            vec![Line::from("Nothing to report yet")]
+        } else {
+            log_lines
+        })
+        .block(
+            Block::default()
+                .title(log_title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Blue)),
+        )
+        .scroll((app.log_scroll as u16, 0));
+        f.render_widget(log_popup, popup_area);
     }
 
     // Tab close confirmation popup
@@ -2461,9 +6315,10 @@ fn ui(f: &mut Frame, app: &mut App) {
                 .find_syntax_for_file(&selected_file.path)
                 .ok()
                 .flatten()
+                .filter(|_| app.highlights(app.file_content.len()))
                 .unwrap_or_else(|| app.syntax_set.find_syntax_plain_text());
 
-            let theme = &app.theme_set.themes["base16-ocean.dark"];
+            let theme = themes::get(&app.theme_set, &app.current_theme);
             let mut highlighter = HighlightLines::new(syntax, theme);
 
             let mut lines: Vec<Line> = Vec::new();
@@ -2525,7 +6380,7 @@ fn ui(f: &mut Frame, app: &mut App) {
                                     if char_idx == app.cursor_col && app.cursor_blink_state {
                                         // Insert cursor before this character
                                         spans.push(Span::styled(
-                                            "█",
+                                            app.config.cursor_shape.glyph(),
                                             Style::default().fg(Color::White).bg(Color::DarkGray),
                                         ));
                                     }
@@ -2558,7 +6413,7 @@ fn ui(f: &mut Frame, app: &mut App) {
                             // If cursor is at end of line
                             if app.cursor_col >= line_chars.len() && app.cursor_blink_state {
                                 spans.push(Span::styled(
-                                    "█",
+                                    app.config.cursor_shape.glyph(),
                                     Style::default().fg(Color::White).bg(Color::DarkGray),
                                 ));
                             }
@@ -2579,7 +6434,7 @@ fn ui(f: &mut Frame, app: &mut App) {
                             for (col_idx, ch) in line_chars.iter().enumerate() {
                                 if col_idx == app.cursor_col && app.cursor_blink_state {
                                     spans.push(Span::styled(
-                                        "█",
+                                        app.config.cursor_shape.glyph(),
                                         Style::default().fg(Color::White).bg(Color::DarkGray),
                                     ));
                                 }
@@ -2591,7 +6446,7 @@ fn ui(f: &mut Frame, app: &mut App) {
 
                             if app.cursor_col >= line_chars.len() && app.cursor_blink_state {
                                 spans.push(Span::styled(
-                                    "█",
+                                    app.config.cursor_shape.glyph(),
                                     Style::default().fg(Color::White).bg(Color::DarkGray),
                                 ));
                             }
@@ -2690,9 +6545,10 @@ fn ui(f: &mut Frame, app: &mut App) {
                 .find_syntax_for_file(&selected_file.path)
                 .ok()
                 .flatten()
+                .filter(|_| app.highlights(app.file_content.len()))
                 .unwrap_or_else(|| app.syntax_set.find_syntax_plain_text());
 
-            let theme = &app.theme_set.themes["base16-ocean.dark"];
+            let theme = themes::get(&app.theme_set, &app.current_theme);
             let mut highlighter = HighlightLines::new(syntax, theme);
 
             let mut lines: Vec<Line> = Vec::new();
@@ -3002,21 +6858,243 @@ fn ui(f: &mut Frame, app: &mut App) {
                 .border_style(Style::default().fg(Color::Cyan)),
         );
 
-        f.render_widget(finder_list, finder_area);
+        f.render_widget(finder_list, finder_area);
+
+        let help_area = ratatui::layout::Rect {
+            x: finder_area.x + 2,
+            y: finder_area.y + finder_area.height - 2,
+            width: finder_area.width - 4,
+            height: 1,
+        };
+        f.render_widget(
+            Paragraph::new(
+                "Type to filter, ↑↓ to navigate, Enter to open, Del to delete, Esc to close",
+            )
+            .style(Style::default().fg(Color::Gray)),
+            help_area,
+        );
+    }
+
+    // Search in files overlay
+    if app.grep_mode {
+        let grep_area = centered_rect(80, 70, size);
+        f.render_widget(Clear, grep_area);
+
+        let items: Vec<ListItem> = app
+            .grep_results
+            .iter()
+            .map(|grep_match| {
+                let relative_path = grep_match
+                    .path
+                    .strip_prefix(&app.current_path)
+                    .unwrap_or(&grep_match.path)
+                    .to_string_lossy()
+                    .to_string();
+                ListItem::new(Line::from(vec![
+                    Span::styled(relative_path, Style::default().fg(Color::Cyan)),
+                    Span::raw(":"),
+                    Span::styled(
+                        (grep_match.line + 1).to_string(),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(": "),
+                    Span::raw(grep_match.text.clone()),
+                ]))
+            })
+            .collect();
+
+        let mut grep_list_state = ListState::default();
+        if !app.grep_results.is_empty() {
+            grep_list_state.select(Some(app.grep_selected));
+        }
+        let grep_list = List::new(items)
+            .block(
+                Block::default()
+                    .title(format!(" Search in Files: {}▏", app.grep_query))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan)),
+            )
+            .highlight_style(Style::default().bg(Color::Blue).fg(Color::White));
+        f.render_stateful_widget(grep_list, grep_area, &mut grep_list_state);
+
+        let status = if app.grep_results_query.is_empty() {
+            "Type a query and press Enter to search, Esc to close".to_string()
+        } else if app.grep_receiver.is_some() {
+            format!("Searching… {} matches", app.grep_results.len())
+        } else if app.grep_query != app.grep_results_query {
+            "Press Enter to search again".to_string()
+        } else {
+            format!(
+                "{} matches | ↑↓ to navigate, Enter to open, Esc to close",
+                app.grep_results.len()
+            )
+        };
+        let help_area = ratatui::layout::Rect {
+            x: grep_area.x + 2,
+            y: grep_area.y + grep_area.height - 1,
+            width: grep_area.width - 4,
+            height: 1,
+        };
+        f.render_widget(
+            Paragraph::new(status).style(Style::default().fg(Color::Gray)),
+            help_area,
+        );
+    }
+
+    if app.show_places {
+        let places_area = centered_rect(60, 50, size);
+        f.render_widget(Clear, places_area);
+
+        let name_width = app
+            .config
+            .places
+            .keys()
+            .map(|name| name.width())
+            .max()
+            .unwrap_or(0);
+        let items: Vec<ListItem> = app
+            .config
+            .places
+            .iter()
+            .map(|(name, path)| {
+                // Places that have gone missing stay listed, in red
+                let path_color = if path.is_dir() {
+                    Color::DarkGray
+                } else {
+                    Color::Red
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{}  ", fit_to_width(name, name_width)),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                    Span::styled(path.display().to_string(), Style::default().fg(path_color)),
+                ]))
+            })
+            .collect();
+        let mut places_list_state = ListState::default();
+        places_list_state.select(Some(app.places_selected));
+        let places_list = List::new(items)
+            .block(
+                Block::default()
+                    .title(" Places - Enter to go there, Esc to close ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan)),
+            )
+            .highlight_style(Style::default().bg(Color::Blue).fg(Color::White));
+        f.render_stateful_widget(places_list, places_area, &mut places_list_state);
+    }
+
+    if app.show_filters && !app.editor_focused() {
+        let filters_area = centered_rect(70, 50, size);
+        f.render_widget(Clear, filters_area);
+        let filters_popup = Paragraph::new(app.filter_report())
+            .block(
+                Block::default()
+                    .title(" Filters - keys still work, ? or Esc to close ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan)),
+            )
+            .wrap(Wrap { trim: false });
+        f.render_widget(filters_popup, filters_area);
+    }
+
+    if let Some(job) = &app.checksum {
+        let checksum_area = centered_rect(70, 40, size);
+        f.render_widget(Clear, checksum_area);
+
+        let mut lines = vec![
+            Line::from(Span::styled(
+                job.path.display().to_string(),
+                Style::default().fg(Color::Cyan),
+            )),
+            Line::from(""),
+        ];
+        match &job.result {
+            None => {
+                let percent = (job.hashed * 100).checked_div(job.size).unwrap_or(0);
+                lines.push(Line::from(format!(
+                    "Hashing... {}% ({} of {})",
+                    percent,
+                    size::format_size(job.hashed, true, app.size_units),
+                    size::format_size(job.size, true, app.size_units)
+                )));
+            }
+            Some(Ok(hash)) => lines.push(Line::from(Span::styled(
+                hash.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ))),
+            Some(Err(e)) => lines.push(Line::from(Span::styled(
+                format!("Could not hash the file: {}", e),
+                Style::default().fg(Color::Red),
+            ))),
+        }
+        if let Some(expected) = &job.expected {
+            lines.push(Line::from(""));
+            lines.push(match job.matches_expected() {
+                Some(true) => Line::from(Span::styled(
+                    "✓ Matches the expected hash",
+                    Style::default().fg(Color::Green),
+                )),
+                Some(false) => Line::from(Span::styled(
+                    format!("✗ Does not match {}", checksum::expected_hash(expected)),
+                    Style::default().fg(Color::Red),
+                )),
+                None => Line::from(format!("Expected {}", checksum::expected_hash(expected))),
+            });
+        }
+
+        let checksum_popup = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(format!(
+                        " {} - Tab algorithm, c copy, v compare, Esc close ",
+                        job.algorithm.label()
+                    ))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan)),
+            )
+            .wrap(Wrap { trim: false });
+        f.render_widget(checksum_popup, checksum_area);
+    }
+
+    if let Some(history) = &app.history {
+        let history_area = centered_rect(70, 60, size);
+        f.render_widget(Clear, history_area);
 
-        let help_area = ratatui::layout::Rect {
-            x: finder_area.x + 2,
-            y: finder_area.y + finder_area.height - 2,
-            width: finder_area.width - 4,
-            height: 1,
+        let items: Vec<ListItem> = history
+            .iter()
+            .map(|(path, last_opened)| {
+                let opened = chrono::DateTime::from_timestamp(*last_opened as i64, 0)
+                    .unwrap_or_default()
+                    .format("%Y-%m-%d %H:%M ")
+                    .to_string();
+                ListItem::new(Line::from(vec![
+                    Span::styled(opened, Style::default().fg(Color::DarkGray)),
+                    Span::styled(path.display().to_string(), Style::default().fg(Color::Cyan)),
+                ]))
+            })
+            .collect();
+        let mut history_list_state = ListState::default();
+        if !history.is_empty() {
+            history_list_state.select(Some(app.history_selected));
+        }
+        let title = if app.history_confirm_clear {
+            format!(" Forget all {} recent files? [y/n] ", history.len())
+        } else if history.is_empty() {
+            " Recent Files - nothing opened yet, Esc to close ".to_string()
+        } else {
+            " Recent Files - Enter to open, C to clear history, Esc to close ".to_string()
         };
-        f.render_widget(
-            Paragraph::new(
-                "Type to filter, ↑↓ to navigate, Enter to open, Del to delete, Esc to close",
+        let history_list = List::new(items)
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan)),
             )
-            .style(Style::default().fg(Color::Gray)),
-            help_area,
-        );
+            .highlight_style(Style::default().bg(Color::Blue).fg(Color::White));
+        f.render_stateful_widget(history_list, history_area, &mut history_list_state);
     }
 
     // File tree modal
@@ -3085,28 +7163,870 @@ fn ui(f: &mut Frame, app: &mut App) {
             .and_then(|n| n.to_str())
             .unwrap_or("Unknown");
 
-        let confirm_text = vec![
-            Line::from(""),
-            Line::from(format!("Delete file: {}", file_name)),
-            Line::from(""),
-            Line::from("This action cannot be undone!"),
-            Line::from(""),
-            Line::from("Press:"),
-            Line::from("  Y - Yes, delete file"),
-            Line::from("  N - No, cancel"),
-        ];
+        let confirm_text = vec![
+            Line::from(""),
+            Line::from(format!("Delete file: {}", file_name)),
+            Line::from(""),
+            Line::from("This action cannot be undone!"),
+            Line::from(""),
+            Line::from("Press:"),
+            Line::from("  Y - Yes, delete file"),
+            Line::from("  N - No, cancel"),
+        ];
+
+        let confirm_dialog = Paragraph::new(confirm_text)
+            .block(
+                Block::default()
+                    .title(" Confirm Delete ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Red)),
+            )
+            .style(Style::default().fg(Color::White));
+
+        f.render_widget(confirm_dialog, confirm_area);
+    }
+}
+
+/// Guesses from the extension or well-known names whether a file is text
+/// the viewer can open.
+fn is_text_path(path: &Path) -> bool {
+    if let Some(ext) = path.extension() {
+        if let Some(ext_str) = ext.to_str() {
+            matches!(
+                ext_str.to_lowercase().as_str(),
+                "txt"
+                    | "md"
+                    | "rs"
+                    | "py"
+                    | "js"
+                    | "ts"
+                    | "html"
+                    | "css"
+                    | "json"
+                    | "xml"
+                    | "yaml"
+                    | "yml"
+                    | "toml"
+                    | "cfg"
+                    | "conf"
+                    | "log"
+                    | "sh"
+                    | "bash"
+                    | "zsh"
+                    | "fish"
+                    | "c"
+                    | "cpp"
+                    | "h"
+                    | "hpp"
+                    | "java"
+                    | "go"
+                    | "php"
+                    | "rb"
+                    | "pl"
+                    | "lua"
+                    | "vim"
+                    | "sql"
+                    | "csv"
+            )
+        } else {
+            false
+        }
+    } else {
+        // Check if filename suggests it's a text file
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        matches!(
+            name.as_str(),
+            "readme"
+                | "license"
+                | "changelog"
+                | "makefile"
+                | "dockerfile"
+                | "gitignore"
+                | "gitattributes"
+                | "editorconfig"
+        )
+    }
+}
+
+/// `path` relative to `base`, comparing canonical forms when the paths as
+/// given don't share a prefix (symlinked checkouts, `.` components).
+/// The directory listing: the whole screen, or the pane beside open files.
+/// Borders, highlight symbol and scrollbar take 5 columns off the width.
+fn render_file_list(f: &mut Frame, app: &mut App, area: Rect, border_style: Style) {
+    app.file_list_area = Some(area);
+    let git_column = app.shows_git_column();
+    let mut row_width = (area.width as usize).saturating_sub(5);
+    if git_column {
+        row_width = row_width.saturating_sub(GIT_COLUMN_WIDTH);
+    }
+    let name_width = app.name_column_width(row_width);
+    let group_starts = app.group_starts();
+    // Directory sizes are just the entry's own, so they get no bar
+    let largest = app
+        .files
+        .iter()
+        .filter(|file| !file.is_dir)
+        .map(|file| file.size)
+        .max()
+        .unwrap_or(0);
+    let mut items: Vec<ListItem> = app
+        .files
+        .iter()
+        .map(|file| {
+            let style = if app.marked_paths.contains(&file.path) {
+                Style::default()
+                    .fg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD)
+            } else if app.clutter == ClutterMode::Dim && app.is_clutter(file) {
+                Style::default().fg(Color::DarkGray)
+            } else if file.is_dir {
+                Style::default().fg(Color::Blue)
+            } else if app.is_text_file(file) {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default().fg(Color::White)
+            };
+
+            let mut row = if app.heat_colors {
+                let age = SystemTime::now()
+                    .duration_since(file.modified)
+                    .unwrap_or_default();
+                Line::from(vec![
+                    Span::raw(file.format_row_without_date(
+                        name_width,
+                        app.human_readable,
+                        app.size_units,
+                    )),
+                    Span::raw(" "),
+                    Span::styled(
+                        file.format_date(),
+                        Style::default().fg(heat::heat_color(age)),
+                    ),
+                ])
+            } else {
+                Line::from(file.format_row(name_width, app.human_readable, app.size_units))
+            };
+            if git_column {
+                let marker = match app.git_state(file) {
+                    Some(state) => Span::styled(
+                        format!("{:<2} ", state.marker()),
+                        Style::default().fg(state.color()),
+                    ),
+                    None => Span::raw(" ".repeat(GIT_COLUMN_WIDTH)),
+                };
+                row.spans.insert(0, marker);
+            }
+            if app.show_size_bars {
+                let size = if file.is_dir { 0 } else { file.size };
+                row.spans.push(Span::styled(
+                    format!(" {}", size::size_bar(size, largest, size::BAR_WIDTH)),
+                    Style::default().fg(Color::Cyan),
+                ));
+            }
+            if app.show_inodes {
+                let links_style = if file.is_hard_linked() {
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                row.spans.push(Span::styled(
+                    format!(" {}", file.format_links()),
+                    links_style,
+                ));
+            }
+            ListItem::new(row).style(style)
+        })
+        .collect();
+
+    // Headers go in from the bottom up so earlier indices stay valid.
+    // They aren't in `files`, so navigation never lands on them; only
+    // the highlighted row has to shift past the ones above it.
+    for &(start, group, count) in group_starts.iter().rev() {
+        items.insert(
+            start,
+            ListItem::new(format!("── {} ({}) ──", group.label(), count)).style(
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        );
+    }
+    let headers_above = group_starts
+        .iter()
+        .filter(|(start, _, _)| *start <= app.selected_index)
+        .count();
+    if !app.files.is_empty() {
+        app.list_state
+            .select(Some(app.selected_index + headers_above));
+    }
+
+    let header = sort_header(app, area, name_width);
+    let files_list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title(header),
+        )
+        .highlight_style(app.config.selection_style())
+        .highlight_symbol(&app.config.highlight_symbol);
+
+    f.render_stateful_widget(files_list, area, &mut app.list_state);
+
+    // Scrollbar
+    let scrollbar = Scrollbar::default()
+        .orientation(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(Some("↑"))
+        .end_symbol(Some("↓"));
+    f.render_stateful_widget(
+        scrollbar,
+        area.inner(&Margin {
+            vertical: 1,
+            horizontal: 1,
+        }),
+        &mut app.scroll_state,
+    );
+}
+
+/// Column titles for the listing, set into its top border above the
+/// columns they name, with the sort column highlighted and an arrow for its
+/// direction. Remembers where each title landed for mouse clicks.
+fn sort_header(app: &mut App, area: Rect, name_width: usize) -> Line<'static> {
+    // Rows start after the border, the highlight symbol and the icon
+    let mut indent = UnicodeWidthStr::width(app.config.highlight_symbol.as_str()) + 3;
+    if app.shows_git_column() {
+        indent += GIT_COLUMN_WIDTH;
+    }
+    let columns = [
+        (SortKey::Name, name_width, false),
+        (SortKey::Size, 10, true),
+        (SortKey::Modified, 16, false),
+    ];
+
+    let mut spans = vec![Span::raw(" ".repeat(indent))];
+    let mut hits = Vec::new();
+    let mut x = area.x + 1 + indent as u16;
+    for (key, width, right_aligned) in columns {
+        // Sorting by extension still sorts on the name column
+        let active = app.sort_mode.key == key
+            || (key == SortKey::Name && app.sort_mode.key == SortKey::Extension);
+        let label = if active {
+            format!("{} {}", app.sort_mode.key.label(), app.sort_mode.arrow())
+        } else {
+            key.label().to_string()
+        };
+        let text = if right_aligned {
+            format!("{:>width$}", label, width = width)
+        } else {
+            fit_to_width(&label, width)
+        };
+        let style = if active {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        hits.push((area.y, x..x + width as u16, key));
+        x += width as u16 + 1;
+        spans.push(Span::styled(text, style));
+        spans.push(Span::raw(" "));
+        if key == SortKey::Size {
+            // The permissions column isn't sortable; keep its room
+            spans.push(Span::raw(" ".repeat(11)));
+            x += 11;
+        }
+    }
+    if app.show_size_bars {
+        spans.push(Span::styled(
+            format!("{} ", fit_to_width("Size bar", size::BAR_WIDTH)),
+            Style::default().fg(Color::Gray),
+        ));
+    }
+    if app.show_inodes {
+        spans.push(Span::styled(
+            format!("{:>10} {:>5}", "Inode", "Links"),
+            Style::default().fg(Color::Gray),
+        ));
+    }
+    app.sort_header = hits;
+    Line::from(spans)
+}
+
+/// Draws one open file: the whole content area, or one side of a split.
+fn render_tab_pane(f: &mut Frame, app: &App, tab: &Tab, area: Rect, focused: bool) {
+    let max_visible = (area.height as usize).saturating_sub(2); // Account for borders
+    let content_lines: Vec<&str> = tab.content.lines().collect();
+    let total_lines = content_lines.len();
+    // The pager has no caret; its current line only matters for search hits.
+    // Only the focused pane of a split shows either.
+    let show_cursor = focused && !tab.read_only;
+    let highlight_cursor_line = show_cursor || (focused && !app.search_matches.is_empty());
+    let wrap = if tab.read_only || tab.markdown_preview {
+        app.viewer_wrap
+    } else {
+        app.editor_wrap
+    };
+    let hidden_columns = if wrap { 0 } else { tab.horizontal_scroll };
+    let tab_width = app.config.tab_width;
+
+    // Calculate visible lines, skipping the bodies of folded blocks
+    let visible_lines = content_lines
+        .iter()
+        .enumerate()
+        .skip(tab.scroll_offset)
+        .filter(|(line, _)| !tab.folds.is_hidden(*line))
+        .take(max_visible);
+
+    // Prepare syntax highlighting; plain text when it's off or the file is
+    // too big to color every frame
+    let syntax = if app.highlights(tab.content.len()) {
+        tab_syntax(&app.syntax_set, tab)
+    } else {
+        app.syntax_set.find_syntax_plain_text()
+    };
+
+    let theme = themes::get(&app.theme_set, &app.current_theme);
+    let mut highlighter = HighlightLines::new(syntax, theme);
+
+    let mut lines: Vec<Line> = Vec::new();
+    let line_number_width = total_lines.to_string().len().max(3);
+
+    if tab.markdown_preview {
+        lines = markdown::render(&tab.content)
+            .into_iter()
+            .skip(tab.scroll_offset)
+            .take(max_visible)
+            .map(|line| Line::from(skip_columns(line.spans, hidden_columns)))
+            .collect();
+    } else {
+        for (actual_line_idx, line_text) in visible_lines {
+            let line_number = actual_line_idx + 1;
+
+            // Create line number span, with any change marker in the gap after it
+            let line_num_str = format!("{:width$}", line_number, width = line_number_width);
+            let line_num_span = Span::styled(line_num_str, Style::default().fg(Color::DarkGray));
+            let marker = match tab.gutter.get(actual_line_idx) {
+                Some(linediff::Change::Added) => {
+                    Span::styled("▎", Style::default().fg(Color::Green))
+                }
+                Some(linediff::Change::Modified) => {
+                    Span::styled("▎", Style::default().fg(Color::Yellow))
+                }
+                Some(linediff::Change::Removed) => {
+                    Span::styled("▁", Style::default().fg(Color::Red))
+                }
+                None => Span::raw(" "),
+            };
+
+            let mut spans = vec![line_num_span, marker];
+            // Screen column within the text, for expanding tabs
+            let mut column = 0;
+
+            if actual_line_idx == tab.cursor_line && highlight_cursor_line {
+                // This line contains the cursor - highlight background
+                match highlighter.highlight_line(line_text, &app.syntax_set) {
+                    Ok(highlighted) => {
+                        let line_chars: Vec<char> = line_text.chars().collect();
+                        let mut char_idx = 0;
+
+                        for (style, text) in highlighted {
+                            let fg_color = style.foreground;
+                            let color = Color::Rgb(fg_color.r, fg_color.g, fg_color.b);
+                            let mut modifier = Modifier::empty();
+                            if style
+                                .font_style
+                                .contains(syntect::highlighting::FontStyle::BOLD)
+                            {
+                                modifier |= Modifier::BOLD;
+                            }
+
+                            for ch in text.chars() {
+                                let mut cell = editor::expand_tabs(
+                                    ch.encode_utf8(&mut [0; 4]),
+                                    &mut column,
+                                    tab_width,
+                                );
+                                if show_cursor
+                                    && char_idx == tab.cursor_col
+                                    && app.cursor_blink_state
+                                {
+                                    // Insert cursor before this character;
+                                    // on a tab it takes the tab's first cell
+                                    if ch == '\t' {
+                                        cell.pop();
+                                    }
+                                    spans.push(Span::styled(
+                                        app.config.cursor_shape.glyph(),
+                                        Style::default().fg(Color::White).bg(Color::DarkGray),
+                                    ));
+                                }
+
+                                spans.push(Span::styled(
+                                    cell,
+                                    Style::default()
+                                        .fg(color)
+                                        .add_modifier(modifier)
+                                        .bg(Color::DarkGray),
+                                ));
+                                char_idx += 1;
+                            }
+                        }
+
+                        // If cursor is at end of line
+                        if show_cursor
+                            && tab.cursor_col >= line_chars.len()
+                            && app.cursor_blink_state
+                        {
+                            spans.push(Span::styled(
+                                app.config.cursor_shape.glyph(),
+                                Style::default().fg(Color::White).bg(Color::DarkGray),
+                            ));
+                        }
+                    }
+                    Err(_) => {
+                        spans.push(Span::styled(
+                            editor::expand_tabs(line_text, &mut column, tab_width),
+                            Style::default().bg(Color::DarkGray),
+                        ));
+                    }
+                }
+            } else {
+                // Regular line with syntax highlighting
+                match highlighter.highlight_line(line_text, &app.syntax_set) {
+                    Ok(highlighted) => {
+                        for (style, text) in highlighted {
+                            let fg_color = style.foreground;
+                            let color = Color::Rgb(fg_color.r, fg_color.g, fg_color.b);
+                            let mut modifier = Modifier::empty();
+                            if style
+                                .font_style
+                                .contains(syntect::highlighting::FontStyle::BOLD)
+                            {
+                                modifier |= Modifier::BOLD;
+                            }
+                            spans.push(Span::styled(
+                                editor::expand_tabs(text, &mut column, tab_width),
+                                Style::default().fg(color).add_modifier(modifier),
+                            ));
+                        }
+                    }
+                    Err(_) => {
+                        spans.push(Span::raw(editor::expand_tabs(
+                            line_text,
+                            &mut column,
+                            tab_width,
+                        )));
+                    }
+                }
+            }
+
+            // The line number and change marker stay put while the text scrolls
+            // sideways
+            if hidden_columns > 0 {
+                let text = spans.split_off(2);
+                spans.extend(skip_columns(text, hidden_columns));
+            }
+            if let Some(hidden) = tab.folds.hidden_under(actual_line_idx) {
+                spans.push(Span::styled(
+                    format!(" … {} lines folded", hidden),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+
+            lines.push(Line::from(spans));
+        }
+    }
+
+    let no_wrap = if wrap { "" } else { ", NO WRAP" };
+    let (edit_title, border_color) = if tab.markdown_preview {
+        (
+            format!(" {} (PREVIEW{}) ", tab.name, no_wrap),
+            Color::Magenta,
+        )
+    } else if let Some(follow) = &tab.tail {
+        let state = if follow.following {
+            "FOLLOWING"
+        } else {
+            "PAUSED"
+        };
+        (
+            format!(" {} ({}{}) ", tab.name, state, no_wrap),
+            Color::Yellow,
+        )
+    } else if tab.read_only && !tab.pdf_pages.is_empty() {
+        let page = pdf::page_at(&tab.pdf_pages, tab.scroll_offset);
+        (
+            format!(
+                " {} (VIEW, PAGE {} OF {}{}) ",
+                tab.name,
+                page,
+                tab.pdf_pages.len(),
+                no_wrap
+            ),
+            Color::Yellow,
+        )
+    } else if tab.read_only {
+        (format!(" {} (VIEW{}) ", tab.name, no_wrap), Color::Yellow)
+    } else if tab.has_unsaved_changes {
+        (
+            format!(" {} (EDITING - UNSAVED{}) ", tab.name, no_wrap),
+            Color::Green,
+        )
+    } else {
+        (format!(" {} (EDITING{}) ", tab.name, no_wrap), Color::Green)
+    };
+    let border_color = if focused {
+        border_color
+    } else {
+        Color::DarkGray
+    };
+
+    let mut content_paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .title(edit_title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color)),
+    );
+    if wrap {
+        content_paragraph = content_paragraph.wrap(Wrap { trim: false });
+    }
+
+    f.render_widget(content_paragraph, area);
+}
+
+/// Cells of overview the minimap column gets beside a long file
+const MINIMAP_WIDTH: u16 = 10;
+
+/// Splits a pane into the file's own area and, for a file that runs past
+/// the view in a pane wide enough to spare it, the inside of a minimap
+/// column on its right.
+fn minimap_split(app: &App, tab: &Tab, area: Rect) -> (Rect, Option<Rect>) {
+    let rows = (area.height as usize).saturating_sub(2);
+    if !app.config.minimap
+        || tab.markdown_preview
+        || area.width < 60
+        || tab.content.lines().count() <= rows
+    {
+        return (area, None);
+    }
+    let column_width = MINIMAP_WIDTH + 2;
+    let file_area = Rect {
+        width: area.width - column_width,
+        ..area
+    };
+    let minimap = Rect {
+        x: area.x + file_area.width + 1,
+        y: area.y + 1,
+        width: MINIMAP_WIDTH,
+        height: area.height.saturating_sub(2),
+    };
+    (file_area, Some(minimap))
+}
+
+/// Draws the whole of `tab` shrunk into `area` (the inside of its column),
+/// with the rows for the lines in view highlighted.
+fn render_minimap(f: &mut Frame, app: &App, tab: &Tab, area: Rect) {
+    let lines: Vec<&str> = tab.content.lines().collect();
+    let rows = area.height as usize;
+    let in_view = minimap::viewport(
+        tab.scroll_offset,
+        app.content_viewport_height,
+        lines.len(),
+        rows,
+    );
+    let text: Vec<Line> = minimap::render(&lines, rows, area.width as usize, app.config.tab_width)
+        .into_iter()
+        .enumerate()
+        .map(|(row, cells)| {
+            let style = if in_view.contains(&row) {
+                Style::default().fg(Color::White).bg(Color::DarkGray)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Line::from(Span::styled(cells, style))
+        })
+        .collect();
+
+    let column = Rect {
+        x: area.x - 1,
+        y: area.y - 1,
+        width: area.width + 2,
+        height: area.height + 2,
+    };
+    f.render_widget(
+        Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray)),
+        ),
+        column,
+    );
+}
+
+/// Drops the first `columns` characters from a line's spans, keeping the
+/// style of whatever remains.
+fn skip_columns(spans: Vec<Span<'_>>, columns: usize) -> Vec<Span<'_>> {
+    let mut remaining = columns;
+    spans
+        .into_iter()
+        .filter_map(|span| {
+            if remaining == 0 {
+                return Some(span);
+            }
+            let len = span.content.chars().count();
+            if len <= remaining {
+                remaining -= len;
+                return None;
+            }
+            let rest: String = span.content.chars().skip(remaining).collect();
+            remaining = 0;
+            Some(Span::styled(rest, span.style))
+        })
+        .collect()
+}
+
+/// Finds a syntax the way people name them: by its full name ("JSON") or
+/// by an extension or short token ("json", "sh").
+fn resolve_syntax<'a>(syntax_set: &'a SyntaxSet, name: &str) -> Option<&'a SyntaxReference> {
+    syntax_set
+        .find_syntax_by_name(name)
+        .or_else(|| syntax_set.find_syntax_by_token(name))
+}
+
+/// The tab's chosen syntax, else a guess from its path, else plain text.
+fn tab_syntax<'a>(syntax_set: &'a SyntaxSet, tab: &Tab) -> &'a SyntaxReference {
+    tab.syntax_override
+        .as_deref()
+        .and_then(|name| syntax_set.find_syntax_by_name(name))
+        .or_else(|| syntax_set.find_syntax_for_file(&tab.path).ok().flatten())
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text())
+}
+
+/// Drops `.` and folds `..` into its parent without touching the
+/// filesystem, so symlinks in `path` stay as written.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+fn relative_to(path: &Path, base: &Path) -> Option<PathBuf> {
+    if let Ok(relative) = path.strip_prefix(base) {
+        return Some(relative.to_path_buf());
+    }
+    let path = path.canonicalize().ok()?;
+    let base = base.canonicalize().ok()?;
+    path.strip_prefix(base).ok().map(Path::to_path_buf)
+}
+
+/// Interprets pasted text as a path the way terminals deliver drag-and-drop:
+/// optionally quoted, with backslash-escaped spaces, or as a `file://` URL.
+/// Relative paths resolve against `base`. Returns the canonical path if it
+/// exists.
+fn pasted_path(text: &str, base: &Path) -> Option<PathBuf> {
+    let text = text.trim();
+    if text.is_empty() || text.contains('\n') {
+        return None;
+    }
+
+    let unquoted = ['\'', '"']
+        .iter()
+        .find_map(|quote| {
+            text.strip_prefix(*quote)
+                .and_then(|rest| rest.strip_suffix(*quote))
+        })
+        .unwrap_or(text);
+
+    let path = if unquoted.starts_with("file://") {
+        UrlType::parse(unquoted).ok()?.to_file_path().ok()?
+    } else {
+        let mut unescaped = String::with_capacity(unquoted.len());
+        let mut chars = unquoted.chars();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' => unescaped.extend(chars.next()),
+                _ => unescaped.push(ch),
+            }
+        }
+        PathBuf::from(unescaped)
+    };
+
+    base.join(path).canonicalize().ok()
+}
+
+/// Pads or truncates `text` to exactly `width` terminal columns, marking
+/// truncation with an ellipsis.
+/// What a `{name}` placeholder in `footer_format` stands for right now.
+/// With a file focused in the editor, path, selected and size describe
+/// that file instead of the listing.
+fn footer_value(app: &App, name: &str, hints: &str) -> Option<String> {
+    let tab = app
+        .tab_manager
+        .get_active_tab()
+        .filter(|_| app.editor_focused());
+    let selected = app.files.get(app.selected_index);
+
+    let value = match name {
+        "hints" => hints.to_string(),
+        "mode" => footer_mode(app).to_string(),
+        "path" => match tab {
+            Some(tab) => tab.path.display().to_string(),
+            None => app.current_path.display().to_string(),
+        },
+        "selected" => match (tab, selected) {
+            (Some(tab), _) => tab.get_display_name(),
+            (None, Some(file)) => file.name.clone(),
+            (None, None) => String::new(),
+        },
+        "count" => app
+            .files
+            .iter()
+            .filter(|file| file.name != "..")
+            .count()
+            .to_string(),
+        "size" => {
+            let size = match tab {
+                Some(tab) => tab.content.len() as u64,
+                None => selected.map_or(0, |file| file.size),
+            };
+            size::format_size(size, app.human_readable, app.size_units)
+                .trim()
+                .to_string()
+        }
+        "git_branch" => app
+            .git_summary
+            .as_ref()
+            .map(|summary| summary.branch.clone())
+            .unwrap_or_default(),
+        "time" => chrono::Local::now().format("%H:%M").to_string(),
+        "encoding" => tab.map(|tab| tab.encoding_label()).unwrap_or_default(),
+        "theme" => app.current_theme.clone(),
+        "line_ending" => tab
+            .and_then(|tab| editor::line_ending_label(&tab.content))
+            .unwrap_or_default()
+            .to_string(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// One word for what has focus, for the `{mode}` footer placeholder.
+fn footer_mode(app: &App) -> &'static str {
+    if app.show_help {
+        "HELP"
+    } else if app.show_terminal {
+        "TERMINAL"
+    } else if app.editor_focused() {
+        match app.tab_manager.get_active_tab() {
+            Some(tab) if tab.markdown_preview => "PREVIEW",
+            Some(tab) if tab.read_only => "VIEW",
+            _ => "EDIT",
+        }
+    } else {
+        "FILES"
+    }
+}
+
+/// Applies one typed key to an editable tab: a character, Enter, Tab
+/// (`tab_width` spaces) or Backspace. Other control characters do nothing.
+fn edit_tab(tab: &mut Tab, ch: char, tab_width: usize) {
+    match ch {
+        '\n' => {
+            editor::insert(&mut tab.content, tab.cursor_line, tab.cursor_col, "\n");
+            tab.cursor_line += 1;
+            tab.cursor_col = 0;
+        }
+        '\t' => {
+            editor::insert(
+                &mut tab.content,
+                tab.cursor_line,
+                tab.cursor_col,
+                &" ".repeat(tab_width),
+            );
+            tab.cursor_col += tab_width;
+        }
+        '\u{8}' | '\u{7f}' => {
+            (tab.cursor_line, tab.cursor_col) =
+                editor::backspace(&mut tab.content, tab.cursor_line, tab.cursor_col);
+        }
+        c if c.is_control() => {
+            // Ignore other control characters
+        }
+        _ => {
+            let mut buffer = [0; 4];
+            editor::insert(
+                &mut tab.content,
+                tab.cursor_line,
+                tab.cursor_col,
+                ch.encode_utf8(&mut buffer),
+            );
+            tab.cursor_col += 1;
+        }
+    }
+
+    tab.mark_dirty();
+
+    // Auto-scroll to keep cursor visible
+    let visible_lines = 30;
+    let total_lines = tab.content.lines().count();
+
+    if tab.cursor_line >= tab.scroll_offset + visible_lines {
+        tab.scroll_offset = tab.cursor_line.saturating_sub(visible_lines - 1);
+    } else if tab.cursor_line < tab.scroll_offset {
+        tab.scroll_offset = tab.cursor_line;
+    }
 
-        let confirm_dialog = Paragraph::new(confirm_text)
-            .block(
-                Block::default()
-                    .title(" Confirm Delete ")
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Red)),
-            )
-            .style(Style::default().fg(Color::White));
+    // Ensure we don't scroll past the end of file
+    let max_scroll = total_lines.saturating_sub(visible_lines);
+    tab.scroll_offset = tab.scroll_offset.min(max_scroll);
+}
 
-        f.render_widget(confirm_dialog, confirm_area);
+/// Scrolls a tab so its last line sits at the bottom of a view `page`
+/// rows tall.
+fn scroll_to_end(tab: &mut Tab, page: usize) {
+    let visible = tab.folds.visible_lines(tab.content.lines().count());
+    tab.scroll_offset = visible
+        .get(visible.len().saturating_sub(page))
+        .copied()
+        .unwrap_or(0);
+    tab.cursor_line = visible.last().copied().unwrap_or(0);
+    tab.undo.break_run();
+}
+
+fn fit_to_width(text: &str, width: usize) -> String {
+    let text_width = UnicodeWidthStr::width(text);
+    if text_width <= width {
+        return format!("{}{}", text, " ".repeat(width - text_width));
+    }
+
+    let mut fitted = String::new();
+    let mut used = 0;
+    for ch in text.chars() {
+        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if used + ch_width + 1 > width {
+            break;
+        }
+        fitted.push(ch);
+        used += ch_width;
     }
+    fitted.push('…');
+    used += 1;
+    fitted.push_str(&" ".repeat(width.saturating_sub(used)));
+    fitted
 }
 
 fn centered_rect(
@@ -3134,16 +8054,60 @@ fn centered_rect(
 }
 
 fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()> {
+    let result = event_loop(terminal, &mut app);
+    // Quitting closes every tab, so their histories are kept like any other
+    for index in 0..app.tab_manager.tab_count() {
+        app.save_undo_history(index);
+    }
+    result
+}
+
+fn event_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> AppResult<()> {
     loop {
         // Update cursor blink state
         app.update_cursor_blink();
+        app.poll_grep_results();
+        app.poll_flat_results();
+        if let Some(job) = &mut app.checksum {
+            job.poll();
+        }
+        app.poll_tails();
+        app.refresh_gutters();
+
+        if app.idle_quit_due() {
+            return Ok(());
+        }
 
-        terminal.draw(|f| ui(f, &mut app))?;
+        terminal.draw(|f| ui(f, app))?;
 
-        // Use poll to check for events with timeout for cursor blinking
-        if poll(std::time::Duration::from_millis(100))? {
+        // Wake up for input, or when something on screen needs to change
+        if poll(app.poll_timeout())? {
             match event::read()? {
                 Event::Key(key) => {
+                    app.last_key = std::time::Instant::now();
+                    if app.handle_overwrite_key(key)?
+                        || app.handle_prompt_key(key)?
+                        || app.handle_run_prompt_key(key)?
+                        || app.handle_open_prompt_key(key)
+                        || app.handle_shortcut_prompt_key(key)
+                        || app.handle_paste_prompt_key(key)
+                        || app.handle_delete_prompt_key(key)?
+                        || app.handle_transfer_key(key)?
+                        || app.handle_name_filter_key(key)?
+                        || app.handle_log_key(key)
+                        || app.handle_history_key(key)
+                        || app.handle_places_key(key)?
+                        || app.handle_checksum_key(key)
+                        || app.handle_filters_key(key)
+                        || app.handle_help_key(key)
+                        || app.handle_grep_key(key)
+                        || app.handle_search_key(key)
+                        || app.handle_terminal_view_key(key)
+                        || app.handle_pager_key(key)
+                    {
+                        continue;
+                    }
+
                     match key.code {
                         KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                             // Force exit - bypasses all modals and dialogs
@@ -3166,10 +8130,14 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                                 app.show_lsp_status = false;
                             } else if app.show_terminal {
                                 app.toggle_terminal()?;
-                            } else if app.tab_manager.has_tabs() {
+                            } else if app.browser_focused && app.tab_manager.has_tabs() {
+                                app.hide_file_pane();
+                            } else if app.editor_focused() {
                                 app.close_file();
                             } else if app.show_help {
                                 app.toggle_help();
+                            } else if key.code == KeyCode::Esc && !app.name_filter.is_empty() {
+                                app.clear_name_filter()?;
                             } else {
                                 return Ok(());
                             }
@@ -3185,7 +8153,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                                 // Don't navigate when confirmation is shown
                             } else if app.show_terminal {
                                 // In terminal mode, don't handle up/down
-                            } else if app.tab_manager.has_tabs() {
+                            } else if app.editor_focused() {
                                 app.handle_cursor_movement(CursorDirection::Up);
                             } else if !app.show_help {
                                 app.navigate_up();
@@ -3196,7 +8164,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                                 // Don't navigate when confirmation is shown
                             } else if app.show_terminal {
                                 // In terminal mode, don't handle up/down
-                            } else if app.tab_manager.has_tabs() {
+                            } else if app.editor_focused() {
                                 app.handle_cursor_movement(CursorDirection::Down);
                             } else if !app.show_help {
                                 app.navigate_down();
@@ -3207,7 +8175,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                                 // Don't navigate when confirmation is shown
                             } else if app.show_terminal {
                                 app.handle_terminal_input('k')?;
-                            } else if app.tab_manager.has_tabs() {
+                            } else if app.editor_focused() {
                                 // In tab editing mode, 'k' should be typed as a character
                                 app.handle_file_edit('k');
                                 // Trigger autocomplete for Go files
@@ -3229,7 +8197,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                                 // Don't navigate when confirmation is shown
                             } else if app.show_terminal {
                                 app.handle_terminal_input('j')?;
-                            } else if app.tab_manager.has_tabs() {
+                            } else if app.editor_focused() {
                                 // In tab editing mode, 'j' should be typed as a character
                                 app.handle_file_edit('j');
                                 // Trigger autocomplete for Go files
@@ -3249,9 +8217,11 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                         KeyCode::Enter => {
                             if app.show_unsaved_alert {
                                 // Don't handle enter when alert is shown
+                            } else if app.file_finder_mode {
+                                app.open_selected_file()?;
                             } else if app.show_terminal {
                                 app.handle_terminal_input('\n')?;
-                            } else if app.file_editing_mode {
+                            } else if app.file_editing_mode || app.editor_focused() {
                                 app.handle_file_edit('\n');
                             } else if !app.show_help && !app.show_file_content {
                                 if app.file_has_unsaved_changes {
@@ -3261,72 +8231,125 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                                 }
                             }
                         }
-                        KeyCode::Left => {
-                            if app.tab_manager.has_tabs()
-                                && !app.tab_manager.show_close_confirmation
-                            {
-                                app.handle_cursor_movement(CursorDirection::Left);
-                            }
+                        KeyCode::Left
+                            if app.editor_focused() && !app.tab_manager.show_close_confirmation =>
+                        {
+                            app.handle_cursor_movement(CursorDirection::Left);
                         }
-                        KeyCode::Right => {
-                            if app.tab_manager.has_tabs()
-                                && !app.tab_manager.show_close_confirmation
-                            {
-                                app.handle_cursor_movement(CursorDirection::Right);
+                        KeyCode::Right
+                            if app.editor_focused() && !app.tab_manager.show_close_confirmation =>
+                        {
+                            app.handle_cursor_movement(CursorDirection::Right);
+                        }
+                        KeyCode::Left | KeyCode::Right
+                            if !app.show_terminal
+                                && !app.show_help
+                                && !app.file_finder_mode
+                                && !app.command_palette_mode
+                                && !app.file_tree_mode =>
+                        {
+                            if key.code == KeyCode::Right {
+                                app.expand_selected();
+                            } else {
+                                app.collapse_selected();
                             }
                         }
+                        KeyCode::Char('a')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && !app.editor_focused()
+                                && !app.show_terminal =>
+                        {
+                            app.toggle_mark_all();
+                        }
+                        KeyCode::Char('d')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && !app.editor_focused()
+                                && !app.show_terminal =>
+                        {
+                            app.clear_marks();
+                        }
                         KeyCode::Char('a') => {
                             if app.tab_manager.show_close_confirmation {
                                 // Don't handle 'a' when confirmation is shown
                             } else if app.show_terminal {
                                 app.handle_terminal_input('a')?;
-                            } else if app.tab_manager.has_tabs() {
+                            } else if app.editor_focused() {
                                 app.handle_file_edit('a');
                             } else if !app.show_help {
                                 app.toggle_hidden()?;
                             }
                         }
+                        KeyCode::Char('h')
+                            if key.modifiers.contains(KeyModifiers::ALT) && !app.show_terminal =>
+                        {
+                            app.toggle_syntax_highlighting();
+                        }
                         KeyCode::Char('h') => {
                             if app.tab_manager.show_close_confirmation {
                                 // Don't handle 'h' when confirmation is shown
                             } else if app.show_terminal {
                                 app.handle_terminal_input('h')?;
-                            } else if app.tab_manager.has_tabs() {
+                            } else if app.editor_focused() {
                                 app.handle_file_edit('h');
                             } else {
                                 app.toggle_help();
                             }
                         }
-                        KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            if app.tab_manager.has_tabs()
+                        KeyCode::Char('f')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && app.editor_focused()
+                                && !app.tab_manager.show_close_confirmation =>
+                        {
+                            app.toggle_search();
+                        }
+                        KeyCode::Char('o')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
                                 && !app.tab_manager.show_close_confirmation
-                            {
-                                app.toggle_search();
-                            }
+                                && !app.editor_focused() =>
+                        {
+                            app.toggle_file_finder();
                         }
-                        KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            if !app.tab_manager.show_close_confirmation
-                                && !app.tab_manager.has_tabs()
-                            {
-                                app.toggle_file_finder();
-                            }
+                        KeyCode::Char('p')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && !app.tab_manager.show_close_confirmation =>
+                        {
+                            app.toggle_command_palette();
                         }
-                        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            if !app.tab_manager.show_close_confirmation {
-                                app.toggle_command_palette();
-                            }
+                        KeyCode::Char('g')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && !app.tab_manager.show_close_confirmation =>
+                        {
+                            app.toggle_grep();
                         }
-                        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            if app.tab_manager.has_tabs()
-                                && !app.tab_manager.show_close_confirmation
-                            {
-                                app.toggle_multi_cursor();
-                            }
+                        KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.toggle_log();
                         }
-                        KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            if !app.tab_manager.show_close_confirmation {
-                                app.toggle_terminal()?;
-                            }
+                        KeyCode::Char('b')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && app.tab_manager.has_tabs()
+                                && !app.show_terminal =>
+                        {
+                            app.toggle_browser_focus();
+                        }
+                        KeyCode::Char('r')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && app.editor_focused()
+                                && !app.tab_manager.show_close_confirmation =>
+                        {
+                            app.toggle_markdown_preview();
+                        }
+                        KeyCode::Char('d')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && app.editor_focused()
+                                && !app.tab_manager.show_close_confirmation =>
+                        {
+                            app.toggle_multi_cursor();
+                        }
+                        KeyCode::Char('t')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && !app.tab_manager.show_close_confirmation =>
+                        {
+                            app.toggle_terminal()?;
                         }
                         KeyCode::Tab => {
                             if key.modifiers.contains(KeyModifiers::CONTROL) {
@@ -3334,110 +8357,172 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                                 app.tab_manager.next_tab();
                             } else if app.show_completions {
                                 app.apply_completion();
-                            } else if app.tab_manager.has_tabs() {
+                            } else if app.editor_focused() {
                                 app.handle_file_edit('\t');
                             }
                         }
-                        KeyCode::BackTab => {
-                            if key.modifiers.contains(KeyModifiers::CONTROL) {
-                                // Ctrl+Shift+Tab: Switch to previous tab
-                                app.tab_manager.previous_tab();
-                            }
+                        KeyCode::BackTab if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            // Ctrl+Shift+Tab: Switch to previous tab
+                            app.tab_manager.previous_tab();
+                        }
+                        // Many terminals never send Ctrl+Tab, so these switch too
+                        KeyCode::PageDown if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.tab_manager.next_tab();
+                        }
+                        KeyCode::PageUp if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.tab_manager.previous_tab();
                         }
                         KeyCode::Char(' ') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            if app.tab_manager.has_tabs()
+                            if app.editor_focused()
                                 && !app.tab_manager.show_close_confirmation
+                                && let Some(tab) = app.tab_manager.get_active_tab()
                             {
-                                if let Some(tab) = app.tab_manager.get_active_tab() {
-                                    let path = tab.path.clone();
-                                    if LspClient::is_go_file(&path) {
-                                        // Show status and trigger autocomplete for Go files
-                                        if app.lsp_client.is_none() {
-                                            let rt = tokio::runtime::Runtime::new().unwrap();
-                                            let _ = rt.block_on(app.start_lsp_for_go());
-                                        }
+                                let path = tab.path.clone();
+                                if LspClient::is_go_file(&path) {
+                                    // Show status and trigger autocomplete for Go files
+                                    if app.lsp_client.is_none() {
+                                        let rt = tokio::runtime::Runtime::new().unwrap();
+                                        let _ = rt.block_on(app.start_lsp_for_go());
+                                    }
 
-                                        if let Some(ref lsp) = app.lsp_client {
-                                            if lsp.status == LspStatus::Running {
-                                                let rt = tokio::runtime::Runtime::new().unwrap();
-                                                let _ = rt.block_on(app.request_completions());
-                                                app.show_autocomplete();
-                                            } else {
-                                                // Show current LSP status
-                                                match &lsp.status {
-                                                    LspStatus::Failed(err) => {
-                                                        if err.contains("not found") {
-                                                            app.lsp_status_message = "❌ gopls not installed - Run: go install golang.org/x/tools/gopls@latest".to_string();
-                                                        } else {
-                                                            app.lsp_status_message =
-                                                                format!("❌ LSP Error: {}", err);
-                                                        }
-                                                    }
-                                                    LspStatus::Starting => {
+                                    if let Some(ref lsp) = app.lsp_client {
+                                        if lsp.status == LspStatus::Running {
+                                            let rt = tokio::runtime::Runtime::new().unwrap();
+                                            let _ = rt.block_on(app.request_completions());
+                                            app.show_autocomplete();
+                                        } else {
+                                            // Show current LSP status
+                                            match &lsp.status {
+                                                LspStatus::Failed(err) => {
+                                                    if err.contains("not found") {
+                                                        app.lsp_status_message = "❌ gopls not installed - Run: go install golang.org/x/tools/gopls@latest".to_string();
+                                                    } else {
                                                         app.lsp_status_message =
-                                                            "🟡 Starting Go LSP server..."
-                                                                .to_string();
-                                                    }
-                                                    _ => {
-                                                        app.lsp_status_message = "❌ Go LSP not ready - Check gopls installation".to_string();
+                                                            format!("❌ LSP Error: {}", err);
                                                     }
                                                 }
-                                                app.show_lsp_status = true;
+                                                LspStatus::Starting => {
+                                                    app.lsp_status_message =
+                                                        "🟡 Starting Go LSP server...".to_string();
+                                                }
+                                                _ => {
+                                                    app.lsp_status_message = "❌ Go LSP not ready - Check gopls installation".to_string();
+                                                }
                                             }
-                                        } else {
-                                            app.lsp_status_message =
-                                                "🟡 Starting Go LSP for first time...".to_string();
                                             app.show_lsp_status = true;
                                         }
+                                    } else {
+                                        app.lsp_status_message =
+                                            "🟡 Starting Go LSP for first time...".to_string();
+                                        app.show_lsp_status = true;
                                     }
                                 }
                             }
                         }
-                        KeyCode::F(3) => {
-                            if app.search_mode {
-                                if key.modifiers.contains(KeyModifiers::SHIFT) {
-                                    app.previous_search_match();
-                                } else {
-                                    app.next_search_match();
-                                }
+                        KeyCode::F(2)
+                            if !app.editor_focused() && !app.show_terminal && !app.show_help =>
+                        {
+                            app.start_rename();
+                        }
+                        KeyCode::F(7)
+                            if !app.editor_focused() && !app.show_terminal && !app.show_help =>
+                        {
+                            app.start_prompt(PromptKind::NewDirectory, String::new());
+                        }
+                        KeyCode::Delete
+                            if !app.editor_focused()
+                                && !app.show_terminal
+                                && !app.show_help
+                                && !app.file_finder_mode
+                                && !app.file_tree_mode =>
+                        {
+                            app.delete_selected();
+                        }
+                        KeyCode::F(6) if app.editor_focused() && !app.show_terminal => {
+                            app.focus_other_pane();
+                        }
+                        KeyCode::F(3) if !app.search_matches.is_empty() => {
+                            if key.modifiers.contains(KeyModifiers::SHIFT) {
+                                app.previous_search_match();
+                            } else {
+                                app.next_search_match();
                             }
                         }
                         KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            if app.show_file_content && app.file_editing_mode {
+                            if app.editor_focused()
+                                || (app.show_file_content && app.file_editing_mode)
+                            {
                                 app.save_file()?;
                             } else if app.show_unsaved_alert {
                                 app.save_file()?;
                                 app.actually_close_file();
                             }
                         }
-                        KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            if app.tab_manager.has_tabs() {
-                                app.revert_changes();
-                            }
+                        // Ctrl+] arrives as Ctrl+5 on many terminals
+                        KeyCode::Char(']' | '5')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && app.editor_focused()
+                                && !app.show_terminal =>
+                        {
+                            app.jump_to_matching_bracket();
                         }
-                        KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            if app.tab_manager.has_tabs() {
-                                app.close_file();
+                        // Ctrl+Shift+Z redoes too
+                        KeyCode::Char('z' | 'Z')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && app.editor_focused() =>
+                        {
+                            if key.modifiers.contains(KeyModifiers::SHIFT)
+                                || key.code == KeyCode::Char('Z')
+                            {
+                                app.redo();
+                            } else {
+                                app.undo();
                             }
                         }
-                        KeyCode::Char('y') => {
-                            if app.tab_manager.show_close_confirmation {
-                                app.tab_manager.confirm_close_tab();
-                            }
+                        KeyCode::Char('y')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && app.editor_focused()
+                                && !app.tab_manager.show_close_confirmation =>
+                        {
+                            app.redo();
                         }
-                        KeyCode::Char('n') => {
-                            if app.tab_manager.show_close_confirmation {
-                                app.tab_manager.cancel_close_tab();
-                            }
+                        KeyCode::Char('w')
+                            if key.modifiers.contains(KeyModifiers::ALT)
+                                && app.editor_focused()
+                                && !app.show_terminal =>
+                        {
+                            app.toggle_editor_wrap();
+                        }
+                        KeyCode::Char('r')
+                            if key.modifiers.contains(KeyModifiers::ALT)
+                                && app.tab_manager.has_tabs()
+                                && !app.show_terminal =>
+                        {
+                            app.reveal_active_file();
+                        }
+                        KeyCode::Char('w')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && app.editor_focused() =>
+                        {
+                            app.close_file();
+                        }
+                        KeyCode::Char('y') if app.tab_manager.show_close_confirmation => {
+                            app.tab_manager.confirm_close_tab();
                         }
-                        KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            // Edit mode toggle removed since tabs are always in edit mode
+                        KeyCode::Char('n') if app.tab_manager.show_close_confirmation => {
+                            app.tab_manager.cancel_close_tab();
+                        }
+                        KeyCode::Char('e')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && app.editor_focused() =>
+                        {
+                            app.toggle_edit_mode();
                         }
 
                         KeyCode::Char('d') => {
                             if app.tab_manager.show_close_confirmation {
                                 // 'd' doesn't do anything in close confirmation
-                            } else if app.tab_manager.has_tabs() {
+                            } else if app.editor_focused() {
                                 app.hide_autocomplete();
                                 app.handle_file_edit('d');
                             }
@@ -3446,18 +8531,26 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                             if app.tab_manager.show_close_confirmation {
                                 // Don't quit when confirmation is shown
                             } else if app.show_terminal {
-                                let _ = app.send_to_terminal("\u{3}"); // Send Ctrl+C to terminal
+                                app.signal_terminal(signals::TerminalSignal::Interrupt)?;
                             } else {
                                 return Ok(());
                             }
                         }
 
+                        // Terminals report Ctrl+\ as either key
+                        KeyCode::Char('\\' | '4')
+                            if key.modifiers.contains(KeyModifiers::CONTROL)
+                                && app.show_terminal =>
+                        {
+                            app.signal_terminal(signals::TerminalSignal::Quit)?;
+                        }
+
                         KeyCode::Backspace => {
                             if app.tab_manager.show_close_confirmation {
                                 // Don't handle backspace when confirmation is shown
                             } else if app.show_terminal {
                                 app.handle_terminal_input('\u{8}')?;
-                            } else if app.tab_manager.has_tabs() {
+                            } else if app.editor_focused() {
                                 app.hide_autocomplete();
                                 app.handle_file_edit('\u{8}');
                             }
@@ -3512,7 +8605,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                                 }
                             } else if app.show_terminal {
                                 app.handle_terminal_input(c)?;
-                            } else if app.tab_manager.has_tabs() {
+                            } else if app.editor_focused() {
                                 if c == '\n'
                                     && app.multi_cursor_mode
                                     && key.modifiers.contains(KeyModifiers::ALT)
@@ -3531,36 +8624,85 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                                     app.handle_file_edit(c);
 
                                     // Update LSP and trigger autocomplete for Go files
-                                    if let Some(tab) = app.tab_manager.get_active_tab() {
-                                        if LspClient::is_go_file(&tab.path) {
-                                            let rt = tokio::runtime::Runtime::new().unwrap();
-                                            let _ = rt.block_on(app.update_file_with_lsp());
+                                    if let Some(tab) = app.tab_manager.get_active_tab()
+                                        && LspClient::is_go_file(&tab.path)
+                                    {
+                                        let rt = tokio::runtime::Runtime::new().unwrap();
+                                        let _ = rt.block_on(app.update_file_with_lsp());
 
-                                            // Auto-trigger autocomplete on trigger characters or when typing
-                                            if is_trigger_char || c.is_alphabetic() {
-                                                let _ =
-                                                    rt.block_on(app.maybe_trigger_autocomplete());
-                                            }
+                                        // Auto-trigger autocomplete on trigger characters or when typing
+                                        if is_trigger_char || c.is_alphabetic() {
+                                            let _ = rt.block_on(app.maybe_trigger_autocomplete());
+                                        }
+                                    }
+                                }
+                            } else if !app.show_help
+                                && !key.modifiers.contains(KeyModifiers::CONTROL)
+                            {
+                                match c {
+                                    ' ' => app.toggle_mark(),
+                                    '?' => app.show_filters = true,
+                                    ':' => app.start_prompt(PromptKind::GoToName, String::new()),
+                                    '/' => app.start_name_filter()?,
+                                    'y' => app.copy_focused_path(false),
+                                    'Y' => app.copy_focused_path(true),
+                                    'R' => app.cycle_age_filter()?,
+                                    'A' => app.toggle_hidden_summary(),
+                                    'D' => app.duplicate_selected()?,
+                                    'p' => app.start_transfer(TransferKind::Copy)?,
+                                    'X' => app.start_transfer(TransferKind::Move)?,
+                                    'L' => app.start_symlink(),
+                                    'C' => app.start_send_to(TransferKind::Copy),
+                                    'K' => app.start_send_to(TransferKind::Move),
+                                    'F' => app.toggle_flat_mode()?,
+                                    'M' => app.heat_colors = !app.heat_colors,
+                                    'G' => app.toggle_group_by_type()?,
+                                    'I' => app.cycle_clutter()?,
+                                    'P' => app.open_places(),
+                                    'H' => app.open_checksum(),
+                                    'V' => app.paste_into_new_file(),
+                                    'i' => app.toggle_inodes(),
+                                    'B' => app.show_size_bars = !app.show_size_bars,
+                                    '*' => app.invert_marks(),
+                                    's' => app.sort_by(app.sort_mode.key.next())?,
+                                    'S' => app.reverse_sort()?,
+                                    'o' => app.cycle_dir_order()?,
+                                    'm' => app.set_position_mark(),
+                                    '`' => app.jump_to_position_mark()?,
+                                    '-' => app.swap_to_previous_dir()?,
+                                    'U' => app.open_as_untitled(),
+                                    'f' => app.follow_selected_file(),
+                                    ',' => app.edit_config(),
+                                    'O' => app.reveal_in_file_manager(),
+                                    'x' => app.show_attributes(),
+                                    'E' => app.start_prompt(
+                                        PromptKind::Export,
+                                        "listing.csv".to_string(),
+                                    ),
+                                    '1'..='4' => {
+                                        let slot = c as usize - '1' as usize;
+                                        if key.modifiers.contains(KeyModifiers::ALT) {
+                                            app.jump_to_pinned(slot)?;
+                                        } else {
+                                            app.pin_directory(slot);
                                         }
                                     }
+                                    // Other characters do nothing in the listing, which
+                                    // prevents accidental exits
+                                    _ => {}
                                 }
                             }
-                            // Don't handle other characters when not in terminal or edit mode
-                            // This prevents accidental exits
                         }
                         // Handle file finder navigation
                         _ if app.file_finder_mode => match key.code {
-                            KeyCode::Up => {
-                                if app.file_finder_selected > 0 {
-                                    app.file_finder_selected -= 1;
-                                }
+                            KeyCode::Up if app.file_finder_selected > 0 => {
+                                app.file_finder_selected -= 1;
                             }
-                            KeyCode::Down => {
+                            KeyCode::Down
                                 if app.file_finder_selected
-                                    < app.file_finder_results.len().saturating_sub(1)
-                                {
-                                    app.file_finder_selected += 1;
-                                }
+                                    < app.file_finder_results.len().saturating_sub(1) =>
+                            {
+                                app.file_finder_selected += 1;
                             }
                             KeyCode::Delete => {
                                 app.confirm_delete_file();
@@ -3568,17 +8710,14 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                             _ => {}
                         },
                         _ if app.file_tree_mode => match key.code {
-                            KeyCode::Up => {
-                                if app.file_tree_selected > 0 {
-                                    app.file_tree_selected -= 1;
-                                }
+                            KeyCode::Up if app.file_tree_selected > 0 => {
+                                app.file_tree_selected -= 1;
                             }
-                            KeyCode::Down => {
+                            KeyCode::Down
                                 if app.file_tree_selected
-                                    < app.file_tree_items.len().saturating_sub(1)
-                                {
-                                    app.file_tree_selected += 1;
-                                }
+                                    < app.file_tree_items.len().saturating_sub(1) =>
+                            {
+                                app.file_tree_selected += 1;
                             }
                             KeyCode::Enter => {
                                 app.open_selected_tree_item()?;
@@ -3589,17 +8728,14 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                             _ => {}
                         },
                         _ if app.command_palette_mode => match key.code {
-                            KeyCode::Up => {
-                                if app.command_palette_selected > 0 {
-                                    app.command_palette_selected -= 1;
-                                }
+                            KeyCode::Up if app.command_palette_selected > 0 => {
+                                app.command_palette_selected -= 1;
                             }
-                            KeyCode::Down => {
+                            KeyCode::Down
                                 if app.command_palette_selected
-                                    < app.command_palette_results.len().saturating_sub(1)
-                                {
-                                    app.command_palette_selected += 1;
-                                }
+                                    < app.command_palette_results.len().saturating_sub(1) =>
+                            {
+                                app.command_palette_selected += 1;
                             }
                             _ => {}
                         },
@@ -3609,9 +8745,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                 Event::Mouse(mouse) => {
                     match mouse.kind {
                         MouseEventKind::ScrollUp => {
-                            if app.tab_manager.has_tabs()
-                                && !app.tab_manager.show_close_confirmation
-                            {
+                            if app.editor_focused() && !app.tab_manager.show_close_confirmation {
                                 // Calculate the editor area bounds (same as centered_rect(85, 85, terminal_size))
                                 let terminal_size = terminal.size().unwrap_or_default();
                                 let popup_area = centered_rect(85, 85, terminal_size);
@@ -3626,7 +8760,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                                     app.handle_cursor_movement(CursorDirection::Up);
                                 }
                             } else if !app.show_help
-                                && !app.tab_manager.has_tabs()
+                                && !app.editor_focused()
                                 && !app.file_finder_mode
                             {
                                 // In file browser, scroll anywhere in the main area
@@ -3651,7 +8785,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                                     app.handle_cursor_movement(CursorDirection::Down);
                                 }
                             } else if !app.show_help
-                                && !app.tab_manager.has_tabs()
+                                && !app.editor_focused()
                                 && !app.file_finder_mode
                             {
                                 // In file browser, scroll anywhere in the main area
@@ -3670,7 +8804,22 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                             app.last_click_time = now;
                             app.last_click_position = (mouse.column, mouse.row);
 
-                            if app.tab_manager.has_tabs()
+                            if !app.show_help
+                                && !app.file_finder_mode
+                                && !app.terminal_maximized
+                                && !app.tab_manager.show_close_confirmation
+                                && let Some(&(area, index)) =
+                                    app.minimap_hits.iter().find(|(area, _)| {
+                                        (area.x..area.x + area.width).contains(&mouse.column)
+                                            && (area.y..area.y + area.height).contains(&mouse.row)
+                                    })
+                            {
+                                app.minimap_jump(
+                                    index,
+                                    (mouse.row - area.y) as usize,
+                                    area.height as usize,
+                                );
+                            } else if app.editor_focused()
                                 && !app.tab_manager.show_close_confirmation
                             {
                                 // Handle mouse click in editor - position cursor
@@ -3696,6 +8845,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
 
                                         if target_line < lines.len() {
                                             tab.cursor_line = target_line;
+                                            tab.undo.break_run();
 
                                             // Account for line numbers in the display
                                             let line_number_width =
@@ -3710,31 +8860,37 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                                         }
                                     }
                                 }
-                            } else if !app.tab_manager.has_tabs()
+                            } else if !app.editor_focused()
                                 && !app.show_help
                                 && !app.file_finder_mode
                                 && !app.show_terminal
                                 && !app.tab_manager.show_close_confirmation
+                                && let Some(&(_, _, key)) =
+                                    app.sort_header.iter().find(|(row, columns, _)| {
+                                        *row == mouse.row && columns.contains(&mouse.column)
+                                    })
                             {
-                                // Handle mouse click in file browser - select file
-                                let terminal_size = terminal.size().unwrap_or_default();
-
-                                // Calculate the main content area (excluding terminal if shown)
-                                let main_area_height = if app.show_terminal {
-                                    terminal_size.height.saturating_sub(12) // Reserve space for terminal
-                                } else {
-                                    terminal_size.height.saturating_sub(3) // Reserve space for footer
-                                };
-
-                                // Check if click is in the file list area (roughly)
-                                if mouse.row >= 2 && mouse.row < main_area_height {
-                                    // Calculate which file was clicked based on row
-                                    let clicked_row = mouse.row.saturating_sub(2) as usize;
-
-                                    // Account for scrolling offset if any
-                                    let target_index = clicked_row;
+                                app.sort_by(key)?;
+                            } else if !app.editor_focused()
+                                && !app.show_help
+                                && !app.file_finder_mode
+                                && !app.show_terminal
+                                && !app.tab_manager.show_close_confirmation
+                            {
+                                // Handle mouse click in file browser - select file,
+                                // inside the borders of where the listing was drawn
+                                if let Some(area) = app.file_list_area
+                                    && mouse.row > area.y
+                                    && mouse.row + 1 < area.y + area.height
+                                    && mouse.column > area.x
+                                    && mouse.column + 1 < area.x + area.width
+                                {
+                                    // Calculate which file was clicked based on row,
+                                    // past the rows scrolled away and any group headers
+                                    let clicked_row =
+                                        (mouse.row - area.y - 1) as usize + app.list_state.offset();
 
-                                    if target_index < app.files.len() {
+                                    if let Some(target_index) = app.entry_at_list_row(clicked_row) {
                                         // If double-click on same file, open it
                                         if is_double_click && target_index == app.selected_index {
                                             let _ = app.enter_directory();
@@ -3753,6 +8909,7 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
                         _ => {}
                     }
                 }
+                Event::Paste(text) => app.handle_paste(&text)?,
                 _ => {}
             }
         }
@@ -3760,22 +8917,51 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> AppResult<()
 }
 
 fn print_simple_list(app: &App) {
-    println!("📁 Directory: {}", app.current_path.display());
+    let terminal_width = crossterm::terminal::size()
+        .map(|(width, _)| width as usize)
+        .unwrap_or(80);
+    let name_width = app.name_column_width(terminal_width);
+
+    println!(
+        "📁 Directory: {}",
+        app.display_path(&app.current_path).display()
+    );
     println!("{}", "─".repeat(80));
 
     for file in &app.files {
-        let icon = file.get_icon();
-        let size_str = FileItem::format_size(file.size, app.human_readable);
-        let date_str = file.format_date();
-
-        println!(
-            "{} {:30} {:>10} {} {}",
-            icon, file.name, size_str, file.permissions, date_str
-        );
+        let row = file.format_row(name_width, app.human_readable, app.size_units);
+        if app.show_inodes {
+            println!("{} {}", row, file.format_links());
+        } else {
+            println!("{}", row);
+        }
     }
 
     println!("{}", "─".repeat(80));
     println!("Total files: {}", app.files.len());
+    println!("{}", app.total_size_label());
+}
+
+/// Rewrites `+N` arguments to `--line=N`, the `vim +N` / `less +N` spelling,
+/// unless a file by that name `exists`.
+fn expand_line_shorthand(
+    args: impl IntoIterator<Item = std::ffi::OsString>,
+    exists: impl Fn(&Path) -> bool,
+) -> Vec<std::ffi::OsString> {
+    args.into_iter()
+        .map(
+            |arg| match arg.to_str().and_then(|arg| arg.strip_prefix('+')) {
+                Some(line)
+                    if !line.is_empty()
+                        && line.chars().all(|c| c.is_ascii_digit())
+                        && !exists(Path::new(&arg)) =>
+                {
+                    format!("--line={}", line).into()
+                }
+                _ => arg,
+            },
+        )
+        .collect()
 }
 
 fn main() -> AppResult<()> {
@@ -3788,13 +8974,15 @@ fn main() -> AppResult<()> {
         }
     }
 
-    let args = Args::parse();
+    let args = Args::parse_from(expand_line_shorthand(std::env::args_os(), Path::exists));
+    pdf::install_panic_hook();
 
-    // Resolve the path
-    let path = if args.path.is_absolute() {
-        args.path
+    // Resolve the path; a quoted `~` never reached the shell
+    let path = paths::expand_home(&args.path);
+    let path = if path.is_absolute() {
+        path
     } else {
-        std::env::current_dir()?.join(args.path)
+        std::env::current_dir()?.join(path)
     };
 
     if !path.exists() {
@@ -3802,13 +8990,91 @@ fn main() -> AppResult<()> {
         std::process::exit(1);
     }
 
+    // A file starts the browser in its directory with the file open
+    let (path, file_name) = match (path.is_file(), path.parent(), path.file_name()) {
+        (true, Some(parent), Some(name)) => (parent.to_path_buf(), Some(name.to_os_string())),
+        _ => (path, None),
+    };
+    if args.line.is_some() && file_name.is_none() {
+        eprintln!("Error: --line needs a file to open");
+        std::process::exit(1);
+    }
+
     if !path.is_dir() {
         eprintln!("Error: Path '{}' is not a directory", path.display());
         std::process::exit(1);
     }
 
+    // Browse the real directory, but keep showing the path as typed so a
+    // symlinked /var doesn't turn into /private/var in the header
+    let typed_path = normalize_lexically(&path);
+    let path = path.canonicalize()?;
+    let path_alias = (typed_path != path
+        && typed_path.canonicalize().is_ok_and(|real| real == path))
+    .then(|| (path.clone(), typed_path));
+
+    // Compare canonical paths so symlinks and `..` can't slip past the root
+    let (path, root) = match args.root {
+        Some(root) => {
+            let root = std::env::current_dir()?
+                .join(paths::expand_home(&root))
+                .canonicalize()?;
+            if !path.starts_with(&root) {
+                eprintln!(
+                    "Error: Path '{}' is outside of root '{}'",
+                    path.display(),
+                    root.display()
+                );
+                std::process::exit(1);
+            }
+            (path, Some(root))
+        }
+        None => (path, None),
+    };
+
+    // Flags on the command line win over the config file
+    let mut config = Config::load();
+    config.show_hidden |= args.all;
+    config.human_readable |= args.human_readable || args.si;
+    if let Some(key) = args.sort {
+        config.sort = key;
+    }
+    config.reverse_sort |= args.reverse;
+    config.git &= !args.no_git;
+
     // Create app
-    let app = App::new(path, args.all, args.human_readable)?;
+    let mut app = App::new(path, config)?;
+    app.path_alias = path_alias;
+    app.name_min_width = args.name_min_width;
+    app.name_max_width = args.name_max_width;
+    app.view_only = args.view_only;
+    app.idle_timeout = args.idle_timeout.map(std::time::Duration::from_secs);
+    app.idle_force = args.idle_force;
+    app.show_inodes = args.inode && cfg!(unix);
+    if root.is_some() || args.newer_than.is_some() {
+        // Reload so the listing honours the root and the age filter
+        app.root = root;
+        app.newer_than = args.newer_than;
+        app.load_directory()?;
+    }
+    app.frecency_path = frecency::Frecency::path();
+    if let Some(store) = &app.frecency_path {
+        app.frecency = frecency::Frecency::load_from(store);
+    }
+    let (theme_set, theme_errors) = themes::load(app.config.theme_dir.as_deref());
+    app.theme_set = theme_set;
+    for error in theme_errors {
+        app.report_error(error);
+    }
+    app.use_configured_theme();
+    // --si implies human readable sizes, like GNU ls
+    if args.si {
+        app.size_units = SizeUnits::Si;
+    }
+
+    if args.edit_config {
+        app.edit_config();
+    }
 
     if args.list {
         // Simple list mode
@@ -3816,12 +9082,41 @@ fn main() -> AppResult<()> {
         return Ok(());
     }
 
+    if args.server {
+        return server::run(app);
+    }
+
+    if let Some(format) = args.export {
+        let listing = export::render(&app, format);
+        match args.output {
+            Some(output) => fs::write(output, listing)?,
+            None => print!("{}", listing),
+        }
+        return Ok(());
+    }
+
+    if let Some(file_name) = file_name {
+        let file = app.current_path.join(file_name);
+        app.reveal_path(&file)?;
+        if let Some(line) = args.line {
+            app.goto_line(line.saturating_sub(1));
+        }
+    }
+
     // Setup terminal for TUI mode
+    let alt_screen = !args.no_alt_screen;
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    if alt_screen {
+        execute!(stdout, EnterAlternateScreen)?;
+    }
+    execute!(stdout, EnableMouseCapture, EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
+    if !alt_screen {
+        // Whatever the shell printed would otherwise show through blank cells
+        terminal.clear()?;
+    }
 
     // Run TUI
     let res = run_app(&mut terminal, app);
@@ -3830,9 +9125,17 @@ fn main() -> AppResult<()> {
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
+    if alt_screen {
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    } else {
+        // Leave the last frame in place and put the prompt below it
+        let size = terminal.size()?;
+        terminal.set_cursor(0, size.height.saturating_sub(1))?;
+        println!();
+    }
     terminal.show_cursor()?;
 
     if let Err(err) = res {
@@ -3841,3 +9144,366 @@ fn main() -> AppResult<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use tempfile::TempDir;
+
+    /// An app listing an empty scratch directory, dropped with the `TempDir`.
+    fn scratch_app() -> (TempDir, App) {
+        let temp = tempfile::tempdir().unwrap();
+        let app = App::new(temp.path().to_path_buf(), Config::default()).unwrap();
+        (temp, app)
+    }
+
+    /// The rows `draw` leaves on a `width` by `height` screen, as text.
+    fn screen(width: u16, height: u16, draw: impl FnOnce(&mut Frame)) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let buffer = terminal.draw(draw).unwrap().buffer.clone();
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buffer.get(x, y).symbol.clone())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_a_symlink_out_of_the_root_is_outside_it() {
+        let (temp, mut app) = scratch_app();
+        fs::create_dir_all(temp.path().join("root/inside")).unwrap();
+        let root = temp.path().join("root").canonicalize().unwrap();
+        fs::create_dir(temp.path().join("outside")).unwrap();
+        std::os::unix::fs::symlink(temp.path().join("outside"), root.join("escape")).unwrap();
+        app.root = Some(root.clone());
+        app.current_path = root.clone();
+        app.load_directory().unwrap();
+
+        assert!(app.is_within_root(&root.join("inside")));
+        assert!(app.is_within_root(&root.join("new.txt")));
+        assert!(!app.is_within_root(&root.join("escape")));
+        assert!(!app.is_within_root(&root.join("escape/new.txt")));
+        assert!(!app.is_within_root(&root.join("..")));
+
+        assert!(app.select_entry(&root.join("escape")));
+        app.enter_directory().unwrap();
+        assert_eq!(app.current_path, root);
+    }
+
+    #[test]
+    fn test_recent_files_open_like_the_listing_and_stay_in_the_root() {
+        let (temp, mut app) = scratch_app();
+        let root = temp.path().canonicalize().unwrap().join("root");
+        fs::create_dir(&root).unwrap();
+        let outside = temp.path().canonicalize().unwrap().join("secret.txt");
+        fs::write(&outside, "secret").unwrap();
+        let launcher = root.join("app.desktop");
+        fs::write(
+            &launcher,
+            "[Desktop Entry]\nType=Link\nURL=https://example.com\n",
+        )
+        .unwrap();
+        app.root = Some(root);
+
+        app.history = Some(vec![(outside, 0)]);
+        app.open_history_entry();
+        assert!(!app.tab_manager.has_tabs());
+
+        // A launcher gets the shortcut view, not its raw text in an editor
+        app.history = Some(vec![(launcher, 0)]);
+        app.open_history_entry();
+        let tab = app.tab_manager.get_active_tab().unwrap();
+        assert_eq!(tab.name, "app.desktop (shortcut)");
+        assert!(tab.generated);
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> bool {
+        app.handle_name_filter_key(KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_name_filter_narrows_what_was_read_and_esc_clears_a_kept_one() {
+        let (temp, mut app) = scratch_app();
+        for name in ["alpha.txt", "beta.txt", "alps.md"] {
+            fs::write(temp.path().join(name), "").unwrap();
+        }
+        app.refresh_files().unwrap();
+        let names =
+            |app: &App| -> Vec<String> { app.files.iter().map(|file| file.name.clone()).collect() };
+
+        app.start_name_filter().unwrap();
+        // Only the first read counts: a file created now doesn't show up
+        fs::write(temp.path().join("alpine.txt"), "").unwrap();
+        press(&mut app, KeyCode::Char('a'));
+        press(&mut app, KeyCode::Char('l'));
+        assert_eq!(names(&app), ["..", "alpha.txt", "alps.md"]);
+        press(&mut app, KeyCode::Char('p'));
+        press(&mut app, KeyCode::Char('h'));
+        assert_eq!(names(&app), ["..", "alpha.txt"]);
+        assert_eq!(app.filter_counts.unwrap().name, 2);
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(names(&app), ["..", "alpha.txt", "alps.md"]);
+
+        // Kept after Enter, the filter no longer takes keys
+        press(&mut app, KeyCode::Enter);
+        assert!(!press(&mut app, KeyCode::Esc));
+        assert_eq!(app.name_filter, "alp");
+        app.clear_name_filter().unwrap();
+        assert!(app.name_filter.is_empty());
+        assert_eq!(app.files.len(), 5);
+    }
+
+    #[test]
+    fn test_only_y_follows_a_shortcut() {
+        let (_temp, mut app) = scratch_app();
+        let listed = app.current_path.clone();
+        let target = || Some(shortcut::Target::Path(PathBuf::from("/")));
+
+        app.pending_shortcut = target();
+        assert!(app.handle_shortcut_prompt_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)));
+        assert!(app.pending_shortcut.is_none());
+        assert_eq!(app.current_path, listed);
+
+        app.pending_shortcut = target();
+        app.handle_shortcut_prompt_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
+        assert_eq!(app.current_path, Path::new("/"));
+    }
+
+    #[test]
+    fn test_clicked_rows_skip_group_headers() {
+        let (temp, mut app) = scratch_app();
+        fs::create_dir(temp.path().join("sub")).unwrap();
+        fs::write(temp.path().join("main.rs"), "").unwrap();
+        fs::write(temp.path().join("logo.png"), "").unwrap();
+        app.group_by_type = true;
+        app.refresh_files().unwrap();
+
+        // .., Directories header, sub, Source header, main.rs, Images
+        // header, logo.png
+        let rows: Vec<Option<usize>> = (0..8).map(|row| app.entry_at_list_row(row)).collect();
+        assert_eq!(
+            rows,
+            [Some(0), None, Some(1), None, Some(2), None, Some(3), None]
+        );
+        assert_eq!(app.files[2].name, "main.rs");
+    }
+
+    #[test]
+    fn test_bulk_deletes_and_moves_ask_for_the_phrase_unless_it_is_empty() {
+        let (temp, mut app) = scratch_app();
+        for name in ["a", "b", "c"] {
+            fs::write(temp.path().join(name), "").unwrap();
+        }
+        app.load_directory().unwrap();
+        app.config.bulk_confirm_threshold = 2;
+        app.marked_paths = ["a", "b", "c"]
+            .iter()
+            .map(|name| temp.path().join(name))
+            .collect();
+
+        app.delete_selected();
+        assert!(matches!(
+            app.prompt.take().map(|prompt| prompt.kind),
+            Some(PromptKind::ConfirmDelete { .. })
+        ));
+        app.start_transfer(TransferKind::Move).unwrap();
+        assert!(matches!(
+            app.prompt.take().map(|prompt| prompt.kind),
+            Some(PromptKind::ConfirmMove { .. })
+        ));
+
+        app.config.bulk_confirm_phrase.clear();
+        app.delete_selected();
+        assert!(app.prompt.is_none());
+        assert_eq!(app.pending_delete.as_ref().map(Vec::len), Some(3));
+    }
+
+    #[test]
+    fn test_marked_size_counts_files_but_not_directories() {
+        let (temp, mut app) = scratch_app();
+        fs::write(temp.path().join("five"), "12345").unwrap();
+        fs::create_dir(temp.path().join("dir")).unwrap();
+        fs::write(temp.path().join("dir/inner"), "x".repeat(100)).unwrap();
+        app.load_directory().unwrap();
+        app.marked_paths = ["five", "dir"]
+            .iter()
+            .map(|name| temp.path().join(name))
+            .collect();
+
+        app.update_marked_size();
+        assert_eq!((app.marked_size, app.marked_dirs), (5, 1));
+    }
+
+    #[test]
+    fn test_line_shorthand_leaves_existing_files_alone() {
+        let args = ["ls-pretty", "notes.txt", "+12", "+x", "+7"].map(std::ffi::OsString::from);
+        let expanded = expand_line_shorthand(args, |path| path == Path::new("+7"));
+        assert_eq!(
+            expanded,
+            ["ls-pretty", "notes.txt", "--line=12", "+x", "+7"].map(std::ffi::OsString::from)
+        );
+    }
+
+    #[test]
+    fn test_an_untitled_copy_keeps_the_lossy_flag() {
+        let (temp, mut app) = scratch_app();
+        fs::write(temp.path().join("broken.txt"), b"\xEF\xBB\xBFok \xFF").unwrap();
+        app.load_directory().unwrap();
+        assert!(app.select_entry(&temp.path().join("broken.txt")));
+
+        app.open_as_untitled();
+        let tab = app.tab_manager.get_active_tab().unwrap();
+        assert!(tab.is_untitled());
+        assert!(tab.lossy);
+    }
+
+    #[test]
+    fn test_stale_entries_say_why_and_have_no_date() {
+        let not_found = io::Error::from(io::ErrorKind::NotFound);
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert!(StaleReason::from_error(&not_found) == StaleReason::Vanished);
+        assert!(StaleReason::from_error(&denied) == StaleReason::Unreadable);
+
+        let item = FileItem::stale(
+            "gone.txt".to_string(),
+            PathBuf::from("/nowhere/gone.txt"),
+            StaleReason::Vanished,
+        );
+        let row = item.format_row_without_date(30, true, SizeUnits::Binary);
+        assert!(row.contains("gone.txt (stale)"), "{}", row);
+        assert_eq!(item.format_date(), "Unknown");
+    }
+
+    #[test]
+    fn test_paths_under_a_symlinked_start_show_as_typed() {
+        let (_temp, mut app) = scratch_app();
+        app.path_alias = Some((PathBuf::from("/private/var"), PathBuf::from("/var")));
+        assert_eq!(
+            app.display_path(Path::new("/private/var")),
+            Path::new("/var")
+        );
+        assert_eq!(
+            app.display_path(Path::new("/private/var/log")),
+            Path::new("/var/log")
+        );
+        assert_eq!(app.display_path(Path::new("/etc")), Path::new("/etc"));
+
+        assert_eq!(
+            normalize_lexically(Path::new("/a/./b/../c")),
+            Path::new("/a/c")
+        );
+    }
+
+    #[test]
+    fn test_position_mark_returns_to_its_directory_and_row() {
+        let (_temp, mut app) = scratch_app();
+        let base = app.current_path.clone();
+        for name in ["a", "b", "c"] {
+            fs::write(base.join(name), "").unwrap();
+        }
+        fs::create_dir(base.join("sub")).unwrap();
+        app.load_directory().unwrap();
+        assert!(app.select_entry(&base.join("b")));
+        app.set_position_mark();
+
+        app.current_path = base.join("sub");
+        app.refresh_files().unwrap();
+        app.jump_to_position_mark().unwrap();
+        assert_eq!(app.current_path, base);
+        assert_eq!(app.files[app.selected_index].path, base.join("b"));
+    }
+
+    #[test]
+    fn test_dash_swaps_between_the_last_two_directories() {
+        let (_temp, mut app) = scratch_app();
+        let base = app.current_path.clone();
+        fs::create_dir(base.join("sub")).unwrap();
+        app.swap_to_previous_dir().unwrap();
+        assert_eq!(app.current_path, base);
+
+        app.current_path = base.join("sub");
+        app.refresh_files().unwrap();
+        app.swap_to_previous_dir().unwrap();
+        assert_eq!(app.current_path, base);
+        app.swap_to_previous_dir().unwrap();
+        assert_eq!(app.current_path, base.join("sub"));
+    }
+
+    #[test]
+    fn test_inverting_marks_flips_only_listed_entries() {
+        let (_temp, mut app) = scratch_app();
+        let base = app.current_path.clone();
+        for name in ["a", "b"] {
+            fs::write(base.join(name), "").unwrap();
+        }
+        app.load_directory().unwrap();
+        let elsewhere = PathBuf::from("/elsewhere/kept");
+        app.marked_paths = [base.join("a"), elsewhere.clone()].into_iter().collect();
+
+        app.invert_marks();
+        let expected: HashSet<PathBuf> = [base.join("b"), elsewhere].into_iter().collect();
+        assert_eq!(app.marked_paths, expected);
+    }
+
+    #[test]
+    fn test_go_to_name_prefers_an_exact_name_over_a_prefix() {
+        let (_temp, mut app) = scratch_app();
+        let base = app.current_path.clone();
+        for name in ["Makefile", "main", "main.rs", "notes"] {
+            fs::write(base.join(name), "").unwrap();
+        }
+        app.load_directory().unwrap();
+        let selected = |app: &App| app.files[app.selected_index].name.clone();
+
+        app.go_to_name("main");
+        assert_eq!(selected(&app), "main");
+        app.go_to_name("makefile");
+        assert_eq!(selected(&app), "Makefile");
+        app.go_to_name("no");
+        assert_eq!(selected(&app), "notes");
+        app.go_to_name("zzz");
+        assert_eq!(selected(&app), "notes");
+    }
+
+    #[test]
+    fn test_delete_without_marks_asks_about_the_selected_entry() {
+        let (_temp, mut app) = scratch_app();
+        let base = app.current_path.clone();
+        fs::write(base.join("only"), "").unwrap();
+        app.load_directory().unwrap();
+
+        assert!(app.select_entry(&base.join("only")));
+        app.delete_selected();
+        assert_eq!(app.pending_delete.take(), Some(vec![base.join("only")]));
+
+        app.selected_index = app.files.iter().position(|file| file.name == "..").unwrap();
+        app.delete_selected();
+        assert!(app.pending_delete.is_none());
+        assert!(base.join("only").exists());
+    }
+
+    #[test]
+    fn test_scrolled_text_stays_beside_the_gutter() {
+        let (_temp, mut app) = scratch_app();
+        app.editor_wrap = false;
+        let path = app.current_path.join("wide.txt");
+        app.tab_manager
+            .add_tab("wide.txt".to_string(), path, "abcdefghij".to_string());
+        app.tab_manager
+            .get_active_tab_mut()
+            .unwrap()
+            .horizontal_scroll = 3;
+
+        let tab = app.tab_manager.get_active_tab().unwrap();
+        let rows = screen(20, 3, |f| {
+            render_tab_pane(f, &app, tab, f.size(), false);
+        });
+        // Number, change-marker gap, then the text from its fourth column
+        assert!(rows[1].starts_with("│  1 defghij"), "{:?}", rows[1]);
+    }
+}
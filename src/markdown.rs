@@ -0,0 +1,279 @@
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use std::path::Path;
+
+pub fn is_markdown_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| {
+            matches!(
+                ext.to_lowercase().as_str(),
+                "md" | "markdown" | "mdown" | "mkd"
+            )
+        })
+        .unwrap_or(false)
+}
+
+/// Renders Markdown into styled lines for the preview pane. This is a reading
+/// aid rather than a faithful layout engine: wrapping is left to the widget.
+pub fn render(source: &str) -> Vec<Line<'static>> {
+    let mut renderer = Renderer::default();
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    for event in Parser::new_ext(source, options) {
+        renderer.handle(event);
+    }
+    renderer.finish()
+}
+
+#[derive(Default)]
+struct Renderer {
+    lines: Vec<Line<'static>>,
+    current: Vec<Span<'static>>,
+    styles: Vec<Style>,
+    // One entry per open list: the next number for ordered lists
+    lists: Vec<Option<u64>>,
+    quote_depth: usize,
+    in_code_block: bool,
+}
+
+impl Renderer {
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.in_code_block => {
+                for line in text.lines() {
+                    self.current.push(Span::styled(
+                        format!("│ {}", line),
+                        Style::default().fg(Color::Yellow),
+                    ));
+                    self.flush_line();
+                }
+            }
+            Event::Text(text) => self.push_text(text.to_string()),
+            Event::Code(code) => {
+                let style = self.style().fg(Color::Yellow).bg(Color::DarkGray);
+                self.current.push(Span::styled(code.to_string(), style));
+            }
+            Event::SoftBreak => self.push_text(" ".to_string()),
+            Event::HardBreak => self.flush_line(),
+            Event::Rule => {
+                self.flush_line();
+                self.lines.push(Line::from(Span::styled(
+                    "─".repeat(40),
+                    Style::default().fg(Color::DarkGray),
+                )));
+                self.blank_line();
+            }
+            Event::TaskListMarker(done) => {
+                self.push_text(if done { "[x] " } else { "[ ] " }.to_string());
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                let style = Style::default().fg(Color::DarkGray);
+                self.current
+                    .push(Span::styled(html.trim_end().to_string(), style));
+            }
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                self.flush_line();
+                let style = match level {
+                    HeadingLevel::H1 => Style::default()
+                        .fg(Color::Magenta)
+                        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                    HeadingLevel::H2 => Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                    _ => Style::default()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::BOLD),
+                };
+                self.styles.push(style);
+            }
+            Tag::BlockQuote(_) => {
+                self.flush_line();
+                self.quote_depth += 1;
+                self.styles.push(
+                    Style::default()
+                        .fg(Color::Gray)
+                        .add_modifier(Modifier::ITALIC),
+                );
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush_line();
+                self.in_code_block = true;
+                if let CodeBlockKind::Fenced(lang) = kind
+                    && !lang.is_empty()
+                {
+                    self.lines.push(Line::from(Span::styled(
+                        format!("┌ {}", lang),
+                        Style::default().fg(Color::DarkGray),
+                    )));
+                }
+            }
+            Tag::List(start) => {
+                self.flush_line();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush_line();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        let marker = format!("{}{}. ", indent, number);
+                        *number += 1;
+                        marker
+                    }
+                    _ => format!("{}• ", indent),
+                };
+                self.current
+                    .push(Span::styled(marker, Style::default().fg(Color::Cyan)));
+            }
+            Tag::Emphasis => self
+                .styles
+                .push(self.style().add_modifier(Modifier::ITALIC)),
+            Tag::Strong => self.styles.push(self.style().add_modifier(Modifier::BOLD)),
+            Tag::Strikethrough => self
+                .styles
+                .push(self.style().add_modifier(Modifier::CROSSED_OUT)),
+            Tag::Link { .. } => self.styles.push(
+                self.style()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::UNDERLINED),
+            ),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Heading(_) => {
+                self.styles.pop();
+                self.flush_line();
+                self.blank_line();
+            }
+            TagEnd::Paragraph => {
+                self.flush_line();
+                // Paragraphs inside list items stay tight
+                if self.lists.is_empty() {
+                    self.blank_line();
+                }
+            }
+            TagEnd::BlockQuote(_) => {
+                self.styles.pop();
+                self.flush_line();
+                self.quote_depth -= 1;
+            }
+            TagEnd::CodeBlock => {
+                self.in_code_block = false;
+                self.blank_line();
+            }
+            TagEnd::List(_) => {
+                self.flush_line();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank_line();
+                }
+            }
+            TagEnd::Item => self.flush_line(),
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link => {
+                self.styles.pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn style(&self) -> Style {
+        self.styles.last().copied().unwrap_or_default()
+    }
+
+    fn push_text(&mut self, text: String) {
+        let style = self.style();
+        self.current.push(Span::styled(text, style));
+    }
+
+    fn flush_line(&mut self) {
+        if self.current.is_empty() {
+            return;
+        }
+        let mut spans = Vec::new();
+        if self.quote_depth > 0 {
+            spans.push(Span::styled(
+                "▎ ".repeat(self.quote_depth),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        spans.append(&mut self.current);
+        self.lines.push(Line::from(spans));
+    }
+
+    fn blank_line(&mut self) {
+        if self.lines.last().is_some_and(|line| !line.spans.is_empty()) {
+            self.lines.push(Line::default());
+        }
+    }
+
+    fn finish(mut self) -> Vec<Line<'static>> {
+        self.flush_line();
+        while self.lines.last().is_some_and(|line| line.spans.is_empty()) {
+            self.lines.pop();
+        }
+        self.lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_headings_lists_and_code() {
+        let source =
+            "# Title\n\n- one\n- two\n\n1. first\n2. second\n\n```rust\nfn main() {}\n```\n";
+        assert_eq!(
+            plain(&render(source)),
+            vec![
+                "Title",
+                "",
+                "• one",
+                "• two",
+                "",
+                "1. first",
+                "2. second",
+                "",
+                "┌ rust",
+                "│ fn main() {}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline_styles() {
+        let lines = render("Some **bold** and [a link](https://example.com).");
+        let spans = &lines[0].spans;
+        assert!(spans[1].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(spans[3].content, "a link");
+        assert!(spans[3].style.add_modifier.contains(Modifier::UNDERLINED));
+    }
+
+    #[test]
+    fn test_markdown_extensions() {
+        assert!(is_markdown_path(Path::new("README.md")));
+        assert!(is_markdown_path(Path::new("notes.Markdown")));
+        assert!(!is_markdown_path(Path::new("main.rs")));
+    }
+}
@@ -0,0 +1,201 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mkv", "avi", "mov", "webm", "m4v"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaInfo {
+    pub width: u32,
+    pub height: u32,
+    /// Frame count where it's cheap to know (GIFs)
+    pub frames: Option<usize>,
+    pub duration: Option<Duration>,
+}
+
+impl MediaInfo {
+    pub fn describe(&self, path: &Path) -> String {
+        let mut text = format!(
+            "{}\n\nResolution: {}x{}\n",
+            path.display(),
+            self.width,
+            self.height
+        );
+        if let Some(frames) = self.frames {
+            text.push_str(&format!("Frames:     {}\n", frames));
+        }
+        if let Some(duration) = self.duration {
+            text.push_str(&format!("Duration:   {:.2}s\n", duration.as_secs_f64()));
+        }
+        text
+    }
+}
+
+pub fn is_media(path: &Path) -> bool {
+    let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+    };
+    let extension = extension.to_lowercase();
+    extension == "gif" || VIDEO_EXTENSIONS.contains(&extension.as_str())
+}
+
+/// Whether `ffprobe` can be run, checked once at startup.
+pub fn ffprobe_available() -> bool {
+    Command::new("ffprobe")
+        .arg("-version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Reads what's known about a GIF or video. GIFs are parsed directly;
+/// videos need `ffprobe`.
+pub fn probe(path: &Path, use_ffprobe: bool) -> Result<MediaInfo, String> {
+    let is_gif = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("gif"));
+    if is_gif {
+        let bytes = fs::read(path).map_err(|e| e.to_string())?;
+        return gif_info(&bytes).ok_or_else(|| "not a valid GIF".to_string());
+    }
+    if !use_ffprobe {
+        return Err("install ffprobe (part of ffmpeg) to inspect videos".to_string());
+    }
+
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0"])
+        .args(["-show_entries", "stream=width,height:format=duration"])
+        .args(["-of", "default=noprint_wrappers=1"])
+        .arg(path)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    parse_ffprobe(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| "ffprobe found no video stream".to_string())
+}
+
+// `key=value` lines as printed by ffprobe's default writer
+fn parse_ffprobe(output: &str) -> Option<MediaInfo> {
+    let value = |key: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+    };
+    Some(MediaInfo {
+        width: value("width")?.parse().ok()?,
+        height: value("height")?.parse().ok()?,
+        frames: None,
+        // Negative, NaN or huge values would panic in from_secs_f64
+        duration: value("duration")
+            .and_then(|d| d.parse::<f64>().ok())
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok()),
+    })
+}
+
+/// Size, frame count and total delay from a GIF's block structure, without
+/// decoding any image data.
+pub fn gif_info(bytes: &[u8]) -> Option<MediaInfo> {
+    if !bytes.starts_with(b"GIF87a") && !bytes.starts_with(b"GIF89a") {
+        return None;
+    }
+    let width = u16::from_le_bytes([*bytes.get(6)?, *bytes.get(7)?]) as u32;
+    let height = u16::from_le_bytes([*bytes.get(8)?, *bytes.get(9)?]) as u32;
+
+    let mut pos = 13 + color_table_len(*bytes.get(10)?);
+    let mut frames = 0;
+    let mut delay_cs = 0u64;
+    loop {
+        match *bytes.get(pos)? {
+            // Extension; graphic control extensions carry the frame delay
+            0x21 => {
+                if *bytes.get(pos + 1)? == 0xF9 {
+                    delay_cs +=
+                        u16::from_le_bytes([*bytes.get(pos + 4)?, *bytes.get(pos + 5)?]) as u64;
+                }
+                pos = skip_sub_blocks(bytes, pos + 2)?;
+            }
+            // Image descriptor, optional local color table, LZW code size
+            0x2C => {
+                frames += 1;
+                let packed = *bytes.get(pos + 9)?;
+                pos = skip_sub_blocks(bytes, pos + 10 + color_table_len(packed) + 1)?;
+            }
+            0x3B => break,
+            _ => return None,
+        }
+    }
+
+    Some(MediaInfo {
+        width,
+        height,
+        frames: Some(frames),
+        duration: (delay_cs > 0).then(|| Duration::from_millis(delay_cs * 10)),
+    })
+}
+
+fn color_table_len(packed: u8) -> usize {
+    if packed & 0x80 == 0 {
+        0
+    } else {
+        3 << ((packed & 0x07) + 1)
+    }
+}
+
+// Data sub-blocks are length-prefixed and end with a zero length
+fn skip_sub_blocks(bytes: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *bytes.get(pos)? as usize;
+        pos += 1;
+        if len == 0 {
+            return Some(pos);
+        }
+        pos += len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(delay_cs: u16) -> Vec<u8> {
+        let mut bytes = vec![0x21, 0xF9, 4, 0];
+        bytes.extend(delay_cs.to_le_bytes());
+        bytes.extend([0, 0]);
+        // 1x1 image at 0,0 without a local color table, one byte of data
+        bytes.extend([0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0, 2, 1, 0x44, 0]);
+        bytes
+    }
+
+    #[test]
+    fn test_gif_info_counts_frames_and_delay() {
+        // 3x2 screen with a two-entry global color table
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend([3, 0, 2, 0, 0x80, 0, 0]);
+        gif.extend([0; 6]);
+        gif.extend(frame(10));
+        gif.extend(frame(15));
+        gif.push(0x3B);
+
+        let info = gif_info(&gif).unwrap();
+        assert_eq!((info.width, info.height), (3, 2));
+        assert_eq!(info.frames, Some(2));
+        assert_eq!(info.duration, Some(Duration::from_millis(250)));
+
+        assert_eq!(gif_info(b"GIF89a\x03\x00"), None);
+        assert_eq!(gif_info(b"PNG"), None);
+    }
+
+    #[test]
+    fn test_parse_ffprobe_output() {
+        let info = parse_ffprobe("width=1920\nheight=1080\nduration=12.500000\n").unwrap();
+        assert_eq!((info.width, info.height), (1920, 1080));
+        assert_eq!(info.duration, Some(Duration::from_millis(12500)));
+        assert_eq!(parse_ffprobe("duration=3.0\n"), None);
+        for odd in ["-1", "nan", "inf", "N/A"] {
+            let output = format!("width=1\nheight=1\nduration={}\n", odd);
+            assert_eq!(parse_ffprobe(&output).unwrap().duration, None, "{}", odd);
+        }
+    }
+}
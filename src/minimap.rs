@@ -0,0 +1,75 @@
+//! A shrunken overview of a file for the viewer's right edge. Each row
+//! stands for a run of lines, drawn as where their text starts and ends so
+//! the shape of the code shows through.
+
+use crate::editor;
+use std::ops::Range;
+
+/// Columns of text one minimap cell stands for
+const COLUMNS_PER_CELL: usize = 8;
+
+/// How many lines each of `rows` rows covers so that `total` lines fit.
+pub fn lines_per_row(total: usize, rows: usize) -> usize {
+    total.div_ceil(rows.max(1)).max(1)
+}
+
+/// One string per row, each `width` cells: blank up to the run's shallowest
+/// indentation, then blocks out to the end of its longest line.
+pub fn render(lines: &[&str], rows: usize, width: usize, tab_width: usize) -> Vec<String> {
+    let per_row = lines_per_row(lines.len(), rows);
+    lines
+        .chunks(per_row)
+        .map(|chunk| {
+            let mut start = usize::MAX;
+            let mut end = 0;
+            for line in chunk.iter().filter(|line| !line.trim().is_empty()) {
+                let mut column = 0;
+                let text = editor::expand_tabs(line, &mut column, tab_width);
+                let indent = text.len() - text.trim_start().len();
+                start = start.min(indent);
+                end = end.max(text.trim_end().chars().count());
+            }
+            let start = (start / COLUMNS_PER_CELL).min(width);
+            let end = end.div_ceil(COLUMNS_PER_CELL).min(width);
+            let mut row = " ".repeat(start);
+            row.push_str(&"▬".repeat(end.saturating_sub(start)));
+            row.push_str(&" ".repeat(width - start.max(end)));
+            row
+        })
+        .collect()
+}
+
+/// The rows showing any of the `visible` lines from `scroll` on.
+pub fn viewport(scroll: usize, visible: usize, total: usize, rows: usize) -> Range<usize> {
+    let per_row = lines_per_row(total, rows);
+    let last = (scroll + visible.max(1) - 1).min(total.saturating_sub(1));
+    scroll / per_row..last / per_row + 1
+}
+
+/// The first line of the run `row` stands for, clamped to the file.
+pub fn line_at(row: usize, total: usize, rows: usize) -> usize {
+    (row * lines_per_row(total, rows)).min(total.saturating_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_follow_indentation_and_length() {
+        let lines = ["fn main() {", "        let x = 1;", "", "", "\tx", ""];
+        let rows = render(&lines, 3, 4, 8);
+        assert_eq!(rows, ["▬▬▬ ", "    ", " ▬  "]);
+        assert!(rows.iter().all(|row| row.chars().count() == 4));
+    }
+
+    #[test]
+    fn test_maps_rows_and_lines_both_ways() {
+        assert_eq!(lines_per_row(100, 30), 4);
+        assert_eq!(lines_per_row(5, 30), 1);
+        assert_eq!(viewport(10, 20, 100, 30), 2..8);
+        assert_eq!(viewport(95, 20, 100, 30), 23..25);
+        assert_eq!(line_at(2, 100, 30), 8);
+        assert_eq!(line_at(40, 100, 30), 99);
+    }
+}
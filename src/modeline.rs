@@ -0,0 +1,84 @@
+// Editors only look this far from either end of the file
+const SCAN_LINES: usize = 5;
+
+/// The language a vim or emacs modeline asks for, if the file has one:
+/// `vim: set ft=json:`, `vi: filetype=sh`, `-*- mode: ruby -*-` or
+/// `-*- python -*-`. Only the first and last few lines are checked.
+pub fn language(content: &str) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let tail_start = lines.len().saturating_sub(SCAN_LINES).max(SCAN_LINES);
+    lines
+        .iter()
+        .take(SCAN_LINES)
+        .chain(lines.iter().skip(tail_start))
+        .find_map(|line| vim(line).or_else(|| emacs(line)))
+}
+
+fn vim(line: &str) -> Option<String> {
+    let start = ["vim:", "vi:", "ex:"]
+        .iter()
+        .find_map(|marker| line.find(marker).map(|i| i + marker.len()))?;
+    line[start..]
+        .split([' ', ':', '\t'])
+        .find_map(|option| {
+            option
+                .strip_prefix("ft=")
+                .or_else(|| option.strip_prefix("filetype="))
+                .or_else(|| option.strip_prefix("syntax="))
+                .or_else(|| option.strip_prefix("syn="))
+        })
+        .filter(|language| !language.is_empty())
+        .map(str::to_string)
+}
+
+fn emacs(line: &str) -> Option<String> {
+    let start = line.find("-*-")? + 3;
+    let inner = &line[start..start + line[start..].find("-*-")?];
+    let inner = inner.trim();
+    if !inner.contains(':') {
+        return (!inner.is_empty()).then(|| inner.to_string());
+    }
+    inner.split(';').find_map(|variable| {
+        let (key, value) = variable.split_once(':')?;
+        (key.trim().eq_ignore_ascii_case("mode")).then(|| value.trim().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vim_modelines() {
+        assert_eq!(
+            language("{}\n// vim: set ft=json:\n").as_deref(),
+            Some("json")
+        );
+        assert_eq!(
+            language("# vi: filetype=sh ts=4\necho").as_deref(),
+            Some("sh")
+        );
+        assert_eq!(language("# vim: ts=4 sw=4"), None);
+    }
+
+    #[test]
+    fn test_emacs_modelines() {
+        assert_eq!(
+            language("# -*- mode: ruby; coding: utf-8 -*-").as_deref(),
+            Some("ruby")
+        );
+        assert_eq!(
+            language("#!/bin/sh\n# -*- python -*-").as_deref(),
+            Some("python")
+        );
+        assert_eq!(language("# -*- coding: utf-8 -*-"), None);
+    }
+
+    #[test]
+    fn test_only_the_ends_of_the_file_are_scanned() {
+        let middle = format!("{}vim: ft=json\n{}", "x\n".repeat(10), "x\n".repeat(10));
+        assert_eq!(language(&middle), None);
+        let tail = format!("{}# vim: ft=yaml", "x\n".repeat(20));
+        assert_eq!(language(&tail).as_deref(), Some("yaml"));
+    }
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// Old entries are dropped past this, so a long session can't grow forever
+const MAX_ENTRIES: usize = 500;
+
+/// How long the newest message stays in the footer.
+pub const TOAST_DURATION: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub message: String,
+}
+
+/// Timestamped feedback from operations. The newest entry shows briefly in
+/// the footer; the full history is in the log panel.
+#[derive(Debug, Default)]
+pub struct Notifications {
+    entries: VecDeque<Notification>,
+    last_pushed: Option<Instant>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, level: Level, message: impl Into<String>) {
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Notification {
+            time: Local::now(),
+            level,
+            message: message.into(),
+        });
+        self.last_pushed = Some(Instant::now());
+    }
+
+    /// The newest entry while it's still fresh enough to show in the footer.
+    pub fn toast(&self) -> Option<&Notification> {
+        self.last_pushed
+            .filter(|pushed| pushed.elapsed() < TOAST_DURATION)
+            .and(self.entries.back())
+    }
+
+    /// Time until the current toast leaves the footer, if one is showing.
+    pub fn toast_remaining(&self) -> Option<Duration> {
+        self.toast()?;
+        self.last_pushed
+            .map(|pushed| TOAST_DURATION.saturating_sub(pushed.elapsed()))
+    }
+
+    /// Hides the current toast; it stays in the history.
+    pub fn dismiss_toast(&mut self) {
+        self.last_pushed = None;
+    }
+
+    /// Oldest first.
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = &Notification> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_capped_and_newest_is_toasted() {
+        let mut notifications = Notifications::default();
+        assert!(notifications.toast().is_none());
+
+        for i in 0..MAX_ENTRIES + 10 {
+            notifications.push(Level::Info, format!("message {}", i));
+        }
+        notifications.push(Level::Error, "failed");
+
+        assert_eq!(notifications.entries().len(), MAX_ENTRIES);
+        assert_eq!(
+            notifications.entries().next().unwrap().message,
+            "message 11"
+        );
+        assert!(notifications.toast_remaining().unwrap() <= TOAST_DURATION);
+        let toast = notifications.toast().unwrap();
+        assert_eq!(
+            (toast.level, toast.message.as_str()),
+            (Level::Error, "failed")
+        );
+
+        notifications.dismiss_toast();
+        assert!(notifications.toast().is_none());
+        assert!(notifications.toast_remaining().is_none());
+        assert_eq!(notifications.entries().len(), MAX_ENTRIES);
+    }
+}
@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+/// Expands a leading `~` (your home) or `~name` (that user's home) the way
+/// a shell would, for paths typed where no shell sees them: prompts, config
+/// values, quoted arguments. Anything else comes back unchanged, including
+/// a `~` later in the path and a `~name` that isn't a known user.
+pub fn expand_home(path: &Path) -> PathBuf {
+    let Some(text) = path.to_str() else {
+        return path.to_path_buf();
+    };
+    let Some(after_tilde) = text.strip_prefix('~') else {
+        return path.to_path_buf();
+    };
+    let (user, rest) = after_tilde
+        .split_once(std::path::is_separator)
+        .unwrap_or((after_tilde, ""));
+
+    let home = if user.is_empty() {
+        dirs::home_dir()
+    } else {
+        user_home(user)
+    };
+    match home {
+        Some(home) if rest.is_empty() => home,
+        Some(home) => home.join(rest),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Completes the last component of a typed path against the directory it
+/// names (relative to `base`), as far as all the candidates agree. A lone
+/// directory gets a trailing `/` so the next Tab goes inside it. Dotfiles
+/// are only offered once a `.` is typed. `None` when there's nothing to add.
+pub fn complete(base: &Path, input: &str) -> Option<String> {
+    let split = input.rfind(std::path::is_separator).map_or(0, |i| i + 1);
+    let (dir_part, prefix) = input.split_at(split);
+    let dir = base.join(expand_home(Path::new(dir_part)));
+
+    let mut matches: Vec<(String, bool)> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            let hidden_skipped = name.starts_with('.') && !prefix.starts_with('.');
+            (name.starts_with(prefix) && !hidden_skipped).then(|| (name, entry.path().is_dir()))
+        })
+        .collect();
+    matches.sort();
+
+    let (first, first_is_dir) = matches.first()?.clone();
+    let completed = if matches.len() == 1 {
+        if first_is_dir { first + "/" } else { first }
+    } else {
+        matches
+            .iter()
+            .fold(first.as_str(), |common, (name, _)| {
+                common_prefix(common, name)
+            })
+            .to_string()
+    };
+    (completed.len() > prefix.len()).then(|| format!("{}{}", dir_part, completed))
+}
+
+fn common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
+    let len = a
+        .chars()
+        .zip(b.chars())
+        .take_while(|(x, y)| x == y)
+        .map(|(x, _)| x.len_utf8())
+        .sum();
+    &a[..len]
+}
+
+#[cfg(unix)]
+fn user_home(name: &str) -> Option<PathBuf> {
+    nix::unistd::User::from_name(name)
+        .ok()
+        .flatten()
+        .map(|user| user.dir)
+}
+
+#[cfg(not(unix))]
+fn user_home(_name: &str) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tilde_expands_to_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_home(Path::new("~")), home);
+        assert_eq!(
+            expand_home(Path::new("~/sub/file.txt")),
+            home.join("sub/file.txt")
+        );
+    }
+
+    #[test]
+    fn test_other_tildes_are_left_alone() {
+        for path in ["notes~", "dir/~backup", "/tmp/~", "~no-such-user-here/x"] {
+            assert_eq!(expand_home(Path::new(path)), Path::new(path));
+        }
+    }
+
+    #[test]
+    fn test_completion_extends_to_the_common_prefix() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("projects/app")).unwrap();
+        std::fs::write(dir.join("notes-2024.txt"), "").unwrap();
+        std::fs::write(dir.join("notes-2025.txt"), "").unwrap();
+        std::fs::write(dir.join(".profile"), "").unwrap();
+
+        assert_eq!(complete(dir, "no").as_deref(), Some("notes-202"));
+        assert_eq!(complete(dir, "notes-202"), None);
+        assert_eq!(complete(dir, "pro").as_deref(), Some("projects/"));
+        assert_eq!(
+            complete(dir, "projects/a").as_deref(),
+            Some("projects/app/")
+        );
+        assert_eq!(complete(dir, ".pr").as_deref(), Some(".profile"));
+        assert_eq!(complete(dir, "missing/x"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_named_user_expands_to_their_home() {
+        let root = nix::unistd::User::from_uid(0.into()).unwrap().unwrap();
+        assert_eq!(
+            expand_home(Path::new(&format!("~{}/etc", root.name))),
+            root.dir.join("etc")
+        );
+    }
+}
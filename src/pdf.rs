@@ -0,0 +1,95 @@
+use std::cell::Cell;
+use std::panic;
+use std::path::Path;
+
+thread_local! {
+    static QUIET_PANICS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Wraps the panic hook, once at startup, so panics caught while reading a
+/// PDF print nothing. Swapping the hook per call would race with panics on
+/// other threads.
+pub fn install_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !QUIET_PANICS.with(Cell::get) {
+            hook(info);
+        }
+    }));
+}
+
+pub fn is_pdf(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("pdf"))
+}
+
+/// The text of each page, in order. Images and layout are dropped.
+pub fn extract_pages(path: &Path) -> Result<Vec<String>, String> {
+    // The extractor panics on some malformed files; the default hook would
+    // print over the TUI, so it's silenced on this thread for the call
+    QUIET_PANICS.with(|quiet| quiet.set(true));
+    let result = panic::catch_unwind(|| pdf_extract::extract_text_by_pages(path));
+    QUIET_PANICS.with(|quiet| quiet.set(false));
+
+    match result {
+        Ok(Ok(pages)) if pages.iter().all(|page| page.trim().is_empty()) => {
+            Err("no text found (scanned pages?)".to_string())
+        }
+        Ok(Ok(pages)) => Ok(pages),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err("the file couldn't be parsed".to_string()),
+    }
+}
+
+/// Joins the pages into one buffer, each under a `── Page N of M ──` rule.
+/// Also returns the line each page starts on.
+pub fn paginate(pages: &[String]) -> (String, Vec<usize>) {
+    let mut text = String::new();
+    let mut starts = Vec::with_capacity(pages.len());
+    let mut line = 0;
+    for (index, page) in pages.iter().enumerate() {
+        starts.push(line);
+        let body = page.trim_matches('\n');
+        text.push_str(&format!("── Page {} of {} ──\n", index + 1, pages.len()));
+        text.push_str(body);
+        text.push_str("\n\n");
+        line += body.lines().count() + 2;
+    }
+    (text, starts)
+}
+
+/// 1-based page shown at `line`, given where each page starts.
+pub fn page_at(starts: &[usize], line: usize) -> usize {
+    starts.partition_point(|&start| start <= line).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pages_are_ruled_and_located() {
+        let pages = ["First page\nmore".to_string(), "\nSecond\n".to_string()];
+        let (text, starts) = paginate(&pages);
+        assert_eq!(
+            text,
+            "── Page 1 of 2 ──\nFirst page\nmore\n\n── Page 2 of 2 ──\nSecond\n\n"
+        );
+        assert_eq!(starts, [0, 4]);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[starts[1]], "── Page 2 of 2 ──");
+
+        assert_eq!(page_at(&starts, 0), 1);
+        assert_eq!(page_at(&starts, 3), 1);
+        assert_eq!(page_at(&starts, 4), 2);
+        assert_eq!(page_at(&starts, 50), 2);
+    }
+
+    #[test]
+    fn test_unreadable_files_are_errors() {
+        assert!(is_pdf(Path::new("Report.PDF")));
+        assert!(!is_pdf(Path::new("notes.txt")));
+        assert!(extract_pages(Path::new("/nonexistent/file.pdf")).is_err());
+    }
+}
@@ -0,0 +1,257 @@
+//! Headless `--server` mode: newline-delimited JSON commands on stdin, one
+//! JSON response per line on stdout.
+//!
+//! ```text
+//! {"id": 1, "cmd": "navigate", "path": "src"}
+//! {"id": 1, "ok": true, "result": {"path": "/repo/src", "entries": [...]}}
+//! ```
+//!
+//! Commands: `list`, `navigate {path}`, `open {path}`, `stat {path}`, `quit`.
+//! Relative paths resolve against the current directory. `id` is optional
+//! and echoed back so clients can match responses to requests.
+
+use crate::encoding;
+use crate::{App, FileItem, format_permissions, is_text_path};
+use anyhow::Result as AppResult;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Option<Value>,
+    #[serde(flatten)]
+    command: Command,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+enum Command {
+    List,
+    Navigate { path: PathBuf },
+    Open { path: PathBuf },
+    Stat { path: PathBuf },
+    Quit,
+}
+
+#[derive(Serialize)]
+struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<Value>,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Entry {
+    name: String,
+    path: PathBuf,
+    is_dir: bool,
+    size: u64,
+    /// Seconds since the Unix epoch
+    modified: u64,
+    permissions: String,
+    hidden: bool,
+}
+
+impl From<&FileItem> for Entry {
+    fn from(file: &FileItem) -> Self {
+        Self {
+            name: file.name.clone(),
+            path: file.path.clone(),
+            is_dir: file.is_dir,
+            size: file.size,
+            modified: unix_seconds(file.modified),
+            permissions: file.permissions.clone(),
+            hidden: file.is_hidden,
+        }
+    }
+}
+
+pub fn run(mut app: App) -> AppResult<()> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (response, quit) = handle_line(&mut app, &line);
+        writeln!(stdout, "{}", serde_json::to_string(&response)?)?;
+        stdout.flush()?;
+        if quit {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn handle_line(app: &mut App, line: &str) -> (Response, bool) {
+    let request: Request = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return (failure(None, format!("invalid request: {}", e)), false),
+    };
+
+    let quit = matches!(request.command, Command::Quit);
+    let response = match dispatch(app, request.command) {
+        Ok(result) => Response {
+            id: request.id,
+            ok: true,
+            result: Some(result),
+            error: None,
+        },
+        Err(error) => failure(request.id, error),
+    };
+    (response, quit)
+}
+
+fn failure(id: Option<Value>, error: String) -> Response {
+    Response {
+        id,
+        ok: false,
+        result: None,
+        error: Some(error),
+    }
+}
+
+fn dispatch(app: &mut App, command: Command) -> Result<Value, String> {
+    match command {
+        Command::List => Ok(listing(app)),
+        Command::Navigate { path } => {
+            let path = resolve(app, &path)?;
+            if !path.is_dir() {
+                return Err(format!("{} is not a directory", path.display()));
+            }
+            if !app.is_within_root(&path) {
+                return Err(format!("{} is outside the root directory", path.display()));
+            }
+            app.current_path = path;
+            app.load_directory().map_err(|e| e.to_string())?;
+            Ok(listing(app))
+        }
+        Command::Open { path } => {
+            let path = resolve(app, &path)?;
+            if !app.is_within_root(&path) {
+                return Err(format!("{} is outside the root directory", path.display()));
+            }
+            if !is_text_path(&path) {
+                return Err(format!("{} is not a text file", path.display()));
+            }
+            let decoded = encoding::read_file(&path).map_err(|e| e.to_string())?;
+            Ok(json!({
+                "path": path,
+                "content": decoded.text,
+                "encoding": decoded.encoding.name(),
+                "lossy": decoded.lossy,
+            }))
+        }
+        Command::Stat { path } => {
+            let path = resolve(app, &path)?;
+            if !app.is_within_root(&path) {
+                return Err(format!("{} is outside the root directory", path.display()));
+            }
+            let metadata = fs::symlink_metadata(&path).map_err(|e| e.to_string())?;
+            Ok(json!({
+                "path": path,
+                "is_dir": metadata.is_dir(),
+                "is_symlink": metadata.file_type().is_symlink(),
+                "size": metadata.len(),
+                "modified": unix_seconds(metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH)),
+                "permissions": format_permissions(&metadata),
+            }))
+        }
+        Command::Quit => Ok(Value::Null),
+    }
+}
+
+pub fn listing(app: &App) -> Value {
+    let entries: Vec<Entry> = app
+        .files
+        .iter()
+        .filter(|file| file.name != "..")
+        .map(Entry::from)
+        .collect();
+    json!({ "path": app.current_path, "entries": entries })
+}
+
+fn resolve(app: &App, path: &Path) -> Result<PathBuf, String> {
+    app.current_path
+        .join(path)
+        .canonicalize()
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn request(app: &mut App, line: &str) -> Value {
+        let (response, _) = handle_line(app, line);
+        serde_json::to_value(response).unwrap()
+    }
+
+    #[test]
+    fn test_navigate_list_and_open() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub/notes.txt"), "hello").unwrap();
+        let mut app = App::new(dir.canonicalize().unwrap(), Config::default()).unwrap();
+
+        let response = request(&mut app, r#"{"id": 7, "cmd": "navigate", "path": "sub"}"#);
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["ok"], true);
+        assert_eq!(response["result"]["entries"][0]["name"], "notes.txt");
+
+        let response = request(&mut app, r#"{"cmd": "open", "path": "notes.txt"}"#);
+        assert_eq!(response["result"]["content"], "hello");
+
+        let response = request(&mut app, r#"{"cmd": "navigate", "path": "missing"}"#);
+        assert_eq!(response["ok"], false);
+        assert!(response.get("result").is_none());
+
+        let response = request(&mut app, r#"{"cmd": "bogus"}"#);
+        assert_eq!(response["ok"], false);
+    }
+
+    #[test]
+    fn test_root_bounds_open_and_stat() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("root")).unwrap();
+        fs::write(dir.join("secret.txt"), "outside").unwrap();
+        let root = dir.join("root").canonicalize().unwrap();
+        let mut app = App::new(root.clone(), Config::default()).unwrap();
+        app.root = Some(root);
+
+        for line in [
+            r#"{"cmd": "open", "path": "../secret.txt"}"#,
+            r#"{"cmd": "stat", "path": "../secret.txt"}"#,
+        ] {
+            let response = request(&mut app, line);
+            assert_eq!(response["ok"], false, "{}", line);
+            let error = response["error"].as_str().unwrap();
+            assert!(
+                error.ends_with("is outside the root directory"),
+                "{}",
+                error
+            );
+        }
+    }
+}
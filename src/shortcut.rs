@@ -0,0 +1,369 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Fixed size of a .lnk header, and the flags in it that say which optional
+// parts follow
+const LNK_HEADER_SIZE: usize = 0x4C;
+const HAS_TARGET_ID_LIST: u32 = 1 << 0;
+const HAS_LINK_INFO: u32 = 1 << 1;
+const HAS_NAME: u32 = 1 << 2;
+const HAS_RELATIVE_PATH: u32 = 1 << 3;
+const HAS_WORKING_DIR: u32 = 1 << 4;
+const HAS_ARGUMENTS: u32 = 1 << 5;
+const HAS_ICON_LOCATION: u32 = 1 << 6;
+const IS_UNICODE: u32 = 1 << 7;
+
+/// Where a launcher file points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// A command line to run, from a .desktop `Exec`
+    Command(String),
+    Path(PathBuf),
+    Url(String),
+}
+
+/// What a .desktop file or a Windows shortcut says about itself, as
+/// label/value pairs in display order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortcut {
+    pub fields: Vec<(&'static str, String)>,
+    pub target: Option<Target>,
+}
+
+impl Shortcut {
+    pub fn describe(&self, path: &Path) -> String {
+        let mut text = format!("{}\n\n", path.display());
+        let width = self.fields.iter().map(|(label, _)| label.len()).max();
+        for (label, value) in &self.fields {
+            text.push_str(&format!(
+                "{:width$}  {}\n",
+                format!("{}:", label),
+                value,
+                width = width.unwrap_or(0) + 1
+            ));
+        }
+        if self.fields.is_empty() {
+            text.push_str("No entries found\n");
+        }
+        // Exactly what following it hands to the shell, however long
+        if let Some(Target::Command(command)) = &self.target {
+            text.push_str(&format!(
+                "\nFollowing it runs this command line:\n{}\n",
+                command
+            ));
+        }
+        text
+    }
+}
+
+pub fn is_shortcut(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("desktop") || e.eq_ignore_ascii_case("lnk"))
+}
+
+pub fn read(path: &Path) -> Result<Shortcut, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let is_lnk = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("lnk"));
+    if is_lnk {
+        parse_lnk(&bytes)
+    } else {
+        Ok(parse_desktop(&String::from_utf8_lossy(&bytes)))
+    }
+}
+
+/// Reads the `[Desktop Entry]` group of a .desktop file. Translated keys
+/// (`Name[de]=`) and other groups such as actions are left out.
+pub fn parse_desktop(text: &str) -> Shortcut {
+    let mut entries: Vec<(String, String)> = Vec::new();
+    let mut in_entry = false;
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=')
+            && in_entry
+            && !key.contains('[')
+        {
+            entries.push((key.trim().to_string(), unescape(value.trim())));
+        }
+    }
+    let get = |key: &str| {
+        entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.clone())
+    };
+
+    let mut fields = Vec::new();
+    for (key, label) in [
+        ("Name", "Name"),
+        ("GenericName", "Generic name"),
+        ("Comment", "Comment"),
+        ("Type", "Type"),
+        ("Exec", "Exec"),
+        ("URL", "URL"),
+        ("Path", "Working dir"),
+        ("Icon", "Icon"),
+        ("Terminal", "Terminal"),
+        ("Categories", "Categories"),
+    ] {
+        if let Some(value) = get(key) {
+            fields.push((label, value));
+        }
+    }
+
+    let target = match (get("Type").as_deref(), get("URL"), get("Exec")) {
+        (Some("Link"), Some(url), _) => Some(Target::Url(url)),
+        (_, _, Some(exec)) => {
+            let command = strip_field_codes(&exec);
+            (!command.is_empty()).then_some(Target::Command(command))
+        }
+        _ => None,
+    };
+    Shortcut { fields, target }
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => out.push(' '),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// An `Exec` line without its `%f`/`%U`-style placeholders, which only mean
+/// something when files are dropped on the launcher. `%%` is a literal `%`.
+fn strip_field_codes(exec: &str) -> String {
+    let mut out = String::with_capacity(exec.len());
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+        } else if chars.next() == Some('%') {
+            out.push('%');
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Reads a Windows shell link: the target path from its link info, plus
+/// the optional strings that follow. The item ID list is skipped rather
+/// than decoded, so a link that only has one shows no target.
+pub fn parse_lnk(bytes: &[u8]) -> Result<Shortcut, String> {
+    let invalid = || "not a valid shortcut file".to_string();
+    if read_u32(bytes, 0) != Some(LNK_HEADER_SIZE as u32) {
+        return Err(invalid());
+    }
+    let flags = read_u32(bytes, 0x14).ok_or_else(invalid)?;
+    let mut offset = LNK_HEADER_SIZE;
+
+    if flags & HAS_TARGET_ID_LIST != 0 {
+        offset += 2 + read_u16(bytes, offset).ok_or_else(invalid)? as usize;
+    }
+
+    let mut target = None;
+    if flags & HAS_LINK_INFO != 0 {
+        let info = bytes.get(offset..).ok_or_else(invalid)?;
+        target = link_info_path(info);
+        offset += read_u32(info, 0).ok_or_else(invalid)? as usize;
+    }
+
+    let mut strings = Vec::new();
+    for (flag, label) in [
+        (HAS_NAME, "Description"),
+        (HAS_RELATIVE_PATH, "Relative path"),
+        (HAS_WORKING_DIR, "Working dir"),
+        (HAS_ARGUMENTS, "Arguments"),
+        (HAS_ICON_LOCATION, "Icon"),
+    ] {
+        if flags & flag == 0 {
+            continue;
+        }
+        let count = read_u16(bytes, offset).ok_or_else(invalid)? as usize;
+        offset += 2;
+        let value = if flags & IS_UNICODE != 0 {
+            let units: Vec<u16> = bytes
+                .get(offset..offset + count * 2)
+                .ok_or_else(invalid)?
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            offset += count * 2;
+            String::from_utf16_lossy(&units)
+        } else {
+            let text = bytes.get(offset..offset + count).ok_or_else(invalid)?;
+            offset += count;
+            String::from_utf8_lossy(text).into_owned()
+        };
+        strings.push((label, value));
+    }
+
+    let target = target.or_else(|| {
+        strings
+            .iter()
+            .find(|(label, _)| *label == "Relative path")
+            .map(|(_, path)| path.clone())
+    });
+    let mut fields = Vec::new();
+    if let Some(target) = &target {
+        fields.push(("Target", target.clone()));
+    }
+    fields.extend(strings);
+    Ok(Shortcut {
+        fields,
+        target: target.map(|path| Target::Path(PathBuf::from(path))),
+    })
+}
+
+/// The path a LinkInfo structure points at: a local base path, or a network
+/// share, each followed by the common suffix.
+fn link_info_path(info: &[u8]) -> Option<String> {
+    let flags = read_u32(info, 8)?;
+    let suffix = read_cstr(info, read_u32(info, 24)? as usize).unwrap_or_default();
+    if flags & 1 != 0 {
+        let base = read_cstr(info, read_u32(info, 16)? as usize)?;
+        return Some(base + &suffix);
+    }
+    if flags & 2 != 0 {
+        let network = read_u32(info, 20)? as usize;
+        let share = read_cstr(info, network + read_u32(info, network + 8)? as usize)?;
+        return Some(if suffix.is_empty() {
+            share
+        } else {
+            format!("{}\\{}", share, suffix)
+        });
+    }
+    None
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    let pair = bytes.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([pair[0], pair[1]]))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let quad = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([quad[0], quad[1], quad[2], quad[3]]))
+}
+
+fn read_cstr(bytes: &[u8], offset: usize) -> Option<String> {
+    let rest = bytes.get(offset..)?;
+    let end = rest.iter().position(|&b| b == 0)?;
+    Some(String::from_utf8_lossy(&rest[..end]).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_desktop_entry_is_parsed() {
+        let text = "\
+# A launcher
+[Desktop Entry]
+Type=Application
+Name=Text Editor
+Name[de]=Texteditor
+Comment=Edit text\\sfiles
+Exec=gedit --new-window %U
+Icon=org.gnome.gedit
+
+[Desktop Action new]
+Exec=gedit --other
+";
+        let shortcut = parse_desktop(text);
+        assert_eq!(
+            shortcut.target,
+            Some(Target::Command("gedit --new-window".to_string()))
+        );
+        assert_eq!(
+            shortcut.fields,
+            [
+                ("Name", "Text Editor".to_string()),
+                ("Comment", "Edit text files".to_string()),
+                ("Type", "Application".to_string()),
+                ("Exec", "gedit --new-window %U".to_string()),
+                ("Icon", "org.gnome.gedit".to_string()),
+            ]
+        );
+
+        let link = parse_desktop("[Desktop Entry]\nType=Link\nURL=https://example.com\n");
+        assert_eq!(
+            link.target,
+            Some(Target::Url("https://example.com".to_string()))
+        );
+        assert_eq!(strip_field_codes("run %f --at 50%% %i"), "run --at 50%");
+    }
+
+    #[test]
+    fn test_description_ends_with_the_whole_command() {
+        let shortcut = parse_desktop("[Desktop Entry]\nExec=sh -c 'rm -rf ~/tmp; echo done' %U\n");
+        let text = shortcut.describe(Path::new("/apps/clean.desktop"));
+        assert!(
+            text.ends_with("runs this command line:\nsh -c 'rm -rf ~/tmp; echo done'\n"),
+            "{}",
+            text
+        );
+    }
+
+    #[test]
+    fn test_lnk_target_and_strings() {
+        let mut bytes = vec![0u8; LNK_HEADER_SIZE];
+        bytes[0] = LNK_HEADER_SIZE as u8;
+        let flags = HAS_LINK_INFO | HAS_ARGUMENTS | IS_UNICODE;
+        bytes[0x14..0x18].copy_from_slice(&flags.to_le_bytes());
+
+        // LinkInfo: a 28-byte header, then the base path and an empty suffix
+        let base = b"C:\\Tools\\app.exe\0";
+        let size = 28 + base.len() + 1;
+        let mut info = Vec::new();
+        for value in [size, 28, 1, 0, 28, 0, 28 + base.len()] {
+            info.extend_from_slice(&(value as u32).to_le_bytes());
+        }
+        info.extend_from_slice(base);
+        info.push(0);
+        bytes.extend_from_slice(&info);
+
+        let arguments: Vec<u16> = "-v".encode_utf16().collect();
+        bytes.extend_from_slice(&(arguments.len() as u16).to_le_bytes());
+        for unit in arguments {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+
+        let shortcut = parse_lnk(&bytes).unwrap();
+        assert_eq!(
+            shortcut.fields,
+            [
+                ("Target", "C:\\Tools\\app.exe".to_string()),
+                ("Arguments", "-v".to_string()),
+            ]
+        );
+        assert_eq!(
+            shortcut.target,
+            Some(Target::Path(PathBuf::from("C:\\Tools\\app.exe")))
+        );
+
+        assert!(parse_lnk(b"not a shortcut").is_err());
+        bytes.truncate(LNK_HEADER_SIZE + 10);
+        assert!(parse_lnk(&bytes).is_err());
+    }
+}
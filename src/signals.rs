@@ -0,0 +1,46 @@
+//! Job control for the embedded terminal: deliver Ctrl+C and Ctrl+\ as real
+//! signals to whatever runs in the foreground, rather than trusting the
+//! PTY's line discipline to turn a byte into one.
+
+use portable_pty::MasterPty;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalSignal {
+    Interrupt,
+    Quit,
+}
+
+impl TerminalSignal {
+    /// The control byte the terminal would send for this key, used when no
+    /// signal can be delivered.
+    pub fn control_byte(self) -> &'static str {
+        match self {
+            TerminalSignal::Interrupt => "\u{3}",
+            TerminalSignal::Quit => "\u{1c}",
+        }
+    }
+}
+
+/// Signals the PTY's foreground process group, or the shell itself if the
+/// group can't be determined. Returns false if nothing was signalled.
+#[cfg(unix)]
+pub fn send(pty: &dyn MasterPty, shell_pid: Option<u32>, signal: TerminalSignal) -> bool {
+    use nix::sys::signal::{Signal, kill, killpg};
+    use nix::unistd::Pid;
+
+    let signal = match signal {
+        TerminalSignal::Interrupt => Signal::SIGINT,
+        TerminalSignal::Quit => Signal::SIGQUIT,
+    };
+    if let Some(group) = pty.process_group_leader().filter(|pid| *pid > 0)
+        && killpg(Pid::from_raw(group), signal).is_ok()
+    {
+        return true;
+    }
+    shell_pid.is_some_and(|pid| kill(Pid::from_raw(pid as i32), signal).is_ok())
+}
+
+#[cfg(not(unix))]
+pub fn send(_pty: &dyn MasterPty, _shell_pid: Option<u32>, _signal: TerminalSignal) -> bool {
+    false
+}
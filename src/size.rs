@@ -0,0 +1,129 @@
+use serde::Deserialize;
+
+/// Which multiples `format_size` uses in human readable mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeUnits {
+    /// Powers of 1024 labeled K, M, G, like `ls -h`
+    #[default]
+    Binary,
+    /// Powers of 1000 labeled kB, MB, GB, like `ls --si` and disk vendors
+    Si,
+}
+
+impl SizeUnits {
+    fn base(self) -> f64 {
+        match self {
+            SizeUnits::Binary => 1024.0,
+            SizeUnits::Si => 1000.0,
+        }
+    }
+
+    fn labels(self) -> &'static [&'static str] {
+        match self {
+            SizeUnits::Binary => &["B", "K", "M", "G", "T"],
+            SizeUnits::Si => &["B", "kB", "MB", "GB", "TB"],
+        }
+    }
+}
+
+pub fn format_size(size: u64, human_readable: bool, units: SizeUnits) -> String {
+    if human_readable {
+        let (base, labels) = (units.base(), units.labels());
+        let mut size = size as f64;
+        let mut unit_index = 0;
+
+        while size >= base && unit_index < labels.len() - 1 {
+            size /= base;
+            unit_index += 1;
+        }
+
+        if unit_index == 0 {
+            format!("{:.0}{}", size, labels[unit_index])
+        } else {
+            format!("{:.1}{}", size, labels[unit_index])
+        }
+    } else {
+        size.to_string()
+    }
+}
+
+/// A human readable total followed by the exact byte count, for summaries
+/// where the rounding matters: `1.4G (1503229184 bytes)`.
+pub fn format_total(size: u64, units: SizeUnits) -> String {
+    format!("{} ({} bytes)", format_size(size, true, units), size)
+}
+
+/// Cells a size bar takes in the listing
+pub const BAR_WIDTH: usize = 12;
+
+/// `size` as a bar of block characters `width` cells wide, full at `max`.
+/// Anything non-empty gets at least a sliver.
+pub fn size_bar(size: u64, max: u64, width: usize) -> String {
+    const PARTIAL: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+    if size == 0 || max == 0 {
+        return " ".repeat(width);
+    }
+    let eighths = (size as u128 * width as u128 * 8 / max as u128).clamp(1, width as u128 * 8);
+    let (full, part) = (eighths as usize / 8, eighths as usize % 8);
+    let mut bar = "█".repeat(full);
+    bar.push_str(PARTIAL[part]);
+    let cells = full + usize::from(part > 0);
+    bar.push_str(&" ".repeat(width - cells));
+    bar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_units() {
+        let format = |size| format_size(size, true, SizeUnits::Binary);
+        assert_eq!(format(0), "0B");
+        assert_eq!(format(1000), "1000B");
+        assert_eq!(format(1023), "1023B");
+        assert_eq!(format(1024), "1.0K");
+        assert_eq!(format(1024 * 1024 - 1), "1024.0K");
+        assert_eq!(format(1024 * 1024), "1.0M");
+        assert_eq!(format(3 * 1024 * 1024 * 1024), "3.0G");
+    }
+
+    #[test]
+    fn test_si_units() {
+        let format = |size| format_size(size, true, SizeUnits::Si);
+        assert_eq!(format(999), "999B");
+        assert_eq!(format(1000), "1.0kB");
+        assert_eq!(format(1024), "1.0kB");
+        assert_eq!(format(1_500_000), "1.5MB");
+        assert_eq!(format(1_000_000_000), "1.0GB");
+        assert_eq!(format(2_000_000_000_000_000), "2000.0TB");
+    }
+
+    #[test]
+    fn test_total_includes_exact_bytes() {
+        assert_eq!(
+            format_total(1_503_229_184, SizeUnits::Binary),
+            "1.4G (1503229184 bytes)"
+        );
+        assert_eq!(format_total(512, SizeUnits::Si), "512B (512 bytes)");
+    }
+
+    #[test]
+    fn test_size_bars_scale_to_the_largest() {
+        assert_eq!(size_bar(100, 100, 4), "████");
+        assert_eq!(size_bar(50, 100, 4), "██  ");
+        assert_eq!(size_bar(6, 16, 2), "▊ ");
+        // Tiny files still show, empty ones don't
+        assert_eq!(size_bar(1, u64::MAX, 3), "▏  ");
+        assert_eq!(size_bar(0, 100, 3), "   ");
+        assert_eq!(size_bar(5, 0, 3), "   ");
+    }
+
+    #[test]
+    fn test_plain_bytes_ignore_units() {
+        assert_eq!(format_size(1_000_000, false, SizeUnits::Si), "1000000");
+        assert_eq!(format_size(1_000_000, false, SizeUnits::Binary), "1000000");
+    }
+}
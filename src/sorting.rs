@@ -0,0 +1,277 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::Chars;
+use std::time::SystemTime;
+
+/// Column the listing is sorted by. Where directories go is up to
+/// `DirOrder`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    #[default]
+    Name,
+    Size,
+    Modified,
+    /// By extension, then by name; no extension comes first
+    Extension,
+}
+
+impl SortKey {
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Name => SortKey::Size,
+            SortKey::Size => SortKey::Modified,
+            SortKey::Modified => SortKey::Extension,
+            SortKey::Extension => SortKey::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Name => "Name",
+            SortKey::Size => "Size",
+            SortKey::Modified => "Modified",
+            SortKey::Extension => "Extension",
+        }
+    }
+}
+
+/// Where directories go relative to files in the listing. `..` stays on
+/// top either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DirOrder {
+    #[default]
+    DirsFirst,
+    FilesFirst,
+    /// Directories sorted in among the files
+    Mixed,
+}
+
+impl DirOrder {
+    pub fn next(self) -> Self {
+        match self {
+            DirOrder::DirsFirst => DirOrder::FilesFirst,
+            DirOrder::FilesFirst => DirOrder::Mixed,
+            DirOrder::Mixed => DirOrder::DirsFirst,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DirOrder::DirsFirst => "Directories first",
+            DirOrder::FilesFirst => "Files first",
+            DirOrder::Mixed => "Directories mixed in with files",
+        }
+    }
+
+    /// Orders a directory against a file; `Equal` leaves it to the sort
+    /// column.
+    pub fn compare(self, a_is_dir: bool, b_is_dir: bool) -> Ordering {
+        match self {
+            DirOrder::DirsFirst => b_is_dir.cmp(&a_is_dir),
+            DirOrder::FilesFirst => a_is_dir.cmp(&b_is_dir),
+            DirOrder::Mixed => Ordering::Equal,
+        }
+    }
+}
+
+/// What a listing entry is sorted on.
+pub struct SortFields<'a> {
+    pub name: &'a str,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SortMode {
+    pub key: SortKey,
+    pub reverse: bool,
+}
+
+impl SortMode {
+    /// Picking the column already sorted on flips the direction; a new
+    /// column starts out ascending.
+    pub fn select(&mut self, key: SortKey) {
+        if self.key == key {
+            self.reverse = !self.reverse;
+        } else {
+            *self = SortMode {
+                key,
+                reverse: false,
+            };
+        }
+    }
+
+    pub fn arrow(self) -> &'static str {
+        if self.reverse { "▼" } else { "▲" }
+    }
+
+    /// Ties on size or date fall back to the name, so the order is stable.
+    pub fn compare(self, a: &SortFields, b: &SortFields) -> Ordering {
+        let by_name = || natural_cmp(a.name, b.name);
+        let ordering = match self.key {
+            SortKey::Name => by_name(),
+            SortKey::Size => a.size.cmp(&b.size).then_with(by_name),
+            SortKey::Modified => a.modified.cmp(&b.modified).then_with(by_name),
+            SortKey::Extension => {
+                let extension = |name| Path::new(name).extension().and_then(|e| e.to_str());
+                match (extension(a.name), extension(b.name)) {
+                    // .TXT and .txt are the same kind of file
+                    (Some(x), Some(y)) => natural_cmp(&x.to_lowercase(), &y.to_lowercase()),
+                    (x, y) => x.is_some().cmp(&y.is_some()),
+                }
+                .then_with(by_name)
+            }
+        };
+        if self.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// Case-insensitive "natural" comparison: runs of digits compare by their
+/// numeric value, so `file2` sorts before `file10` (like `ls -v` or Finder).
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => break,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let a_num = take_digits(&mut a_chars);
+                let b_num = take_digits(&mut b_chars);
+                let ordering = compare_digit_runs(&a_num, &b_num);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(ca), Some(cb)) => {
+                let ordering = ca.to_lowercase().cmp(cb.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+
+    // Names that only differ by case still need a stable order
+    a.cmp(b)
+}
+
+fn take_digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        digits.push(c);
+        chars.next();
+    }
+    digits
+}
+
+fn compare_digit_runs(a: &str, b: &str) -> Ordering {
+    let a_trimmed = a.trim_start_matches('0');
+    let b_trimmed = b.trim_start_matches('0');
+
+    // Longer run of significant digits is the bigger number; equal lengths
+    // compare digit by digit. This avoids overflow on very long numbers.
+    a_trimmed
+        .len()
+        .cmp(&b_trimmed.len())
+        .then_with(|| a_trimmed.cmp(b_trimmed))
+        // `img01` and `img1` are numerically equal; fewer leading zeros first
+        .then_with(|| a.len().cmp(&b.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|s| s.to_string()).collect();
+        names.sort_by(|a, b| natural_cmp(a, b));
+        names
+    }
+
+    #[test]
+    fn test_numbers_compare_numerically() {
+        assert_eq!(
+            sorted(&["img100", "img10", "img2", "img1"]),
+            vec!["img1", "img2", "img10", "img100"]
+        );
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        assert_eq!(
+            sorted(&["b.txt", "A.txt", "c.txt"]),
+            vec!["A.txt", "b.txt", "c.txt"]
+        );
+    }
+
+    #[test]
+    fn test_extensions_and_leading_zeros() {
+        assert_eq!(
+            sorted(&["file10.txt", "file2.txt", "file02.txt", "file1.txt"]),
+            vec!["file1.txt", "file2.txt", "file02.txt", "file10.txt"]
+        );
+    }
+
+    #[test]
+    fn test_prefix_sorts_first() {
+        assert_eq!(natural_cmp("img", "img1"), Ordering::Less);
+        assert_eq!(natural_cmp("img1", "img1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_sort_mode_columns_and_direction() {
+        let entry = |name, size, secs| SortFields {
+            name,
+            size,
+            modified: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs),
+        };
+        let (small_new, big_old) = (entry("b", 1, 200), entry("a", 9, 100));
+        let mut mode = SortMode::default();
+        assert_eq!(mode.compare(&small_new, &big_old), Ordering::Greater);
+
+        mode.select(SortKey::Size);
+        assert_eq!(mode.compare(&small_new, &big_old), Ordering::Less);
+        mode.select(SortKey::Size);
+        assert!(mode.reverse);
+        assert_eq!(mode.compare(&small_new, &big_old), Ordering::Greater);
+
+        // A new column starts ascending again
+        mode.select(SortKey::Modified);
+        assert!(!mode.reverse);
+        assert_eq!(mode.compare(&small_new, &big_old), Ordering::Greater);
+
+        mode.select(SortKey::Extension);
+        let mut names = ["b.txt", "Makefile", "a.rs", "c.TXT", ".bashrc", "a.txt"];
+        names.sort_by(|a, b| mode.compare(&entry(a, 0, 0), &entry(b, 0, 0)));
+        assert_eq!(
+            names,
+            [".bashrc", "Makefile", "a.rs", "a.txt", "b.txt", "c.TXT"]
+        );
+    }
+
+    #[test]
+    fn test_dir_order() {
+        assert_eq!(DirOrder::DirsFirst.compare(true, false), Ordering::Less);
+        assert_eq!(DirOrder::FilesFirst.compare(true, false), Ordering::Greater);
+        assert_eq!(DirOrder::Mixed.compare(true, false), Ordering::Equal);
+        assert_eq!(DirOrder::DirsFirst.compare(false, false), Ordering::Equal);
+        assert_eq!(DirOrder::Mixed.next(), DirOrder::DirsFirst);
+    }
+}
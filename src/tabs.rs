@@ -1,3 +1,9 @@
+use crate::encoding;
+use crate::folding::Folds;
+use crate::linediff::GutterMarks;
+use crate::tail::Tail;
+use crate::undo::{Edit, History, Snapshot};
+use encoding_rs::{Encoding, UTF_8};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
@@ -5,8 +11,31 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph, Tabs as RatatuiTabs},
 };
+use std::fmt;
 use std::path::PathBuf;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabError {
+    IndexOutOfBounds(usize),
+    /// The tab wasn't closed; the close confirmation is showing instead
+    HasUnsavedChanges,
+    NoActiveTab,
+    NoTabs,
+}
+
+impl fmt::Display for TabError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TabError::IndexOutOfBounds(index) => write!(f, "no tab at index {}", index),
+            TabError::HasUnsavedChanges => write!(f, "tab has unsaved changes"),
+            TabError::NoActiveTab => write!(f, "no active tab"),
+            TabError::NoTabs => write!(f, "no tabs open"),
+        }
+    }
+}
+
+impl std::error::Error for TabError {}
+
 #[derive(Debug, Clone)]
 pub struct Tab {
     pub id: usize,
@@ -18,7 +47,32 @@ pub struct Tab {
     pub cursor_line: usize,
     pub cursor_col: usize,
     pub scroll_offset: usize,
+    /// Columns hidden on the left while long lines are unwrapped
+    pub horizontal_scroll: usize,
     pub file_version: i32,
+    pub read_only: bool,
+    pub markdown_preview: bool,
+    /// Collapsed blocks in the pager
+    pub folds: Folds,
+    /// Lines changed since the file was opened or last saved
+    pub gutter: GutterMarks,
+    /// Line each page starts on, for text pulled out of a PDF
+    pub pdf_pages: Vec<usize>,
+    /// Set while the tab follows the file as it grows, like `tail -f`
+    pub tail: Option<Tail>,
+    /// syntect syntax name used instead of guessing from the extension
+    pub syntax_override: Option<String>,
+    // How the file is stored on disk, so saving writes it back the same way
+    pub encoding: &'static Encoding,
+    pub has_bom: bool,
+    pub lossy: bool,
+    /// False when the file couldn't be opened for writing
+    pub writable: bool,
+    /// Shows something about the file rather than the file itself, so it is
+    /// never edited or saved back
+    pub generated: bool,
+    /// Edits Ctrl+Z and Ctrl+Y step back and forth through
+    pub undo: History,
 }
 
 impl Tab {
@@ -33,13 +87,29 @@ impl Tab {
             cursor_line: 0,
             cursor_col: 0,
             scroll_offset: 0,
+            horizontal_scroll: 0,
             file_version: 1,
+            read_only: false,
+            markdown_preview: false,
+            folds: Folds::default(),
+            gutter: GutterMarks::default(),
+            pdf_pages: Vec::new(),
+            tail: None,
+            syntax_override: None,
+            encoding: UTF_8,
+            has_bom: false,
+            lossy: false,
+            writable: true,
+            generated: false,
+            undo: History::default(),
         }
     }
 
     pub fn get_display_name(&self) -> String {
         if self.has_unsaved_changes {
             format!("{}*", self.name)
+        } else if self.read_only {
+            format!("{} [RO]", self.name)
         } else {
             self.name.clone()
         }
@@ -54,12 +124,80 @@ impl Tab {
         self.original_content = self.content.clone();
     }
 
-    pub fn is_dirty(&self) -> bool {
-        self.has_unsaved_changes
+    /// A buffer with no file behind it yet; saving it asks for a path.
+    pub fn is_untitled(&self) -> bool {
+        self.path.as_os_str().is_empty()
+    }
+
+    pub fn encoded_content(&self) -> Result<Vec<u8>, String> {
+        encoding::encode(&self.content, self.encoding, self.has_bom)
+    }
+
+    /// Footer label such as "UTF-8", "UTF-16LE BOM" or "UTF-8 (lossy)".
+    pub fn encoding_label(&self) -> String {
+        let mut label = self.encoding.name().to_string();
+        if self.has_bom {
+            label.push_str(" BOM");
+        }
+        if self.lossy {
+            label.push_str(" (lossy)");
+        }
+        label
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            content: self.content.clone(),
+            cursor_line: self.cursor_line,
+            cursor_col: self.cursor_col,
+            scroll_offset: self.scroll_offset,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.content = snapshot.content;
+        self.cursor_line = snapshot.cursor_line;
+        self.cursor_col = snapshot.cursor_col;
+        self.scroll_offset = snapshot.scroll_offset;
+        self.has_unsaved_changes = self.content != self.original_content;
+    }
+
+    /// Records `before` as the state an edit just changed, unless the edit
+    /// left the content as it was.
+    pub fn record_edit(&mut self, before: Snapshot, edit: Edit) {
+        if before.content != self.content {
+            self.undo.record(before, edit);
+        }
+    }
+
+    /// Steps back one edit. False when there's nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let current = self.snapshot();
+        match self.undo.undo(current) {
+            Some(previous) => {
+                self.restore(previous);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Steps forward again after an undo. False when there's nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let current = self.snapshot();
+        match self.undo.redo(current) {
+            Some(next) => {
+                self.restore(next);
+                true
+            }
+            None => false,
+        }
     }
 
     pub fn revert_changes(&mut self) {
+        let before = self.snapshot();
         self.content = self.original_content.clone();
+        self.record_edit(before, Edit::Other);
         self.has_unsaved_changes = false;
         self.cursor_line = 0;
         self.cursor_col = 0;
@@ -73,6 +211,9 @@ pub struct TabManager {
     next_id: usize,
     pub show_close_confirmation: bool,
     pub tab_to_close: Option<usize>,
+    /// Ids of the tabs shown side by side, left then right. Ids rather than
+    /// indices so opening and closing other tabs doesn't break the pair.
+    split: Option<(usize, usize)>,
 }
 
 impl TabManager {
@@ -83,6 +224,7 @@ impl TabManager {
             next_id: 1,
             show_close_confirmation: false,
             tab_to_close: None,
+            split: None,
         }
     }
 
@@ -103,15 +245,54 @@ impl TabManager {
         self.active_tab
     }
 
-    pub fn close_tab(&mut self, index: usize) -> Result<(), String> {
+    /// Opens a read-only view made from the file at `path`, like its
+    /// attributes or the text of a PDF. It never stands in for the file's
+    /// own tab, which opens separately; only the same view is reused.
+    pub fn add_generated_tab(&mut self, name: String, path: PathBuf, content: String) -> usize {
+        if let Some(index) = self
+            .tabs
+            .iter()
+            .position(|tab| tab.generated && tab.path == path && tab.name == name)
+        {
+            self.active_tab = index;
+            return index;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let mut tab = Tab::new(id, name, path, content);
+        tab.read_only = true;
+        tab.generated = true;
+        self.tabs.push(tab);
+        self.active_tab = self.tabs.len() - 1;
+
+        self.active_tab
+    }
+
+    /// Opens `content` in a new buffer with no file behind it. It starts
+    /// dirty so closing it asks first.
+    pub fn add_untitled_tab(&mut self, content: String) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let mut tab = Tab::new(id, format!("Untitled-{}", id), PathBuf::new(), content);
+        tab.mark_dirty();
+        self.tabs.push(tab);
+        self.active_tab = self.tabs.len() - 1;
+
+        self.active_tab
+    }
+
+    pub fn close_tab(&mut self, index: usize) -> Result<(), TabError> {
         if index >= self.tabs.len() {
-            return Err("Tab index out of bounds".to_string());
+            return Err(TabError::IndexOutOfBounds(index));
         }
 
         if self.tabs[index].has_unsaved_changes {
             self.tab_to_close = Some(index);
             self.show_close_confirmation = true;
-            return Err("Tab has unsaved changes".to_string());
+            return Err(TabError::HasUnsavedChanges);
         }
 
         self.tabs.remove(index);
@@ -127,9 +308,9 @@ impl TabManager {
         Ok(())
     }
 
-    pub fn force_close_tab(&mut self, index: usize) -> Result<(), String> {
+    pub fn force_close_tab(&mut self, index: usize) -> Result<(), TabError> {
         if index >= self.tabs.len() {
-            return Err("Tab index out of bounds".to_string());
+            return Err(TabError::IndexOutOfBounds(index));
         }
 
         self.tabs.remove(index);
@@ -158,16 +339,16 @@ impl TabManager {
         self.tab_to_close = None;
     }
 
-    pub fn close_active_tab(&mut self) -> Result<(), String> {
+    pub fn close_active_tab(&mut self) -> Result<(), TabError> {
         if self.tabs.is_empty() {
-            return Err("No tabs to close".to_string());
+            return Err(TabError::NoTabs);
         }
         self.close_tab(self.active_tab)
     }
 
-    pub fn switch_to_tab(&mut self, index: usize) -> Result<(), String> {
+    pub fn switch_to_tab(&mut self, index: usize) -> Result<(), TabError> {
         if index >= self.tabs.len() {
-            return Err("Tab index out of bounds".to_string());
+            return Err(TabError::IndexOutOfBounds(index));
         }
         self.active_tab = index;
         Ok(())
@@ -217,36 +398,64 @@ impl TabManager {
         self.active_tab
     }
 
-    pub fn find_tab_by_path(&self, path: &PathBuf) -> Option<usize> {
-        self.tabs.iter().position(|tab| tab.path == *path)
+    /// Shows the tab with `id` on the left and the active tab on the right.
+    pub fn split_with(&mut self, id: usize) {
+        if let Some(active) = self.get_active_tab()
+            && active.id != id
+        {
+            self.split = Some((id, active.id));
+        }
     }
 
-    pub fn has_unsaved_changes(&self) -> bool {
-        self.tabs.iter().any(|tab| tab.has_unsaved_changes)
+    pub fn close_split(&mut self) {
+        self.split = None;
+    }
+
+    /// Indices of the left and right panes, while both tabs are open and
+    /// one of them is active. Switching to a third tab hides the split
+    /// until one of the pair is active again.
+    pub fn split_panes(&self) -> Option<(usize, usize)> {
+        let (left, right) = self.split?;
+        let left = self.tabs.iter().position(|tab| tab.id == left)?;
+        let right = self.tabs.iter().position(|tab| tab.id == right)?;
+        (self.active_tab == left || self.active_tab == right).then_some((left, right))
+    }
+
+    /// Moves focus to the other side of the split; false without one.
+    pub fn focus_other_pane(&mut self) -> bool {
+        let Some((left, right)) = self.split_panes() else {
+            return false;
+        };
+        self.active_tab = if self.active_tab == left { right } else { left };
+        true
     }
 
-    pub fn get_unsaved_tabs(&self) -> Vec<&Tab> {
+    /// The tab editing `path`; views generated from it don't count.
+    pub fn find_tab_by_path(&self, path: &PathBuf) -> Option<usize> {
         self.tabs
             .iter()
-            .filter(|tab| tab.has_unsaved_changes)
-            .collect()
+            .position(|tab| !tab.generated && tab.path == *path)
+    }
+
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.tabs.iter().any(|tab| tab.has_unsaved_changes)
     }
 
-    pub fn save_active_tab(&mut self) -> Result<String, String> {
+    pub fn save_active_tab(&mut self) -> Result<String, TabError> {
         if let Some(tab) = self.get_active_tab_mut() {
             tab.mark_clean();
             Ok(tab.content.clone())
         } else {
-            Err("No active tab".to_string())
+            Err(TabError::NoActiveTab)
         }
     }
 
-    pub fn save_all_tabs(&mut self) -> Vec<(PathBuf, String)> {
+    pub fn save_all_tabs(&mut self) -> Vec<(PathBuf, Result<Vec<u8>, String>)> {
         let mut saved_files = Vec::new();
         for tab in &mut self.tabs {
-            if tab.has_unsaved_changes {
+            if tab.has_unsaved_changes && !tab.read_only && !tab.is_untitled() {
                 tab.mark_clean();
-                saved_files.push((tab.path.clone(), tab.content.clone()));
+                saved_files.push((tab.path.clone(), tab.encoded_content()));
             }
         }
         saved_files
@@ -389,6 +598,47 @@ mod tests {
         assert_eq!(manager.get_active_tab_index(), 0);
     }
 
+    #[test]
+    fn test_generated_views_never_stand_in_for_the_file() {
+        let mut manager = TabManager::new();
+        let path = PathBuf::from("notes.txt");
+        let view = manager.add_generated_tab(
+            "notes.txt (attributes)".to_string(),
+            path.clone(),
+            "mode: 644".to_string(),
+        );
+        assert!(manager.get_tab(view).unwrap().read_only);
+        assert_eq!(manager.find_tab_by_path(&path), None);
+
+        let file = manager.add_tab("notes.txt".to_string(), path.clone(), "text".to_string());
+        assert_ne!(file, view);
+        let tab = manager.get_tab(file).unwrap();
+        assert!(!tab.read_only && !tab.generated);
+        assert_eq!(manager.find_tab_by_path(&path), Some(file));
+
+        // Asking for the same view again goes back to it
+        let again = manager.add_generated_tab(
+            "notes.txt (attributes)".to_string(),
+            path.clone(),
+            "mode: 644".to_string(),
+        );
+        assert_eq!(again, view);
+        assert_eq!(manager.tab_count(), 2);
+    }
+
+    #[test]
+    fn test_untitled_tabs_stay_separate_and_are_not_saved_in_place() {
+        let mut manager = TabManager::new();
+        manager.add_untitled_tab("one".to_string());
+        manager.add_untitled_tab("two".to_string());
+        assert_eq!(manager.tab_count(), 2);
+
+        let tab = manager.get_active_tab().unwrap();
+        assert!(tab.is_untitled());
+        assert!(tab.has_unsaved_changes);
+        assert!(manager.save_all_tabs().is_empty());
+    }
+
     #[test]
     fn test_tab_manager_close_tab() {
         let mut manager = TabManager::new();
@@ -403,9 +653,43 @@ mod tests {
             "content2".to_string(),
         );
 
+        assert_eq!(manager.close_tab(5), Err(TabError::IndexOutOfBounds(5)));
         assert!(manager.close_tab(0).is_ok());
         assert_eq!(manager.tab_count(), 1);
         assert_eq!(manager.get_active_tab_index(), 0);
+
+        manager.get_active_tab_mut().unwrap().mark_dirty();
+        assert_eq!(manager.close_active_tab(), Err(TabError::HasUnsavedChanges));
+        assert!(manager.show_close_confirmation);
+        manager.confirm_close_tab();
+        assert_eq!(manager.close_active_tab(), Err(TabError::NoTabs));
+        assert_eq!(manager.save_active_tab(), Err(TabError::NoActiveTab));
+        assert_eq!(manager.switch_to_tab(0), Err(TabError::IndexOutOfBounds(0)));
+    }
+
+    #[test]
+    fn test_split_survives_other_tabs_and_ends_with_its_own() {
+        let mut manager = TabManager::new();
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            manager.add_tab(name.to_string(), PathBuf::from(name), String::new());
+        }
+        manager.switch_to_tab(1).unwrap();
+        let left = manager.get_tab(0).unwrap().id;
+        manager.split_with(left);
+        assert_eq!(manager.split_panes(), Some((0, 1)));
+
+        assert!(manager.focus_other_pane());
+        assert_eq!(manager.get_active_tab_index(), 0);
+
+        // A third tab hides the split, and closing it brings it back
+        manager.switch_to_tab(2).unwrap();
+        assert_eq!(manager.split_panes(), None);
+        assert!(!manager.focus_other_pane());
+        manager.close_active_tab().unwrap();
+        assert_eq!(manager.split_panes(), Some((0, 1)));
+
+        manager.close_tab(0).unwrap();
+        assert_eq!(manager.split_panes(), None);
     }
 
     #[test]
@@ -0,0 +1,154 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// A tail starts with this much of the end of the file, like `tail` does
+/// with its last lines
+pub const START_BYTES: u64 = 256 * 1024;
+
+/// Past this much text the oldest lines are dropped, so a chatty log can
+/// be left running
+pub const MAX_BYTES: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update {
+    Nothing,
+    Appended(String),
+    /// The file got shorter (truncated or rotated), so this replaces
+    /// everything shown so far
+    Restarted(String),
+}
+
+/// Follows a growing file by reading whatever was appended since the last
+/// poll.
+#[derive(Debug, Clone)]
+pub struct Tail {
+    offset: u64,
+    // The start of a character cut off at the end of the last read
+    partial: Vec<u8>,
+    /// Keep the view at the bottom as lines arrive
+    pub following: bool,
+}
+
+impl Tail {
+    /// Starts following `path`, returning the text it already ends with
+    /// from the first whole line in the last `START_BYTES`.
+    pub fn open(path: &Path) -> io::Result<(Self, String)> {
+        let mut file = File::open(path)?;
+        let start = file.metadata()?.len().saturating_sub(START_BYTES);
+        file.seek(SeekFrom::Start(start))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let mut tail = Self {
+            offset: start + bytes.len() as u64,
+            partial: Vec::new(),
+            following: true,
+        };
+        if start > 0 {
+            let first_line = bytes.iter().position(|&b| b == b'\n').map_or(0, |i| i + 1);
+            bytes.drain(..first_line);
+        }
+        let text = tail.decode(bytes);
+        Ok((tail, text))
+    }
+
+    pub fn poll(&mut self, path: &Path) -> io::Result<Update> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            let (tail, text) = Self::open(path)?;
+            *self = Self {
+                following: self.following,
+                ..tail
+            };
+            return Ok(Update::Restarted(text));
+        }
+        if len == self.offset {
+            return Ok(Update::Nothing);
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        self.offset += bytes.len() as u64;
+        let text = self.decode(bytes);
+        Ok(if text.is_empty() {
+            Update::Nothing
+        } else {
+            Update::Appended(text)
+        })
+    }
+
+    /// Turns newly read bytes into text, holding back a character that's
+    /// only partly written yet.
+    fn decode(&mut self, bytes: Vec<u8>) -> String {
+        let mut bytes = [std::mem::take(&mut self.partial), bytes].concat();
+        let complete = match std::str::from_utf8(&bytes) {
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            _ => bytes.len(),
+        };
+        self.partial = bytes.split_off(complete);
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+/// Drops whole lines from the front of `text` until it fits in `max` bytes.
+pub fn trim_front(text: &mut String, max: usize) {
+    if text.len() <= max {
+        return;
+    }
+    let excess = text.len() - max;
+    let cut = text.as_bytes()[excess..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(text.len(), |i| excess + i + 1);
+    text.drain(..cut);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    #[test]
+    fn test_follows_appends_and_truncation() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("app.log");
+        fs::write(&path, "one\n").unwrap();
+        let (mut tail, text) = Tail::open(&path).unwrap();
+        assert_eq!(text, "one\n");
+        assert_eq!(tail.poll(&path).unwrap(), Update::Nothing);
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        // "é" split across two writes comes out whole
+        file.write_all(b"two \xc3").unwrap();
+        assert_eq!(
+            tail.poll(&path).unwrap(),
+            Update::Appended("two ".to_string())
+        );
+        file.write_all(b"\xa9\n").unwrap();
+        assert_eq!(
+            tail.poll(&path).unwrap(),
+            Update::Appended("é\n".to_string())
+        );
+
+        fs::write(&path, "new\n").unwrap();
+        assert_eq!(
+            tail.poll(&path).unwrap(),
+            Update::Restarted("new\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_trim_front_keeps_whole_lines() {
+        let mut text = "aaaa\nbbbb\ncccc\n".to_string();
+        trim_front(&mut text, 100);
+        assert_eq!(text, "aaaa\nbbbb\ncccc\n");
+        trim_front(&mut text, 12);
+        assert_eq!(text, "bbbb\ncccc\n");
+        trim_front(&mut text, 2);
+        assert_eq!(text, "");
+    }
+}
@@ -0,0 +1,104 @@
+use std::ops::Bound;
+use std::path::Path;
+use syntect::highlighting::{Theme, ThemeSet};
+
+/// Used when the configured theme isn't one syntect or the theme
+/// directory provides.
+pub const DEFAULT_THEME: &str = "base16-ocean.dark";
+
+/// syntect's built-in themes plus every `.tmTheme` under `dir`, keyed by
+/// file stem. Files that fail to load are skipped and described in the
+/// returned errors so one broken theme doesn't hide the rest.
+pub fn load(dir: Option<&Path>) -> (ThemeSet, Vec<String>) {
+    let mut theme_set = ThemeSet::load_defaults();
+    let mut errors = Vec::new();
+    let Some(dir) = dir else {
+        return (theme_set, errors);
+    };
+
+    let paths = match ThemeSet::discover_theme_paths(dir) {
+        Ok(paths) => paths,
+        Err(e) => {
+            errors.push(format!("theme directory {}: {}", dir.display(), e));
+            return (theme_set, errors);
+        }
+    };
+    for path in paths {
+        let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        match ThemeSet::get_theme(&path) {
+            Ok(theme) => {
+                theme_set.themes.insert(name, theme);
+            }
+            Err(e) => errors.push(format!("theme {}: {}", path.display(), e)),
+        }
+    }
+    (theme_set, errors)
+}
+
+/// The theme called `name`, or the default one if there's no such theme.
+pub fn get<'a>(theme_set: &'a ThemeSet, name: &str) -> &'a Theme {
+    theme_set
+        .themes
+        .get(name)
+        .unwrap_or_else(|| &theme_set.themes[DEFAULT_THEME])
+}
+
+/// The name of the theme after `current` (or after where it would sort, if
+/// it isn't loaded) in alphabetical order, wrapping around to the first.
+pub fn next<'a>(theme_set: &'a ThemeSet, current: &str) -> &'a str {
+    let mut after = theme_set
+        .themes
+        .range::<str, _>((Bound::Excluded(current), Bound::Unbounded));
+    after
+        .next()
+        .or_else(|| theme_set.themes.iter().next())
+        .map_or(DEFAULT_THEME, |(name, _)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const THEME: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0"><dict>
+<key>name</key><string>Mine</string>
+<key>settings</key><array><dict><key>settings</key><dict>
+<key>foreground</key><string>#FF8800</string>
+</dict></dict></array>
+</dict></plist>"#;
+
+    #[test]
+    fn test_custom_themes_load_and_broken_ones_are_reported() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::write(dir.join("mine.tmTheme"), THEME).unwrap();
+        fs::write(dir.join("broken.tmTheme"), "not a plist").unwrap();
+
+        let (theme_set, errors) = load(Some(dir));
+        assert!(theme_set.themes.contains_key("mine"));
+        assert!(theme_set.themes.contains_key(DEFAULT_THEME));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("broken.tmTheme"));
+
+        assert_eq!(get(&theme_set, "mine").name.as_deref(), Some("Mine"));
+        assert!(std::ptr::eq(
+            get(&theme_set, "missing"),
+            &theme_set.themes[DEFAULT_THEME]
+        ));
+    }
+
+    #[test]
+    fn test_next_cycles_through_every_theme() {
+        let theme_set = ThemeSet::load_defaults();
+        let names: Vec<&str> = theme_set.themes.keys().map(String::as_str).collect();
+        let mut name = names[0];
+        for expected in names.iter().skip(1).chain(&names[..1]) {
+            name = next(&theme_set, name);
+            assert_eq!(name, *expected);
+        }
+        assert_eq!(next(&theme_set, "missing"), names[0]);
+    }
+}
@@ -0,0 +1,309 @@
+use crate::fileops;
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferKind {
+    Copy,
+    Move,
+}
+
+impl TransferKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            TransferKind::Copy => "copy",
+            TransferKind::Move => "move",
+        }
+    }
+
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            TransferKind::Copy => "Copied",
+            TransferKind::Move => "Moved",
+        }
+    }
+}
+
+/// What happens to one entry whose name is already taken in the
+/// destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Overwrite,
+    Skip,
+    /// Use the next free "copy" name, like Duplicate
+    Rename,
+}
+
+/// Chosen once when a batch starts: settle every collision the same way,
+/// or stop and ask at each one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    All(Resolution),
+    AskEach,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    Done,
+    /// Waiting on a `resolve` for the entry that would land on this path
+    Ask(PathBuf),
+}
+
+/// Copies or moves a batch of entries into one directory, one entry at a
+/// time, pausing wherever the policy says to ask.
+#[derive(Debug)]
+pub struct Transfer {
+    pub kind: TransferKind,
+    pub dest: PathBuf,
+    pending: VecDeque<PathBuf>,
+    policy: ConflictPolicy,
+    /// Sources that made it to the destination
+    pub finished: Vec<PathBuf>,
+    pub skipped: usize,
+    pub errors: Vec<String>,
+}
+
+impl Transfer {
+    pub fn new(kind: TransferKind, sources: Vec<PathBuf>, dest: PathBuf) -> Self {
+        Self {
+            kind,
+            dest,
+            pending: sources.into(),
+            policy: ConflictPolicy::AskEach,
+            finished: Vec::new(),
+            skipped: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    /// How many entries would land on a name that's already taken.
+    pub fn conflicts(&self) -> usize {
+        self.pending
+            .iter()
+            .filter(|source| {
+                let target = self.target_for(source);
+                !fileops::same_entry(&target, source) && exists(&target)
+            })
+            .count()
+    }
+
+    pub fn set_policy(&mut self, policy: ConflictPolicy) {
+        self.policy = policy;
+    }
+
+    /// Works through the batch until it's finished or an entry needs asking
+    /// about.
+    pub fn run(&mut self) -> Step {
+        while let Some(source) = self.pending.front().cloned() {
+            let target = self.target_for(&source);
+            let resolution = if fileops::same_entry(&target, &source) {
+                // Onto itself: a copy gets a new name, a move has nothing to do
+                match self.kind {
+                    TransferKind::Copy => Some(Resolution::Rename),
+                    TransferKind::Move => Some(Resolution::Skip),
+                }
+            } else if exists(&target) {
+                match self.policy {
+                    ConflictPolicy::All(resolution) => Some(resolution),
+                    ConflictPolicy::AskEach => return Step::Ask(target),
+                }
+            } else {
+                None
+            };
+            self.pending.pop_front();
+            self.transfer(&source, target, resolution);
+        }
+        Step::Done
+    }
+
+    /// Settles the entry `run` stopped at, then carries on with the rest.
+    pub fn resolve(&mut self, resolution: Resolution) -> Step {
+        if let Some(source) = self.pending.pop_front() {
+            let target = self.target_for(&source);
+            self.transfer(&source, target, Some(resolution));
+        }
+        self.run()
+    }
+
+    fn target_for(&self, source: &Path) -> PathBuf {
+        match source.file_name() {
+            Some(name) => self.dest.join(name),
+            None => self.dest.clone(),
+        }
+    }
+
+    fn transfer(&mut self, source: &Path, target: PathBuf, resolution: Option<Resolution>) {
+        let target = match resolution {
+            Some(Resolution::Skip) => {
+                self.skipped += 1;
+                return;
+            }
+            Some(Resolution::Rename) => {
+                let name = source.file_name().unwrap_or_default().to_string_lossy();
+                fileops::copy_name(&self.dest, &name)
+            }
+            Some(Resolution::Overwrite) | None => target,
+        };
+
+        // Compared as resolved paths, so `..` or a symlink in either can't
+        // hide that they overlap
+        let dest = fs::canonicalize(&self.dest).unwrap_or_else(|_| fileops::resolve(&self.dest));
+        let result = if dest.starts_with(fileops::resolve(source)) {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a directory can't go inside itself",
+            ))
+        } else if resolution == Some(Resolution::Overwrite)
+            && fileops::resolve(source).starts_with(fileops::resolve(&target))
+        {
+            // Removing the target first would take the source with it
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "it would replace itself",
+            ))
+        } else if resolution == Some(Resolution::Overwrite) {
+            fileops::remove(&target).and_then(|()| self.transfer_to(source, &target))
+        } else {
+            self.transfer_to(source, &target)
+        };
+        match result {
+            Ok(()) => self.finished.push(source.to_path_buf()),
+            Err(e) => self.errors.push(format!(
+                "Could not {} {}: {}",
+                self.kind.label(),
+                source.display(),
+                e
+            )),
+        }
+    }
+
+    fn transfer_to(&self, source: &Path, target: &Path) -> io::Result<()> {
+        match self.kind {
+            TransferKind::Copy => fileops::copy_recursive(source, target),
+            TransferKind::Move => match fs::rename(source, target) {
+                // Across filesystems a move has to be a copy and a delete
+                Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                    fileops::copy_recursive(source, target)?;
+                    fileops::remove(source)
+                }
+                result => result,
+            },
+        }
+    }
+}
+
+fn exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// `from` holding a, b and c, and `to` already holding a and b, both
+    /// in a directory removed when the `TempDir` drops.
+    fn scratch() -> (TempDir, PathBuf, PathBuf) {
+        let root = tempfile::tempdir().unwrap();
+        let (from, to) = (root.path().join("from"), root.path().join("to"));
+        fs::create_dir_all(&from).unwrap();
+        fs::create_dir_all(&to).unwrap();
+        for file in ["a.txt", "b.txt", "c.txt"] {
+            fs::write(from.join(file), "new").unwrap();
+        }
+        fs::write(to.join("a.txt"), "old").unwrap();
+        fs::write(to.join("b.txt"), "old").unwrap();
+        (root, from, to)
+    }
+
+    fn sources(from: &Path) -> Vec<PathBuf> {
+        ["a.txt", "b.txt", "c.txt"]
+            .iter()
+            .map(|file| from.join(file))
+            .collect()
+    }
+
+    #[test]
+    fn test_one_policy_covers_the_whole_batch() {
+        for (policy, a_contents, renamed) in [
+            (Resolution::Skip, "old", false),
+            (Resolution::Overwrite, "new", false),
+            (Resolution::Rename, "old", true),
+        ] {
+            let (_root, from, to) = scratch();
+            let mut transfer = Transfer::new(TransferKind::Copy, sources(&from), to.clone());
+            assert_eq!(transfer.conflicts(), 2);
+            transfer.set_policy(ConflictPolicy::All(policy));
+            assert_eq!(transfer.run(), Step::Done);
+
+            assert_eq!(fs::read_to_string(to.join("a.txt")).unwrap(), a_contents);
+            assert_eq!(fs::read_to_string(to.join("c.txt")).unwrap(), "new");
+            assert_eq!(to.join("a copy.txt").exists(), renamed);
+            let skipped = if policy == Resolution::Skip { 2 } else { 0 };
+            assert_eq!(transfer.skipped, skipped);
+            assert_eq!(transfer.finished.len(), 3 - skipped);
+        }
+    }
+
+    #[test]
+    fn test_ask_each_stops_at_every_collision() {
+        let (_root, from, to) = scratch();
+        let mut transfer = Transfer::new(TransferKind::Move, sources(&from), to.clone());
+
+        assert_eq!(transfer.run(), Step::Ask(to.join("a.txt")));
+        assert_eq!(
+            transfer.resolve(Resolution::Overwrite),
+            Step::Ask(to.join("b.txt"))
+        );
+        assert_eq!(transfer.resolve(Resolution::Skip), Step::Done);
+
+        assert_eq!(fs::read_to_string(to.join("a.txt")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(to.join("b.txt")).unwrap(), "old");
+        assert!(!from.join("a.txt").exists());
+        assert!(from.join("b.txt").exists());
+        assert!(!from.join("c.txt").exists());
+    }
+
+    #[test]
+    fn test_same_directory_and_nesting() {
+        let (_root, from, _) = scratch();
+        let mut copy = Transfer::new(TransferKind::Copy, vec![from.join("a.txt")], from.clone());
+        assert_eq!(copy.conflicts(), 0);
+        assert_eq!(copy.run(), Step::Done);
+        assert!(from.join("a copy.txt").exists());
+
+        let mut nest = Transfer::new(TransferKind::Move, vec![from.clone()], from.join("inner"));
+        fs::create_dir(from.join("inner")).unwrap();
+        assert_eq!(nest.run(), Step::Done);
+        assert_eq!(nest.errors.len(), 1);
+        assert!(from.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_a_symlinked_destination_is_still_the_source() {
+        let (root, from, _) = scratch();
+        let link = root.path().join("link");
+        std::os::unix::fs::symlink(&from, &link).unwrap();
+
+        // link/a.txt is from/a.txt, so overwriting it would delete the source
+        let mut onto_itself =
+            Transfer::new(TransferKind::Move, vec![from.join("a.txt")], link.clone());
+        assert_eq!(onto_itself.conflicts(), 0);
+        onto_itself.set_policy(ConflictPolicy::All(Resolution::Overwrite));
+        assert_eq!(onto_itself.run(), Step::Done);
+        assert_eq!(fs::read_to_string(from.join("a.txt")).unwrap(), "new");
+
+        let mut copy = Transfer::new(TransferKind::Copy, vec![from.join("b.txt")], link.clone());
+        assert_eq!(copy.run(), Step::Done);
+        assert!(from.join("b copy.txt").exists());
+
+        fs::create_dir(from.join("inner")).unwrap();
+        let mut nest = Transfer::new(TransferKind::Copy, vec![from.clone()], link.join("inner"));
+        assert_eq!(nest.run(), Step::Done);
+        assert_eq!(nest.errors.len(), 1);
+        assert!(!from.join("inner/from").exists());
+    }
+}
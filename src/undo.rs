@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Most steps an editor buffer keeps; the oldest go first
+const LIMIT: usize = 200;
+/// Most steps, each way, written out when a file is closed
+const SAVED_STEPS: usize = 50;
+/// Most snapshot text written out per file, undo and redo together
+const SAVED_BYTES: usize = 4 * 1024 * 1024;
+
+/// A buffer and its cursor as they were before an edit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub content: String,
+    pub cursor_line: usize,
+    pub cursor_col: usize,
+    pub scroll_offset: usize,
+}
+
+/// What an edit did, so runs of the same small edit undo together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// One character typed
+    Typing,
+    /// One character deleted with Backspace
+    Deleting,
+    /// Anything else: a new line, a paste, a revert
+    Other,
+}
+
+/// Undo and redo for one buffer, as whole snapshots. Typing a word, or
+/// backspacing over one, is a single step.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    // The kind of edit a step is still collecting
+    #[serde(skip)]
+    run: Option<Edit>,
+}
+
+impl History {
+    /// Notes an edit made to `before`. Typing right after typing (or
+    /// deleting after deleting) joins the step already recorded. Any edit
+    /// drops what could be redone.
+    pub fn record(&mut self, before: Snapshot, edit: Edit) {
+        self.redo.clear();
+        if edit == Edit::Other || self.run != Some(edit) {
+            self.undo.push(before);
+            if self.undo.len() > LIMIT {
+                self.undo.remove(0);
+            }
+        }
+        self.run = (edit != Edit::Other).then_some(edit);
+    }
+
+    /// Makes the next edit a step of its own, e.g. after the cursor moved.
+    pub fn break_run(&mut self) {
+        self.run = None;
+    }
+
+    /// The state before the last step, with `current` kept for redo.
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        self.run = None;
+        Some(previous)
+    }
+
+    /// The state the last undo left, with `current` kept for undo.
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        self.run = None;
+        Some(next)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.undo.is_empty() && self.redo.is_empty()
+    }
+
+    /// The steps nearest the current text, at most `steps` each way and
+    /// `bytes` of snapshots in all; undo gets the budget first.
+    fn trimmed(&self, steps: usize, bytes: usize) -> History {
+        let mut budget = bytes;
+        let mut newest = |stack: &[Snapshot]| {
+            let kept = stack
+                .iter()
+                .rev()
+                .take(steps)
+                .take_while(
+                    |snapshot| match budget.checked_sub(snapshot.content.len()) {
+                        Some(left) => {
+                            budget = left;
+                            true
+                        }
+                        None => false,
+                    },
+                )
+                .count();
+            stack[stack.len() - kept..].to_vec()
+        };
+        History {
+            undo: newest(&self.undo),
+            redo: newest(&self.redo),
+            run: None,
+        }
+    }
+}
+
+/// A history kept on disk, with a hash of the text it leads up to.
+#[derive(Serialize, Deserialize)]
+struct Saved {
+    content_hash: String,
+    history: History,
+}
+
+/// `~/.local/state/ls-pretty/undo` on Linux, the data directory elsewhere.
+pub fn store_dir() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_dir)
+        .map(|dir| dir.join("ls-pretty").join("undo"))
+}
+
+/// Keeps the newest of `history` for the file at `path`, whose text is
+/// `content`. An empty history forgets whatever was kept before.
+pub fn save(dir: &Path, path: &Path, content: &str, history: &History) -> io::Result<()> {
+    let file = store_file(dir, path);
+    if history.is_empty() {
+        return match fs::remove_file(&file) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    fs::create_dir_all(dir)?;
+    let saved = Saved {
+        content_hash: hash(content),
+        history: history.trimmed(SAVED_STEPS, SAVED_BYTES),
+    };
+    fs::write(file, serde_json::to_string(&saved)?)
+}
+
+/// The history kept for `path`, as long as the file still reads `content`;
+/// one for text changed since would undo into the wrong places.
+pub fn load(dir: &Path, path: &Path, content: &str) -> Option<History> {
+    let saved: Saved =
+        serde_json::from_str(&fs::read_to_string(store_file(dir, path)).ok()?).ok()?;
+    (saved.content_hash == hash(content)).then_some(saved.history)
+}
+
+// One file per path, named by its hash so any path makes a valid file name
+fn store_file(dir: &Path, path: &Path) -> PathBuf {
+    dir.join(format!("{}.json", hash(&path.to_string_lossy())))
+}
+
+fn hash(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(content: &str) -> Snapshot {
+        Snapshot {
+            content: content.to_string(),
+            cursor_line: 0,
+            cursor_col: content.chars().count(),
+            scroll_offset: 0,
+        }
+    }
+
+    #[test]
+    fn test_typing_is_one_step_and_new_lines_are_their_own() {
+        let mut history = History::default();
+        history.record(at(""), Edit::Typing);
+        history.record(at("a"), Edit::Typing);
+        history.record(at("ab"), Edit::Other);
+        history.record(at("ab\n"), Edit::Typing);
+
+        assert_eq!(history.undo(at("ab\nc")), Some(at("ab\n")));
+        assert_eq!(history.undo(at("ab\n")), Some(at("ab")));
+        assert_eq!(history.undo(at("ab")), Some(at("")));
+        assert_eq!(history.undo(at("")), None);
+    }
+
+    #[test]
+    fn test_moving_the_cursor_splits_a_run() {
+        let mut history = History::default();
+        history.record(at(""), Edit::Typing);
+        history.break_run();
+        history.record(at("a"), Edit::Typing);
+        history.record(at("ab"), Edit::Deleting);
+        history.record(at("a"), Edit::Deleting);
+
+        assert_eq!(history.undo(at("")), Some(at("ab")));
+        assert_eq!(history.undo(at("ab")), Some(at("a")));
+        assert_eq!(history.undo(at("a")), Some(at("")));
+    }
+
+    #[test]
+    fn test_redo_replays_undone_steps_until_the_next_edit() {
+        let mut history = History::default();
+        history.record(at(""), Edit::Other);
+        history.record(at("a"), Edit::Other);
+
+        assert_eq!(history.undo(at("ab")), Some(at("a")));
+        assert_eq!(history.undo(at("a")), Some(at("")));
+        assert_eq!(history.redo(at("")), Some(at("a")));
+        assert_eq!(history.redo(at("a")), Some(at("ab")));
+        assert_eq!(history.redo(at("ab")), None);
+
+        assert_eq!(history.undo(at("ab")), Some(at("a")));
+        history.record(at("a"), Edit::Typing);
+        assert_eq!(history.redo(at("ax")), None);
+    }
+
+    #[test]
+    fn test_trimming_keeps_the_steps_nearest_the_current_text() {
+        let mut history = History::default();
+        for text in ["", "a", "ab", "abc"] {
+            history.record(at(text), Edit::Other);
+        }
+        history.undo(at("abcd"));
+
+        let mut trimmed = history.trimmed(2, 100);
+        assert_eq!(trimmed.undo(at("abc")), Some(at("ab")));
+        assert_eq!(trimmed.undo(at("ab")), Some(at("a")));
+        assert_eq!(trimmed.undo(at("a")), None);
+
+        // Undo's three snapshots use up 3 bytes, leaving none for "abcd"
+        let trimmed = history.trimmed(10, 3);
+        assert_eq!(trimmed.undo.len(), 3);
+        assert!(trimmed.redo.is_empty());
+
+        // Past the budget, older steps go even if they'd fit
+        let trimmed = history.trimmed(10, 2);
+        assert_eq!(trimmed.undo, vec![at("ab")]);
+    }
+
+    #[test]
+    fn test_saved_history_only_loads_for_unchanged_text() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let path = Path::new("/projects/notes.txt");
+        let mut history = History::default();
+        history.record(at(""), Edit::Other);
+
+        save(dir, path, "a", &history).unwrap();
+        let mut loaded = load(dir, path, "a").unwrap();
+        assert_eq!(loaded.undo(at("a")), Some(at("")));
+        assert!(load(dir, path, "changed since").is_none());
+        assert!(load(dir, Path::new("/projects/other.txt"), "a").is_none());
+
+        save(dir, path, "a", &History::default()).unwrap();
+        assert!(load(dir, path, "a").is_none());
+    }
+}
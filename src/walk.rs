@@ -0,0 +1,64 @@
+use ignore::WalkBuilder;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Lists files under `root` on a background thread for the flat view,
+/// honouring `.gitignore` and going at most `max_depth` levels deep. Paths
+/// stream through the returned channel in file name order and it
+/// disconnects when the walk finishes. Dropping the receiver stops the walk.
+pub fn spawn_walk(root: PathBuf, show_hidden: bool, max_depth: usize) -> Receiver<PathBuf> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let walker = WalkBuilder::new(&root)
+            .hidden(!show_hidden)
+            .max_depth(Some(max_depth))
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build();
+        for entry in walker.flatten() {
+            if entry.file_type().is_some_and(|t| t.is_file())
+                && sender.send(entry.into_path()).is_err()
+            {
+                return;
+            }
+        }
+    });
+
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_walk_lists_nested_files_within_depth() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("a/b/c")).unwrap();
+        fs::write(root.join("top.txt"), "").unwrap();
+        fs::write(root.join(".hidden"), "").unwrap();
+        fs::write(root.join("a/b/mid.txt"), "").unwrap();
+        fs::write(root.join("a/b/c/deep.txt"), "").unwrap();
+
+        let relative = |show_hidden, depth| -> Vec<String> {
+            spawn_walk(root.to_path_buf(), show_hidden, depth)
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .into_owned()
+                })
+                .collect()
+        };
+
+        assert_eq!(relative(false, 3), vec!["a/b/mid.txt", "top.txt"]);
+        assert_eq!(
+            relative(true, 4),
+            vec![".hidden", "a/b/c/deep.txt", "a/b/mid.txt", "top.txt"]
+        );
+    }
+}